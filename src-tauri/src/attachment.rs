@@ -0,0 +1,207 @@
+// 通用附件（PDF/Office/压缩包/音视频）：类型映射、大小策略与 Markdown 插入语法
+// 类型以文件头为准，扩展名仅用于细化 OLE 等无法从内容区分的容器格式。
+
+use serde::Serialize;
+
+use crate::upload_policy::{self, UploadPolicy};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentKind {
+  Image,
+  Document,
+  Archive,
+  Audio,
+  Video,
+}
+
+// (MIME, 类别, 默认扩展名)
+const ATTACHMENT_TYPES: &[(&str, AttachmentKind, &str)] = &[
+  ("image/png", AttachmentKind::Image, "png"),
+  ("image/jpeg", AttachmentKind::Image, "jpg"),
+  ("image/gif", AttachmentKind::Image, "gif"),
+  ("image/webp", AttachmentKind::Image, "webp"),
+  ("image/avif", AttachmentKind::Image, "avif"),
+  ("image/svg+xml", AttachmentKind::Image, "svg"),
+  ("application/pdf", AttachmentKind::Document, "pdf"),
+  ("application/vnd.openxmlformats-officedocument.wordprocessingml.document", AttachmentKind::Document, "docx"),
+  ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet", AttachmentKind::Document, "xlsx"),
+  ("application/vnd.openxmlformats-officedocument.presentationml.presentation", AttachmentKind::Document, "pptx"),
+  ("application/msword", AttachmentKind::Document, "doc"),
+  ("application/vnd.ms-excel", AttachmentKind::Document, "xls"),
+  ("application/vnd.ms-powerpoint", AttachmentKind::Document, "ppt"),
+  ("application/zip", AttachmentKind::Archive, "zip"),
+  ("application/x-7z-compressed", AttachmentKind::Archive, "7z"),
+  ("application/vnd.rar", AttachmentKind::Archive, "rar"),
+  ("application/gzip", AttachmentKind::Archive, "gz"),
+  ("audio/mpeg", AttachmentKind::Audio, "mp3"),
+  ("audio/mp4", AttachmentKind::Audio, "m4a"),
+  ("audio/wav", AttachmentKind::Audio, "wav"),
+  ("audio/ogg", AttachmentKind::Audio, "ogg"),
+  ("audio/flac", AttachmentKind::Audio, "flac"),
+  ("video/mp4", AttachmentKind::Video, "mp4"),
+  ("video/quicktime", AttachmentKind::Video, "mov"),
+  ("video/webm", AttachmentKind::Video, "webm"),
+  ("video/x-matroska", AttachmentKind::Video, "mkv"),
+];
+
+// 各类别默认大小上限；图床配置中的 max_bytes 优先
+pub fn default_max_bytes(kind: AttachmentKind) -> u64 {
+  const MB: u64 = 1024 * 1024;
+  match kind {
+    AttachmentKind::Image => upload_policy::DEFAULT_MAX_BYTES,
+    AttachmentKind::Document => 50 * MB,
+    AttachmentKind::Archive | AttachmentKind::Audio => 100 * MB,
+    AttachmentKind::Video => 500 * MB,
+  }
+}
+
+pub fn kind_of(mime: &str) -> Option<AttachmentKind> {
+  ATTACHMENT_TYPES.iter().find(|(m, _, _)| *m == mime).map(|(_, k, _)| *k)
+}
+
+pub fn ext_of(mime: &str) -> Option<&'static str> {
+  ATTACHMENT_TYPES.iter().find(|(m, _, _)| *m == mime).map(|(_, _, e)| *e)
+}
+
+// 结合文件名嗅探：OLE 复合文档按扩展名区分 doc/xls/ppt，zip 内容无法识别时按扩展名保留 Office 类型
+pub fn sniff_attachment(bytes: &[u8], file_name: &str) -> Option<&'static str> {
+  let sniffed = upload_policy::sniff_mime(bytes)?;
+  let ext = file_name.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+  match sniffed {
+    "application/x-ole-storage" => match ext.as_str() {
+      "doc" => Some("application/msword"),
+      "xls" => Some("application/vnd.ms-excel"),
+      "ppt" => Some("application/vnd.ms-powerpoint"),
+      _ => None,
+    },
+    "application/zip" => match ext.as_str() {
+      "docx" | "xlsx" | "pptx" => ATTACHMENT_TYPES.iter().find(|(_, _, e)| *e == ext).map(|(m, _, _)| *m),
+      _ => Some(sniffed),
+    },
+    other => Some(other),
+  }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentInfo {
+  pub kind: AttachmentKind,
+  pub content_type: String,
+  pub ext: String,
+}
+
+// 附件策略：嗅探 + 白名单（全部已知类型或图床配置指定）+ 按类别的大小上限
+pub fn check_attachment(
+  bytes: Vec<u8>,
+  file_name: &str,
+  declared: Option<&str>,
+  allowed_types: Option<&[String]>,
+  max_bytes: Option<u64>,
+) -> Result<(AttachmentInfo, Vec<u8>), String> {
+  let sniffed = sniff_attachment(&bytes, file_name);
+  let kind = sniffed.and_then(kind_of);
+  let default_types: Vec<String> = ATTACHMENT_TYPES.iter().map(|(m, _, _)| m.to_string()).collect();
+  let mut policy = UploadPolicy::from_profile(Some(allowed_types.unwrap_or(&default_types)), max_bytes);
+  if max_bytes.unwrap_or(0) == 0 {
+    if let Some(k) = kind { policy.max_bytes = default_max_bytes(k); }
+  }
+  let checked = policy.check(bytes, sniffed, declared)?;
+  let kind = kind.ok_or_else(|| format!("upload policy: type {} not allowed", checked.content_type))?;
+  let ext = ext_of(&checked.content_type).unwrap_or("bin").to_string();
+  Ok((AttachmentInfo { kind, content_type: checked.content_type, ext }, checked.bytes))
+}
+
+// 按类型生成插入语法：图片 ![]()、文档/压缩包 []()、音视频使用 HTML 标签
+pub fn markdown_for(kind: AttachmentKind, name: &str, url: &str) -> String {
+  // 含空格或括号的本地路径使用 <> 包裹，保证 CommonMark 可解析
+  let md_url = if url.contains(|c: char| c.is_whitespace() || c == '(' || c == ')') { format!("<{}>", url) } else { url.to_string() };
+  let label = name.replace('[', "\\[").replace(']', "\\]");
+  let attr = url.replace('&', "&amp;").replace('"', "&quot;");
+  match kind {
+    AttachmentKind::Image => format!("![{}]({})", label, md_url),
+    AttachmentKind::Document | AttachmentKind::Archive => format!("[{}]({})", label, md_url),
+    AttachmentKind::Audio => format!("<audio controls src=\"{}\"></audio>", attr),
+    AttachmentKind::Video => format!("<video controls src=\"{}\"></video>", attr),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const OLE: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1, 0, 0];
+
+  #[test]
+  fn sniffing() {
+    let cases: &[(&[u8], &str, Option<&str>)] = &[
+      (OLE, "report.DOC", Some("application/msword")),
+      (OLE, "table.xls", Some("application/vnd.ms-excel")),
+      (OLE, "slides.ppt", Some("application/vnd.ms-powerpoint")),
+      (OLE, "mail.msg", None),
+      (OLE, "noext", None),
+      (b"PK\x03\x04....", "files.zip", Some("application/zip")),
+      // zip 中没能找到 word/ 目录（如目录在 64KB 之后）：按扩展名保留 Office 类型
+      (b"PK\x03\x04....", "letter.docx", Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document")),
+      (b"PK\x03\x04....", "sheet.xlsx", Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")),
+      (b"PK\x03\x04....word/document.xml", "renamed.zip", Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document")),
+      // 扩展名不能把其他类型伪装成 Office 文档
+      (b"%PDF-1.7", "fake.docx", Some("application/pdf")),
+      (b"\x00\x00\x00\x20ftypM4A \x00\x00\x00\x00", "song.m4a", Some("audio/mp4")),
+      (b"\x00\x00\x00\x20ftypM4A \x00\x00\x00\x00", "song.mp4", Some("audio/mp4")),
+      (b"\x00\x00\x00\x18ftypisom\x00\x00\x00\x00", "clip.m4a", Some("video/mp4")),
+      (b"plain text", "notes.pdf", None),
+    ];
+    for (bytes, name, want) in cases {
+      assert_eq!(sniff_attachment(bytes, name), *want, "{}", name);
+    }
+  }
+
+  #[test]
+  fn size_limits() {
+    const MB: u64 = 1024 * 1024;
+    assert_eq!(default_max_bytes(AttachmentKind::Image), upload_policy::DEFAULT_MAX_BYTES);
+    assert_eq!(default_max_bytes(AttachmentKind::Document), 50 * MB);
+    assert_eq!(default_max_bytes(AttachmentKind::Archive), 100 * MB);
+    assert_eq!(default_max_bytes(AttachmentKind::Audio), 100 * MB);
+    assert_eq!(default_max_bytes(AttachmentKind::Video), 500 * MB);
+
+    // 比图片上限大的 PDF 按文档的默认上限放行
+    let mut pdf = b"%PDF-1.7\n".to_vec();
+    pdf.resize((upload_policy::DEFAULT_MAX_BYTES + MB) as usize, b' ');
+    let (info, bytes) = check_attachment(pdf.clone(), "big.pdf", None, None, None).unwrap();
+    assert_eq!((info.kind, info.content_type.as_str(), info.ext.as_str()), (AttachmentKind::Document, "application/pdf", "pdf"));
+    assert_eq!(bytes.len(), pdf.len());
+    assert!(check_attachment(pdf.clone(), "big.pdf", None, None, Some(0)).is_ok());
+    // 图片超过图片上限
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    png.resize(pdf.len(), 0);
+    assert!(check_attachment(png, "big.png", None, None, None).unwrap_err().contains("too large"));
+    // 图床配置的 max_bytes 优先于按类别的默认值
+    assert!(check_attachment(pdf, "big.pdf", None, None, Some(MB)).unwrap_err().contains("too large"));
+    assert!(check_attachment(b"ID3\x04 tiny".to_vec(), "a.mp3", None, None, Some(MB)).is_ok());
+  }
+
+  #[test]
+  fn allowed_types() {
+    let only_pdf = vec!["application/pdf".to_string()];
+    assert!(check_attachment(b"%PDF-1.7".to_vec(), "a.pdf", None, Some(&only_pdf), None).is_ok());
+    assert!(check_attachment(b"PK\x03\x04".to_vec(), "a.zip", None, Some(&only_pdf), None).unwrap_err().contains("not allowed"));
+    assert!(check_attachment(OLE.to_vec(), "a.msg", None, None, None).is_err());
+    let (info, _) = check_attachment(OLE.to_vec(), "a.xls", None, None, None).unwrap();
+    assert_eq!((info.kind, info.ext.as_str()), (AttachmentKind::Document, "xls"));
+  }
+
+  #[test]
+  fn markdown() {
+    assert_eq!(markdown_for(AttachmentKind::Image, "a.png", "https://x/a.png"), "![a.png](https://x/a.png)");
+    assert_eq!(markdown_for(AttachmentKind::Document, "My Report.pdf", "files/My Report.pdf"), "[My Report.pdf](<files/My Report.pdf>)");
+    assert_eq!(markdown_for(AttachmentKind::Archive, "a (1).zip", "files/a(1).zip"), "[a (1).zip](<files/a(1).zip>)");
+    assert_eq!(markdown_for(AttachmentKind::Document, "[draft] v2.docx", "d.docx"), r"[\[draft\] v2.docx](d.docx)");
+    assert_eq!(
+      markdown_for(AttachmentKind::Audio, "a.mp3", r#"https://x/a.mp3?a=1&b="2""#),
+      r#"<audio controls src="https://x/a.mp3?a=1&amp;b=&quot;2&quot;"></audio>"#,
+    );
+    assert_eq!(markdown_for(AttachmentKind::Video, "v.mp4", "media/my clip.mp4"), r#"<video controls src="media/my clip.mp4"></video>"#);
+  }
+}
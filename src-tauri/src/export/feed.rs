@@ -0,0 +1,581 @@
+// Atom / RSS 订阅与 sitemap.xml：条目取 front matter 的标题、日期、摘要，正文截取到 <!-- more -->
+// 单独生成时按文件修改时间与大小缓存已渲染的条目，仅重新渲染变更的笔记；输出内容不变时不改写文件
+
+use chrono::{DateTime, FixedOffset, Offset};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::export::{encode_href, is_markdown, parse_date, split_fragment};
+use crate::markdown;
+use crate::publish::{post_meta, slugify, PostMeta, PublishDoc};
+use crate::transform::{self, TransformOptions};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedFormat {
+  Atom,
+  Rss,
+  #[default]
+  Both,
+}
+
+// 站点导出与单独生成共用的订阅配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedOptions {
+  // 站点根地址，如 https://example.com/blog
+  pub base_url: String,
+  #[serde(default)]
+  pub title: Option<String>,
+  #[serde(default)]
+  pub description: Option<String>,
+  #[serde(default)]
+  pub author: Option<String>,
+  #[serde(default)]
+  pub format: FeedFormat,
+  // 订阅中的条目数上限，缺省 20；sitemap 不受限制
+  #[serde(default)]
+  pub limit: Option<usize>,
+  // 日期未带时区时使用的偏移，如 +08:00，缺省 UTC
+  #[serde(default)]
+  pub timezone: Option<String>,
+  #[serde(default = "crate::publish::default_true")]
+  pub sitemap: bool,
+}
+
+impl FeedOptions {
+  pub fn base(&self) -> &str { self.base_url.trim().trim_end_matches('/') }
+
+  pub fn offset(&self) -> Result<FixedOffset, String> {
+    match self.timezone.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+      None => Ok(chrono::Utc.fix()),
+      Some(tz) => {
+        let probe = format!("2000-01-01T00:00:00{}", if tz.eq_ignore_ascii_case("z") { "Z" } else { tz });
+        DateTime::parse_from_rfc3339(&probe).map(|d| *d.offset()).map_err(|_| format!("invalid timezone: {}", tz))
+      }
+    }
+  }
+
+  pub fn validate(&self) -> Result<(), String> {
+    let u = url::Url::parse(self.base()).map_err(|e| format!("invalid base url {}: {e}", self.base_url))?;
+    if !matches!(u.scheme(), "http" | "https") { return Err(format!("base url must be http(s): {}", self.base_url)); }
+    self.offset().map(|_| ())
+  }
+}
+
+// 已渲染的条目；日期为 RFC3339
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedEntry {
+  pub link: String,
+  pub title: String,
+  pub published: String,
+  pub updated: String,
+  pub summary: Option<String>,
+  pub content: String,
+  pub categories: Vec<String>,
+}
+
+fn to_rfc3339(s: Option<&str>, tz: FixedOffset) -> Option<DateTime<FixedOffset>> {
+  let (dt, off) = parse_date(s?)?;
+  let off = off.unwrap_or(tz);
+  dt.and_local_timezone(off).single()
+}
+
+fn time_of(t: SystemTime, tz: FixedOffset) -> DateTime<FixedOffset> {
+  DateTime::<chrono::Utc>::from(t).with_timezone(&tz)
+}
+
+// <!-- more --> 之前的部分；无标记时返回 None（代码块中的标记不算）
+pub fn split_more(body: &str) -> Option<&str> {
+  let mut at = 0;
+  for (line, code) in transform::code_lines(body) {
+    let t = line.trim();
+    // <!--> 同时满足首尾条件，内容区间为空
+    let inner = t.get(4..t.len().saturating_sub(3)).unwrap_or("");
+    if !code && t.starts_with("<!--") && t.ends_with("-->") && inner.trim().eq_ignore_ascii_case("more") {
+      return Some(&body[..at]);
+    }
+    at += line.len();
+  }
+  None
+}
+
+fn has_scheme(u: &str) -> bool {
+  u.find(':').is_some_and(|i| i > 1 && u[..i].chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')))
+}
+
+// 阅读器不一定支持 xml:base，正文中的相对地址按条目地址改为绝对地址
+fn absolutize(body: &str, page: &str) -> String {
+  let Ok(base) = url::Url::parse(page) else { return body.to_string() };
+  let mut refs = markdown::find_images(body);
+  refs.extend(markdown::find_links(body));
+  let edits = refs.into_iter()
+    .filter(|r| !r.url.is_empty() && !r.url.starts_with('#') && !has_scheme(&r.url))
+    .filter_map(|r| base.join(&r.url).ok().map(|u| (r.range, u.to_string())))
+    .collect();
+  markdown::replace_ranges(body, edits)
+}
+
+// body 为已转换、站内链接已改写的正文；link 为条目的绝对地址
+pub fn build_entry(meta: &PostMeta, body: &str, link: &str, mtime: SystemTime, tz: FixedOffset) -> FeedEntry {
+  let published = to_rfc3339(meta.date.as_deref(), tz).unwrap_or_else(|| time_of(mtime, tz));
+  let updated = to_rfc3339(meta.updated.as_deref(), tz).filter(|u| *u >= published).unwrap_or(published);
+  let excerpt = split_more(body).unwrap_or(body);
+  let content = markdown::render_html(&absolutize(excerpt, link));
+  FeedEntry {
+    link: link.to_string(),
+    title: meta.title.clone(),
+    published: published.to_rfc3339(),
+    updated: updated.to_rfc3339(),
+    summary: meta.excerpt.clone(),
+    content,
+    categories: meta.tags.iter().chain(meta.categories.iter()).fold(Vec::new(), |mut v, c| {
+      if !v.contains(c) { v.push(c.clone()); }
+      v
+    }),
+  }
+}
+
+// XML 转义，同时去掉 XML 1.0 不允许的控制字符
+fn xml_escape(s: &str) -> String {
+  let mut out = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      '\'' => out.push_str("&apos;"),
+      '\t' | '\n' | '\r' => out.push(c),
+      c if (c as u32) < 0x20 || c == '\u{fffe}' || c == '\u{ffff}' => {}
+      c => out.push(c),
+    }
+  }
+  out
+}
+
+fn parse_rfc3339(s: &str) -> Option<DateTime<FixedOffset>> {
+  DateTime::parse_from_rfc3339(s).ok()
+}
+
+// 订阅整体的更新时间取条目中最新者，保证内容不变时输出不变
+fn feed_updated(entries: &[&FeedEntry], tz: FixedOffset) -> DateTime<FixedOffset> {
+  entries.iter().filter_map(|e| parse_rfc3339(&e.updated)).max()
+    .unwrap_or_else(|| time_of(UNIX_EPOCH, tz))
+}
+
+fn feed_title(opts: &FeedOptions) -> String {
+  opts.title.clone().filter(|s| !s.trim().is_empty()).unwrap_or_else(|| opts.base().to_string())
+}
+
+pub fn atom(opts: &FeedOptions, entries: &[&FeedEntry], tz: FixedOffset) -> String {
+  let base = opts.base();
+  let title = feed_title(opts);
+  let mut x = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+  x.push_str(&format!("  <title>{}</title>\n", xml_escape(&title)));
+  if let Some(d) = opts.description.as_deref().filter(|s| !s.trim().is_empty()) {
+    x.push_str(&format!("  <subtitle>{}</subtitle>\n", xml_escape(d)));
+  }
+  x.push_str(&format!("  <id>{}/</id>\n", xml_escape(base)));
+  x.push_str(&format!("  <link rel=\"alternate\" type=\"text/html\" href=\"{}/\"/>\n", xml_escape(base)));
+  x.push_str(&format!("  <link rel=\"self\" type=\"application/atom+xml\" href=\"{}/atom.xml\"/>\n", xml_escape(base)));
+  x.push_str(&format!("  <updated>{}</updated>\n", feed_updated(entries, tz).to_rfc3339()));
+  // 条目不单独声明作者，订阅级 author 为必填
+  let author = opts.author.clone().filter(|s| !s.trim().is_empty()).unwrap_or_else(|| title.clone());
+  x.push_str(&format!("  <author><name>{}</name></author>\n", xml_escape(&author)));
+  x.push_str("  <generator>flymd</generator>\n");
+  for e in entries {
+    x.push_str("  <entry>\n");
+    x.push_str(&format!("    <title>{}</title>\n", xml_escape(&e.title)));
+    x.push_str(&format!("    <id>{}</id>\n", xml_escape(&e.link)));
+    x.push_str(&format!("    <link rel=\"alternate\" type=\"text/html\" href=\"{}\"/>\n", xml_escape(&e.link)));
+    x.push_str(&format!("    <published>{}</published>\n", e.published));
+    x.push_str(&format!("    <updated>{}</updated>\n", e.updated));
+    for c in &e.categories { x.push_str(&format!("    <category term=\"{}\"/>\n", xml_escape(c))); }
+    if let Some(s) = &e.summary { x.push_str(&format!("    <summary type=\"text\">{}</summary>\n", xml_escape(s))); }
+    x.push_str(&format!("    <content type=\"html\">{}</content>\n", xml_escape(&e.content)));
+    x.push_str("  </entry>\n");
+  }
+  x.push_str("</feed>\n");
+  x
+}
+
+pub fn rss(opts: &FeedOptions, entries: &[&FeedEntry], tz: FixedOffset) -> String {
+  let base = opts.base();
+  let title = feed_title(opts);
+  // channel 的 description 为必填
+  let desc = opts.description.clone().filter(|s| !s.trim().is_empty()).unwrap_or_else(|| title.clone());
+  let mut x = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n<channel>\n");
+  x.push_str(&format!("  <title>{}</title>\n", xml_escape(&title)));
+  x.push_str(&format!("  <link>{}/</link>\n", xml_escape(base)));
+  x.push_str(&format!("  <description>{}</description>\n", xml_escape(&desc)));
+  x.push_str(&format!("  <atom:link href=\"{}/rss.xml\" rel=\"self\" type=\"application/rss+xml\"/>\n", xml_escape(base)));
+  x.push_str(&format!("  <lastBuildDate>{}</lastBuildDate>\n", feed_updated(entries, tz).to_rfc2822()));
+  x.push_str("  <generator>flymd</generator>\n");
+  for e in entries {
+    x.push_str("  <item>\n");
+    x.push_str(&format!("    <title>{}</title>\n", xml_escape(&e.title)));
+    x.push_str(&format!("    <link>{}</link>\n", xml_escape(&e.link)));
+    x.push_str(&format!("    <guid isPermaLink=\"true\">{}</guid>\n", xml_escape(&e.link)));
+    if let Some(d) = parse_rfc3339(&e.published) { x.push_str(&format!("    <pubDate>{}</pubDate>\n", d.to_rfc2822())); }
+    for c in &e.categories { x.push_str(&format!("    <category>{}</category>\n", xml_escape(c))); }
+    x.push_str(&format!("    <description>{}</description>\n", xml_escape(&e.content)));
+    x.push_str("  </item>\n");
+  }
+  x.push_str("</channel>\n</rss>\n");
+  x
+}
+
+// (地址, 最后修改时间 RFC3339)
+pub fn sitemap(urls: &[(String, Option<String>)]) -> String {
+  let mut x = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+  for (loc, lastmod) in urls {
+    x.push_str(&format!("  <url><loc>{}</loc>", xml_escape(loc)));
+    if let Some(m) = lastmod { x.push_str(&format!("<lastmod>{}</lastmod>", m)); }
+    x.push_str("</url>\n");
+  }
+  x.push_str("</urlset>\n");
+  x
+}
+
+fn write_if_changed(path: &Path, data: &str) -> Result<bool, String> {
+  if std::fs::read(path).is_ok_and(|old| old == data.as_bytes()) { return Ok(false); }
+  if let Some(dir) = path.parent() {
+    std::fs::create_dir_all(dir).map_err(|e| format!("create dir {} error: {e}", dir.display()))?;
+  }
+  std::fs::write(path, data).map_err(|e| format!("write {} error: {e}", path.display()))?;
+  Ok(true)
+}
+
+// 按日期倒序写出订阅与 sitemap；extra_urls 为首页、标签页等非条目页面。返回 (已改写, 未变化) 的文件名
+pub fn write_feeds(
+  out: &Path,
+  opts: &FeedOptions,
+  entries: &[FeedEntry],
+  extra_urls: &[String],
+) -> Result<(Vec<String>, Vec<String>), String> {
+  let tz = opts.offset()?;
+  let mut sorted: Vec<&FeedEntry> = entries.iter().collect();
+  sorted.sort_by(|a, b| {
+    parse_rfc3339(&b.published).cmp(&parse_rfc3339(&a.published)).then_with(|| a.link.cmp(&b.link))
+  });
+  let limited = &sorted[..sorted.len().min(opts.limit.unwrap_or(20).max(1))];
+
+  let mut files: Vec<(&str, String)> = Vec::new();
+  if opts.format != FeedFormat::Rss { files.push(("atom.xml", atom(opts, limited, tz))); }
+  if opts.format != FeedFormat::Atom { files.push(("rss.xml", rss(opts, limited, tz))); }
+  if opts.sitemap {
+    let mut urls: Vec<(String, Option<String>)> = extra_urls.iter().map(|u| (u.clone(), None)).collect();
+    urls.extend(sorted.iter().map(|e| (e.link.clone(), Some(e.updated.clone()))));
+    files.push(("sitemap.xml", sitemap(&urls)));
+  }
+  let (mut written, mut unchanged) = (Vec::new(), Vec::new());
+  for (name, data) in files {
+    if write_if_changed(&out.join(name), &data)? { written.push(name.to_string()) } else { unchanged.push(name.to_string()) }
+  }
+  Ok((written, unchanged))
+}
+
+// ---- 单独生成（博客工作流）----
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedReq {
+  #[serde(flatten)]
+  pub feed: FeedOptions,
+  pub files: Vec<String>,
+  pub out_dir: String,
+  // 计算 {path} 的根目录，缺省为各文件所在目录
+  #[serde(default)]
+  pub library_dir: Option<String>,
+  // 条目地址模板（相对 base_url）：{path} 去扩展名的相对路径、{slug}、{year}/{month}/{day}；缺省 {path}.html
+  #[serde(default)]
+  pub link_pattern: Option<String>,
+  #[serde(default)]
+  pub include_drafts: bool,
+  #[serde(default)]
+  pub transform: TransformOptions,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedResult {
+  pub entries: usize,
+  // 本次重新渲染与复用缓存的笔记数
+  pub rendered: usize,
+  pub reused: usize,
+  pub written: Vec<String>,
+  pub unchanged: Vec<String>,
+}
+
+const CACHE_FILE: &str = ".flymd-feed.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FeedCache {
+  // 影响条目内容的配置摘要，变化时缓存整体失效
+  key: String,
+  // 笔记地址表的摘要：任一笔记的地址变化时，其他笔记中指向它的链接要重新解析
+  #[serde(default)]
+  links: String,
+  notes: HashMap<String, CachedNote>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedNote {
+  mtime_ms: u128,
+  size: u64,
+  // 草稿为 None
+  entry: Option<FeedEntry>,
+}
+
+fn cache_key(req: &FeedReq) -> String {
+  let s = format!("{}|{:?}|{:?}|{}|{:?}", req.feed.base(), req.feed.timezone, req.link_pattern, req.include_drafts, req.transform);
+  hex::encode(Sha256::digest(s.as_bytes()))
+}
+
+fn note_link(req: &FeedReq, src: &Path, meta: &PostMeta, date: Option<DateTime<FixedOffset>>) -> String {
+  let root = req.library_dir.as_deref().map(Path::new).or_else(|| src.parent());
+  let rel = root.and_then(|r| src.strip_prefix(r).ok()).unwrap_or(src);
+  let rel = rel.with_extension("");
+  let path = rel.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect::<Vec<_>>().join("/");
+  let stem = src.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+  let slug = meta.slug.as_deref().map(|s| s.trim_matches('/').to_string())
+    .or_else(|| Some(slugify(&meta.title)).filter(|s| !s.is_empty()))
+    .unwrap_or(stem);
+  let pattern = req.link_pattern.as_deref().filter(|s| !s.trim().is_empty()).unwrap_or("{path}.html");
+  let d = date.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default();
+  let (y, m, day) = (d.get(0..4).unwrap_or(""), d.get(5..7).unwrap_or(""), d.get(8..10).unwrap_or(""));
+  let rel = pattern
+    .replace("{path}", &encode_href(&path))
+    .replace("{slug}", &encode_href(&slug))
+    .replace("{year}", y)
+    .replace("{month}", m)
+    .replace("{day}", day);
+  format!("{}/{}", req.feed.base(), rel.trim_start_matches('/'))
+}
+
+// 指向同批笔记的相对 .md 链接改为条目地址
+fn note_links(body: &str, doc_dir: Option<&Path>, by_path: &HashMap<PathBuf, String>) -> String {
+  let mut edits = Vec::new();
+  for r in markdown::find_links(body) {
+    if has_scheme(&r.url) || r.url.starts_with('#') { continue; }
+    let (path, suffix) = split_fragment(&r.url);
+    let decoded = percent_encoding::percent_decode_str(path).decode_utf8_lossy().to_string();
+    let target = Path::new(&decoded);
+    if !is_markdown(target) { continue; }
+    let abs = if target.is_absolute() { target.to_path_buf() } else { doc_dir.map(|d| d.join(target)).unwrap_or_default() };
+    let Some(link) = std::fs::canonicalize(&abs).ok().and_then(|a| by_path.get(&a)) else { continue };
+    edits.push((r.range, format!("{}{}", link, suffix)));
+  }
+  markdown::replace_ranges(body, edits)
+}
+
+fn stat(path: &Path) -> Result<(u128, u64, SystemTime), String> {
+  let md = std::fs::metadata(path).map_err(|e| format!("stat {} error: {e}", path.display()))?;
+  let mtime = md.modified().unwrap_or(UNIX_EPOCH);
+  let ms = mtime.duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+  Ok((ms, md.len(), mtime))
+}
+
+pub fn export_feed_blocking(req: &FeedReq) -> Result<FeedResult, String> {
+  req.feed.validate()?;
+  let tz = req.feed.offset()?;
+  let out = PathBuf::from(&req.out_dir);
+  std::fs::create_dir_all(&out).map_err(|e| format!("create out dir error: {e}"))?;
+  let cache_path = out.join(CACHE_FILE);
+  let key = cache_key(req);
+  let old: FeedCache = std::fs::read(&cache_path).ok()
+    .and_then(|b| serde_json::from_slice(&b).ok())
+    .filter(|c: &FeedCache| c.key == key)
+    .unwrap_or_default();
+
+  // 1. 先确定全部笔记的地址以便解析 wiki 链接：未变化的笔记取缓存中的地址，其余读取元信息
+  struct Note { src: PathBuf, mtime: SystemTime, ms: u128, size: u64, cached: Option<CachedNote>, parsed: Option<(PostMeta, String, String)> }
+  let load = |src: &Path| -> Result<(PostMeta, String), String> {
+    let text = std::fs::read_to_string(src).map_err(|e| format!("read {} error: {e}", src.display()))?;
+    let (front, body) = markdown::parse_front_matter(&text);
+    let doc = PublishDoc { doc_path: Some(src.to_string_lossy().to_string()), ..Default::default() };
+    Ok((post_meta(&front, body, &doc), body.to_string()))
+  };
+  // 同一文件重复列出时只收录一次
+  let mut seen = HashSet::new();
+  let files: Vec<&String> = req.files.iter().filter(|f| seen.insert(f.as_str())).collect();
+  let mut cache = FeedCache { key, links: String::new(), notes: HashMap::new() };
+  let mut notes = Vec::new();
+  let mut links: HashMap<String, String> = HashMap::new();
+  let mut by_path: HashMap<PathBuf, String> = HashMap::new();
+  // 条目地址同时是 Atom <id> 与 RSS <guid>，两篇笔记共用地址时阅读器会把它们当作同一条
+  let mut owners: HashMap<String, PathBuf> = HashMap::new();
+  for f in &files {
+    let src = PathBuf::from(f);
+    let (ms, size, mtime) = stat(&src)?;
+    let id = src.to_string_lossy().to_string();
+    let (link, cached, parsed) = match old.notes.get(&id).filter(|c| c.mtime_ms == ms && c.size == size) {
+      Some(c) => (c.entry.as_ref().map(|e| e.link.clone()), Some(c.clone()), None),
+      None => {
+        let (meta, body) = load(&src)?;
+        if meta.draft && !req.include_drafts {
+          cache.notes.insert(id, CachedNote { mtime_ms: ms, size, entry: None });
+          continue;
+        }
+        let date = to_rfc3339(meta.date.as_deref(), tz).or_else(|| Some(time_of(mtime, tz)));
+        let link = note_link(req, &src, &meta, date);
+        (Some(link.clone()), None, Some((meta, body, link)))
+      }
+    };
+    if let Some(link) = link {
+      if let Some(other) = owners.insert(link.clone(), src.clone()) {
+        return Err(format!(
+          "{} and {} have the same feed link {}; give one of them a different slug or change the link pattern",
+          other.display(), src.display(), link
+        ));
+      }
+      if let Some(stem) = src.file_stem() { links.insert(stem.to_string_lossy().to_lowercase(), link.clone()); }
+      by_path.insert(std::fs::canonicalize(&src).unwrap_or_else(|_| src.clone()), link);
+    }
+    notes.push(Note { src, mtime, ms, size, cached, parsed });
+  }
+  let mut table: Vec<String> = links.iter().map(|(k, v)| format!("{}\t{}", k, v))
+    .chain(by_path.iter().map(|(k, v)| format!("{}\t{}", k.display(), v)))
+    .collect();
+  table.sort();
+  cache.links = hex::encode(Sha256::digest(table.join("\n").as_bytes()));
+  let links_changed = cache.links != old.links;
+
+  // 2. 渲染变更的笔记；地址表变化时缓存的条目也要重新渲染
+  let resolve = |name: &str| links.get(&name.to_lowercase()).cloned();
+  let (mut rendered, mut reused) = (0, 0);
+  for n in notes {
+    let id = n.src.to_string_lossy().to_string();
+    let (meta, body, link) = match (n.cached, n.parsed) {
+      (Some(c), _) if !links_changed || c.entry.is_none() => {
+        cache.notes.insert(id, c);
+        reused += 1;
+        continue;
+      }
+      (Some(c), _) => {
+        let (meta, body) = load(&n.src)?;
+        (meta, body, c.entry.map(|e| e.link).unwrap_or_default())
+      }
+      (None, Some(parsed)) => parsed,
+      (None, None) => continue,
+    };
+    let body = transform::apply(&body, &req.transform, &resolve);
+    let body = note_links(&body, n.src.parent(), &by_path);
+    let entry = build_entry(&meta, &body, &link, n.mtime, tz);
+    cache.notes.insert(id, CachedNote { mtime_ms: n.ms, size: n.size, entry: Some(entry) });
+    rendered += 1;
+  }
+
+  // 3. 输出
+  let entries: Vec<FeedEntry> = files.iter()
+    .filter_map(|f| cache.notes.get(f.as_str()).and_then(|c| c.entry.clone()))
+    .collect();
+  let home = vec![format!("{}/", req.feed.base())];
+  let (written, unchanged) = write_feeds(&out, &req.feed, &entries, &home)?;
+  let data = serde_json::to_vec_pretty(&cache).map_err(|e| format!("serialize cache error: {e}"))?;
+  std::fs::write(&cache_path, data).map_err(|e| format!("write {} error: {e}", cache_path.display()))?;
+
+  Ok(FeedResult { entries: entries.len(), rendered, reused, written, unchanged })
+}
+
+#[tauri::command]
+pub async fn export_feed(req: FeedReq) -> Result<FeedResult, String> {
+  tauri::async_runtime::spawn_blocking(move || export_feed_blocking(&req))
+    .await
+    .map_err(|e| format!("join error: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn more_marker() {
+    assert_eq!(split_more("intro\n<!-- more -->\nrest\n"), Some("intro\n"));
+    assert_eq!(split_more("a\n<!--MORE-->\n"), Some("a\n"));
+    for s in ["<!-->\n", "x\n<!--->\n", "<!---->\n", "```\n<!-- more -->\n```\n"] {
+      assert_eq!(split_more(s), None, "{:?}", s);
+    }
+  }
+
+  #[test]
+  fn cache_follows_link_changes() {
+    let dir = std::env::temp_dir().join(format!("flymd-feed-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let out = dir.join("out");
+    std::fs::create_dir_all(&out).unwrap();
+    let (a, b) = (dir.join("a.md"), dir.join("b.md"));
+    std::fs::write(&a, "---\ntitle: A\ndate: 2025-01-02\n---\nsee [b](b.md) and [[b]]\n").unwrap();
+    std::fs::write(&b, "---\ntitle: B\nslug: first\ndate: 2025-01-01\n---\nb\n").unwrap();
+    let req = FeedReq {
+      feed: FeedOptions {
+        base_url: "https://ex.com".into(), title: None, description: None, author: None,
+        format: FeedFormat::Atom, limit: None, timezone: None, sitemap: false,
+      },
+      files: vec![a.to_string_lossy().to_string(), b.to_string_lossy().to_string()],
+      out_dir: out.to_string_lossy().to_string(),
+      library_dir: None,
+      link_pattern: Some("{slug}/".into()),
+      include_drafts: false,
+      transform: TransformOptions { wiki_links: true, ..Default::default() },
+    };
+    let atom = || std::fs::read_to_string(out.join("atom.xml")).unwrap();
+    let r = export_feed_blocking(&req).unwrap();
+    assert_eq!((r.rendered, r.reused), (2, 0));
+    assert!(atom().contains("https://ex.com/first/"));
+
+    let r = export_feed_blocking(&req).unwrap();
+    assert_eq!((r.rendered, r.reused), (0, 2));
+
+    // 只改 b 的 slug：a 的正文链接也要更新
+    std::fs::write(&b, "---\ntitle: B\nslug: second-one\ndate: 2025-01-01\n---\nb\n").unwrap();
+    let r = export_feed_blocking(&req).unwrap();
+    assert_eq!((r.rendered, r.reused), (2, 0));
+    assert!(!atom().contains("https://ex.com/first/"));
+    assert_eq!(atom().matches("https://ex.com/second-one/").count(), 4);
+    let _ = std::fs::remove_dir_all(&dir);
+  }
+
+  #[test]
+  fn rejects_duplicate_links() {
+    let dir = std::env::temp_dir().join(format!("flymd-feed-dup-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let out = dir.join("out");
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    let (a, b) = (dir.join("a.md"), dir.join("sub/b.md"));
+    std::fs::write(&a, "---\ntitle: Same Title\n---\na\n").unwrap();
+    std::fs::write(&b, "---\ntitle: same title\n---\nb\n").unwrap();
+    let mut req = FeedReq {
+      feed: FeedOptions {
+        base_url: "https://ex.com".into(), title: None, description: None, author: None,
+        format: FeedFormat::Rss, limit: None, timezone: None, sitemap: false,
+      },
+      files: vec![a.to_string_lossy().to_string(), b.to_string_lossy().to_string()],
+      out_dir: out.to_string_lossy().to_string(),
+      library_dir: Some(dir.to_string_lossy().to_string()),
+      link_pattern: Some("{slug}/".into()),
+      include_drafts: false,
+      transform: TransformOptions::default(),
+    };
+    let err = export_feed_blocking(&req).unwrap_err();
+    assert!(err.contains("https://ex.com/same-title/") && err.contains("b.md"), "{err}");
+    assert!(!out.join("rss.xml").exists());
+
+    // 按路径生成地址时不冲突；同一文件重复列出只收录一次
+    req.link_pattern = None;
+    req.files.push(a.to_string_lossy().to_string());
+    let r = export_feed_blocking(&req).unwrap();
+    let rss = std::fs::read_to_string(out.join("rss.xml")).unwrap();
+    assert_eq!(rss.matches(">https://ex.com/a.html</guid>").count(), 1);
+    assert!(rss.contains(">https://ex.com/sub/b.html</guid>"));
+    assert_eq!(r.rendered, 2);
+    let _ = std::fs::remove_dir_all(&dir);
+  }
+}
@@ -0,0 +1,83 @@
+// 导出公共部分：遍历库目录收集 Markdown 文档、HTML 转义、站内相对链接
+
+pub mod feed;
+pub mod site;
+pub mod ssg;
+pub mod styled;
+
+use std::path::{Path, PathBuf};
+
+pub fn is_markdown(p: &Path) -> bool {
+  matches!(
+    p.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref(),
+    Some("md" | "markdown" | "mdown" | "mkd")
+  )
+}
+
+// 递归收集库目录下的 Markdown 文件（跳过隐藏目录与 skip 目录），按路径排序保证输出稳定
+pub fn collect_markdown(root: &Path, skip: Option<&Path>) -> Result<Vec<PathBuf>, String> {
+  let mut out = Vec::new();
+  let mut stack = vec![root.to_path_buf()];
+  while let Some(dir) = stack.pop() {
+    let rd = std::fs::read_dir(&dir).map_err(|e| format!("read dir {} error: {e}", dir.display()))?;
+    for ent in rd.flatten() {
+      let p = ent.path();
+      let hidden = ent.file_name().to_string_lossy().starts_with('.');
+      let Ok(ft) = ent.file_type() else { continue };
+      if ft.is_dir() {
+        if hidden || skip.is_some_and(|s| p == s) || ent.file_name() == "node_modules" { continue; }
+        stack.push(p);
+      } else if ft.is_file() && !hidden && is_markdown(&p) {
+        out.push(p);
+      }
+    }
+  }
+  out.sort();
+  Ok(out)
+}
+
+pub fn escape_html(s: &str) -> String {
+  let mut out = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      '\'' => out.push_str("&#39;"),
+      _ => out.push(c),
+    }
+  }
+  out
+}
+
+const HREF_SET: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+  .remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+// 站内相对路径（/ 分隔）逐段编码为 href
+pub fn encode_href(rel: &str) -> String {
+  rel.split('/').map(|seg| percent_encoding::utf8_percent_encode(seg, HREF_SET).to_string()).collect::<Vec<_>>().join("/")
+}
+
+// 从深度为 depth 的页面回到站点根目录的前缀，如 "../../"
+pub fn root_prefix(depth: usize) -> String {
+  "../".repeat(depth)
+}
+
+// 去掉查询串与锚点，返回 (路径, "#锚点"/"?查询" 后缀)
+pub fn split_fragment(url: &str) -> (&str, &str) {
+  match url.find(['#', '?']) {
+    Some(i) => (&url[..i], &url[i..]),
+    None => (url, ""),
+  }
+}
+
+// front matter 日期：RFC3339 / YYYY-MM-DD[ HH:MM[:SS]]，返回本地时间与（若有）时区偏移
+pub fn parse_date(s: &str) -> Option<(chrono::NaiveDateTime, Option<chrono::FixedOffset>)> {
+  let s = s.trim();
+  if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) { return Some((dt.naive_local(), Some(*dt.offset()))); }
+  for fmt in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y/%m/%d %H:%M:%S", "%Y/%m/%d %H:%M"] {
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, fmt) { return Some((dt, None)); }
+  }
+  ["%Y-%m-%d", "%Y/%m/%d"].iter().find_map(|f| chrono::NaiveDate::parse_from_str(s, f).ok()).and_then(|d| d.and_hms_opt(0, 0, 0)).map(|dt| (dt, None))
+}
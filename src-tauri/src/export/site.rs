@@ -0,0 +1,465 @@
+// 整库导出为静态网站：逐篇渲染 Markdown 套用模板，按目录树生成导航，复制引用的本地图片，
+// 生成首页与标签页。输出目录可直接本地打开，或用现有 S3 上传整站
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::feed::{self, FeedOptions};
+use super::{collect_markdown, encode_href, escape_html, is_markdown, root_prefix, split_fragment};
+use crate::fsutil::sanitize_file_name;
+use crate::markdown;
+use crate::publish::{post_meta, resolve_local_path, slugify, PostMeta, PublishDoc};
+use crate::transform::{self, TransformOptions};
+
+// 模板占位符：{{site_title}} {{title}} {{root}} {{nav}} {{meta}} {{content}}
+const DEFAULT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{title}} - {{site_title}}</title>
+<link rel="stylesheet" href="{{root}}assets/style.css">
+</head>
+<body>
+<nav class="sidebar">
+<a class="site-title" href="{{root}}index.html">{{site_title}}</a>
+<a class="tags-link" href="{{root}}tags/index.html">标签</a>
+{{nav}}
+</nav>
+<main>
+<article>
+{{meta}}
+{{content}}
+</article>
+</main>
+</body>
+</html>
+"#;
+
+const DEFAULT_CSS: &str = r#"body{margin:0;display:flex;font-family:-apple-system,BlinkMacSystemFont,"Segoe UI","PingFang SC","Microsoft YaHei",sans-serif;color:#24292f;line-height:1.7}
+.sidebar{width:260px;flex-shrink:0;height:100vh;position:sticky;top:0;overflow:auto;padding:16px;box-sizing:border-box;background:#f6f8fa;border-right:1px solid #d0d7de;font-size:14px}
+.sidebar ul{list-style:none;padding-left:14px;margin:4px 0}
+.sidebar>ul{padding-left:0}
+.sidebar a{color:#24292f;text-decoration:none}
+.sidebar a:hover{text-decoration:underline}
+.sidebar li.current>a{font-weight:600;color:#0969da}
+.sidebar summary{cursor:pointer;color:#57606a}
+.site-title{display:block;font-size:18px;font-weight:600;margin-bottom:8px}
+.tags-link{display:block;margin-bottom:12px;color:#57606a!important}
+main{flex:1;min-width:0;padding:32px 48px}
+article{max-width:860px;margin:0 auto}
+.post-meta{color:#57606a;font-size:14px;margin-bottom:16px}
+.tag{display:inline-block;margin-right:6px;padding:0 8px;border-radius:10px;background:#ddf4ff;color:#0969da;text-decoration:none;font-size:13px}
+.post-list{list-style:none;padding:0}
+.post-list li{display:flex;justify-content:space-between;gap:12px;padding:6px 0;border-bottom:1px solid #eaeef2}
+.post-list time{color:#57606a;font-size:13px;white-space:nowrap}
+img{max-width:100%}
+pre{background:#f6f8fa;padding:12px;overflow:auto;border-radius:6px}
+code{font-family:SFMono-Regular,Consolas,monospace;font-size:90%}
+table{border-collapse:collapse}
+th,td{border:1px solid #d0d7de;padding:4px 10px}
+blockquote{margin:0;padding:0 1em;color:#57606a;border-left:4px solid #d0d7de}
+@media(max-width:760px){body{display:block}.sidebar{position:static;width:auto;height:auto}main{padding:16px}}
+"#;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteExportReq {
+  pub library_dir: String,
+  pub out_dir: String,
+  // 站点标题，缺省为库目录名
+  #[serde(default)]
+  pub title: Option<String>,
+  // 自定义 HTML 模板文件，占位符同 DEFAULT_TEMPLATE
+  #[serde(default)]
+  pub template_path: Option<String>,
+  // 默认跳过 front matter 中 draft: true 的文档
+  #[serde(default)]
+  pub include_drafts: bool,
+  #[serde(default)]
+  pub transform: TransformOptions,
+  // 配置后同时生成 atom.xml / rss.xml / sitemap.xml
+  #[serde(default)]
+  pub feed: Option<FeedOptions>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteExportResult {
+  pub out_dir: String,
+  pub pages: usize,
+  pub images: usize,
+  pub tags: usize,
+  pub skipped_drafts: usize,
+  // 本次改写的订阅与 sitemap 文件
+  pub feeds: Vec<String>,
+}
+
+struct Page {
+  src: PathBuf,
+  // 输出相对路径（/ 分隔），如 notes/a.html
+  out_rel: String,
+  meta: PostMeta,
+  body: String,
+}
+
+impl Page {
+  fn depth(&self) -> usize { self.out_rel.matches('/').count() }
+}
+
+#[derive(Default)]
+struct NavNode {
+  dirs: BTreeMap<String, NavNode>,
+  pages: Vec<(String, String)>,
+}
+
+impl NavNode {
+  fn insert(&mut self, dirs: &[&str], title: String, out_rel: String) {
+    match dirs.split_first() {
+      Some((d, rest)) => self.dirs.entry(d.to_string()).or_default().insert(rest, title, out_rel),
+      None => self.pages.push((title, out_rel)),
+    }
+  }
+
+  fn render(&self, root: &str, current: Option<&str>, out: &mut String) {
+    out.push_str("<ul>");
+    for (title, rel) in &self.pages {
+      let cls = if current == Some(rel.as_str()) { " class=\"current\"" } else { "" };
+      out.push_str(&format!("<li{}><a href=\"{}{}\">{}</a></li>", cls, root, encode_href(rel), escape_html(title)));
+    }
+    for (name, node) in &self.dirs {
+      // 当前页所在目录展开，其余折叠
+      let open = current.is_some_and(|c| node.contains(c));
+      out.push_str(&format!("<li><details{}><summary>{}</summary>", if open { " open" } else { "" }, escape_html(name)));
+      node.render(root, current, out);
+      out.push_str("</details></li>");
+    }
+    out.push_str("</ul>");
+  }
+
+  fn contains(&self, rel: &str) -> bool {
+    self.pages.iter().any(|(_, r)| r == rel) || self.dirs.values().any(|d| d.contains(rel))
+  }
+}
+
+// 单遍替换 {{name}}，避免正文中出现的占位符文本被二次替换
+fn fill_template(tpl: &str, vars: &[(&str, &str)]) -> String {
+  let mut out = String::with_capacity(tpl.len() + vars.iter().map(|(_, v)| v.len()).sum::<usize>());
+  let mut rest = tpl;
+  while let Some(i) = rest.find("{{") {
+    out.push_str(&rest[..i]);
+    let after = &rest[i + 2..];
+    match after.find("}}") {
+      Some(j) => {
+        let key = after[..j].trim();
+        match vars.iter().find(|(k, _)| *k == key) {
+          Some((_, v)) => out.push_str(v),
+          None => out.push_str(&rest[i..i + 2 + j + 2]),
+        }
+        rest = &after[j + 2..];
+      }
+      None => { out.push_str(&rest[i..]); rest = ""; }
+    }
+  }
+  out.push_str(rest);
+  out
+}
+
+fn is_external(url: &str) -> bool {
+  let u = url.trim();
+  u.is_empty() || u.starts_with('#') || u.starts_with("//") || u.contains("://")
+    || u.to_ascii_lowercase().starts_with("mailto:") || u.to_ascii_lowercase().starts_with("data:")
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<(), String> {
+  if let Some(dir) = path.parent() {
+    std::fs::create_dir_all(dir).map_err(|e| format!("create dir {} error: {e}", dir.display()))?;
+  }
+  std::fs::write(path, data).map_err(|e| format!("write {} error: {e}", path.display()))
+}
+
+fn date_of(meta: &PostMeta) -> String {
+  meta.date.as_deref().map(|d| d.chars().take(10).collect()).unwrap_or_default()
+}
+
+struct Site<'a> {
+  title: String,
+  template: String,
+  out: &'a Path,
+  nav: NavNode,
+  tag_files: HashMap<String, String>,
+}
+
+impl Site<'_> {
+  fn tag_href(&self, root: &str, tag: &str) -> Option<String> {
+    self.tag_files.get(&tag.to_lowercase()).map(|f| format!("{}tags/{}", root, encode_href(f)))
+  }
+
+  fn write_page(&self, out_rel: &str, title: &str, meta_html: &str, content: &str, current: Option<&str>) -> Result<(), String> {
+    let root = root_prefix(out_rel.matches('/').count());
+    let mut nav = String::new();
+    self.nav.render(&root, current, &mut nav);
+    let html = fill_template(&self.template, &[
+      ("site_title", &escape_html(&self.title)),
+      ("title", &escape_html(title)),
+      ("root", &root),
+      ("nav", &nav),
+      ("meta", meta_html),
+      ("content", content),
+    ]);
+    write_file(&self.out.join(out_rel), html.as_bytes())
+  }
+
+  fn post_list(&self, root: &str, pages: &[&Page]) -> String {
+    let mut s = String::from("<ul class=\"post-list\">");
+    for p in pages {
+      s.push_str(&format!(
+        "<li><a href=\"{}{}\">{}</a><time>{}</time></li>",
+        root, encode_href(&p.out_rel), escape_html(&p.meta.title), escape_html(&date_of(&p.meta))
+      ));
+    }
+    s.push_str("</ul>");
+    s
+  }
+}
+
+// 生成页与文档页冲突时（根目录 index.md、tags/、assets/ 目录），文档页移到 pages/ 下；
+// 返回 (输出路径, 是否被移动)
+fn out_rel_for(rel: &Path) -> (String, bool) {
+  let mut parts: Vec<String> = rel.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+  if let Some(last) = parts.last_mut() {
+    let stem = Path::new(last.as_str()).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    *last = format!("{}.html", stem);
+  }
+  let joined = parts.join("/");
+  let reserved = joined == "index.html" || (parts.len() > 1 && matches!(parts[0].as_str(), "tags" | "assets"));
+  if reserved { (format!("pages/{}", joined), true) } else { (joined, false) }
+}
+
+// 输出路径去重：a.md 与 a.markdown、根目录 index.md 与 pages/index.md 等会落到同一文件，
+// 大小写不同的路径在 Windows/macOS 上也是同一文件。先到者保留原名，其余加 -2、-3 后缀
+fn dedup_out_rels(rels: Vec<(String, bool)>) -> Vec<String> {
+  let mut order: Vec<usize> = (0..rels.len()).collect();
+  // 未被移动的页面优先占用原路径
+  order.sort_by_key(|&i| rels[i].1);
+  let mut used = HashSet::new();
+  let mut out = vec![String::new(); rels.len()];
+  for i in order {
+    let rel = &rels[i].0;
+    let (base, ext) = rel.rsplit_once('.').unwrap_or((rel.as_str(), "html"));
+    let mut cand = rel.clone();
+    let mut n = 1;
+    while !used.insert(cand.to_lowercase()) { n += 1; cand = format!("{}-{}.{}", base, n, ext); }
+    out[i] = cand;
+  }
+  out
+}
+
+pub fn export_site_blocking(req: &SiteExportReq) -> Result<SiteExportResult, String> {
+  if let Some(f) = &req.feed { f.validate()?; }
+  let lib = std::fs::canonicalize(&req.library_dir).map_err(|e| format!("library dir error: {e}"))?;
+  std::fs::create_dir_all(&req.out_dir).map_err(|e| format!("create out dir error: {e}"))?;
+  let out = std::fs::canonicalize(&req.out_dir).map_err(|e| format!("out dir error: {e}"))?;
+  if out == lib { return Err("output directory must differ from the library directory".into()); }
+
+  let template = match req.template_path.as_deref().filter(|s| !s.trim().is_empty()) {
+    Some(p) => std::fs::read_to_string(p).map_err(|e| format!("read template {} error: {e}", p))?,
+    None => DEFAULT_TEMPLATE.to_string(),
+  };
+  let title = req.title.clone().filter(|s| !s.trim().is_empty())
+    .or_else(|| lib.file_name().map(|n| n.to_string_lossy().to_string()))
+    .unwrap_or_else(|| "flymd".into());
+
+  // 1. 读取文档与元信息
+  let mut pages = Vec::new();
+  let mut rels = Vec::new();
+  let mut skipped_drafts = 0;
+  for src in collect_markdown(&lib, Some(&out))? {
+    let text = std::fs::read_to_string(&src).map_err(|e| format!("read {} error: {e}", src.display()))?;
+    let (front, body) = markdown::parse_front_matter(&text);
+    let doc = PublishDoc { doc_path: Some(src.to_string_lossy().to_string()), ..Default::default() };
+    let meta = post_meta(&front, body, &doc);
+    if meta.draft && !req.include_drafts { skipped_drafts += 1; continue; }
+    rels.push(out_rel_for(src.strip_prefix(&lib).unwrap_or(&src)));
+    pages.push(Page { out_rel: String::new(), body: body.to_string(), meta, src });
+  }
+  for (p, rel) in pages.iter_mut().zip(dedup_out_rels(rels)) { p.out_rel = rel; }
+
+  // 2. 导航树、文档路径映射、标签
+  let mut nav = NavNode::default();
+  let mut by_src: HashMap<PathBuf, String> = HashMap::new();
+  let mut tags: BTreeMap<String, (String, Vec<usize>)> = BTreeMap::new();
+  for (i, p) in pages.iter().enumerate() {
+    let segs: Vec<&str> = p.out_rel.split('/').collect();
+    nav.insert(&segs[..segs.len() - 1], p.meta.title.clone(), p.out_rel.clone());
+    by_src.insert(p.src.clone(), p.out_rel.clone());
+    for t in &p.meta.tags {
+      let e = tags.entry(t.to_lowercase()).or_insert_with(|| (t.clone(), Vec::new()));
+      if !e.1.contains(&i) { e.1.push(i); }
+    }
+  }
+  let mut used = HashSet::new();
+  let mut tag_files = HashMap::new();
+  for (key, (display, _)) in &tags {
+    let base = match slugify(display) { s if s.is_empty() => sanitize_file_name(display, "tag"), s => s };
+    let mut name = format!("{}.html", base);
+    let mut n = 1;
+    while !used.insert(name.clone()) { n += 1; name = format!("{}-{}.html", base, n); }
+    tag_files.insert(key.clone(), name);
+  }
+  let site = Site { title, template, out: &out, nav, tag_files };
+
+  // 3. 逐篇渲染：转换流水线、复制本地图片、改写 .md 链接与 wiki 链接为 .html
+  let by_stem: HashMap<String, String> = pages.iter()
+    .filter_map(|p| Some((p.src.file_stem()?.to_string_lossy().to_lowercase(), p.out_rel.clone())))
+    .collect();
+  let mut assets: HashMap<PathBuf, String> = HashMap::new();
+  let mut entries = Vec::new();
+  for p in &pages {
+    let root = root_prefix(p.depth());
+    let doc_dir = p.src.parent();
+    let resolve = |name: &str| by_stem.get(&name.to_lowercase()).map(|rel| format!("{}{}", root, encode_href(rel)));
+    let body = transform::apply(&p.body, &req.transform, &resolve);
+    let mut edits = Vec::new();
+    for r in markdown::find_images(&body) {
+      let Some(path) = resolve_local_path(&r.url, doc_dir) else { continue };
+      // 规范化后去重：img/a.png 与 ../img/a.png 指向同一文件
+      let path = std::fs::canonicalize(&path).unwrap_or(path);
+      let asset = match assets.get(&path) {
+        Some(a) => a.clone(),
+        None => {
+          let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+          let tag = hex::encode(Sha256::digest(path.to_string_lossy().as_bytes()));
+          let a = format!("assets/img/{}-{}", &tag[..8], sanitize_file_name(&name, "image"));
+          let dest = out.join(&a);
+          if let Some(dir) = dest.parent() { std::fs::create_dir_all(dir).map_err(|e| format!("create dir error: {e}"))?; }
+          std::fs::copy(&path, &dest).map_err(|e| format!("copy {} error: {e}", path.display()))?;
+          assets.insert(path, a.clone());
+          a
+        }
+      };
+      edits.push((r.range, format!("{}{}", root, encode_href(&asset))));
+    }
+    for r in markdown::find_links(&body) {
+      if is_external(&r.url) { continue; }
+      let (path, suffix) = split_fragment(&r.url);
+      let decoded = percent_encoding::percent_decode_str(path).decode_utf8_lossy().to_string();
+      let target = Path::new(&decoded);
+      if !is_markdown(target) { continue; }
+      let abs = if target.is_absolute() { target.to_path_buf() } else { doc_dir.map(|d| d.join(target)).unwrap_or_default() };
+      let Some(rel) = std::fs::canonicalize(&abs).ok().and_then(|a| by_src.get(&a)) else { continue };
+      edits.push((r.range, format!("{}{}{}", root, encode_href(rel), suffix)));
+    }
+    let body = markdown::replace_ranges(&body, edits);
+    if let Some(f) = &req.feed {
+      let mtime = std::fs::metadata(&p.src).and_then(|m| m.modified()).unwrap_or(std::time::UNIX_EPOCH);
+      let link = format!("{}/{}", f.base(), encode_href(&p.out_rel));
+      entries.push(feed::build_entry(&p.meta, &body, &link, mtime, f.offset()?));
+    }
+    let mut meta_html = String::from("<div class=\"post-meta\">");
+    let date = date_of(&p.meta);
+    if !date.is_empty() { meta_html.push_str(&format!("<time>{}</time> ", escape_html(&date))); }
+    for t in &p.meta.tags {
+      if let Some(href) = site.tag_href(&root, t) {
+        meta_html.push_str(&format!("<a class=\"tag\" href=\"{}\">{}</a>", href, escape_html(t)));
+      }
+    }
+    meta_html.push_str("</div>");
+    site.write_page(&p.out_rel, &p.meta.title, &meta_html, &markdown::render_html(&body), Some(&p.out_rel))?;
+  }
+
+  // 4. 首页（按日期倒序）、标签总览与各标签页
+  let mut order: Vec<&Page> = pages.iter().collect();
+  order.sort_by(|a, b| b.meta.date.cmp(&a.meta.date).then_with(|| a.meta.title.cmp(&b.meta.title)));
+  let index = format!("<h1>{}</h1>{}", escape_html(&site.title), site.post_list("", &order));
+  site.write_page("index.html", &site.title, "", &index, None)?;
+
+  let mut overview = String::from("<h1>标签</h1><p>");
+  for (key, (display, idx)) in &tags {
+    let file = &site.tag_files[key];
+    overview.push_str(&format!("<a class=\"tag\" href=\"{}\">{} ({})</a>", encode_href(file), escape_html(display), idx.len()));
+    let mut list: Vec<&Page> = idx.iter().map(|i| &pages[*i]).collect();
+    list.sort_by(|a, b| b.meta.date.cmp(&a.meta.date));
+    let content = format!("<h1>{}</h1>{}", escape_html(display), site.post_list("../", &list));
+    site.write_page(&format!("tags/{}", file), display, "", &content, None)?;
+  }
+  overview.push_str("</p>");
+  site.write_page("tags/index.html", "标签", "", &overview, None)?;
+  write_file(&out.join("assets/style.css"), DEFAULT_CSS.as_bytes())?;
+
+  // 5. 订阅与 sitemap
+  let mut feeds = Vec::new();
+  if let Some(f) = &req.feed {
+    let mut extra = vec![format!("{}/", f.base()), format!("{}/tags/", f.base())];
+    extra.extend(site.tag_files.values().map(|file| format!("{}/tags/{}", f.base(), encode_href(file))));
+    extra.sort();
+    feeds = feed::write_feeds(&out, f, &entries, &extra)?.0;
+  }
+
+  Ok(SiteExportResult {
+    out_dir: out.to_string_lossy().to_string(),
+    pages: pages.len(),
+    images: assets.len(),
+    tags: tags.len(),
+    skipped_drafts,
+    feeds,
+  })
+}
+
+#[tauri::command]
+pub async fn export_site(req: SiteExportReq) -> Result<SiteExportResult, String> {
+  tauri::async_runtime::spawn_blocking(move || export_site_blocking(&req))
+    .await
+    .map_err(|e| format!("join error: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn out_paths() {
+    assert_eq!(out_rel_for(Path::new("index.md")), ("pages/index.html".to_string(), true));
+    assert_eq!(out_rel_for(Path::new("tags/a.md")), ("pages/tags/a.html".to_string(), true));
+    assert_eq!(out_rel_for(Path::new("notes/index.md")), ("notes/index.html".to_string(), false));
+    assert_eq!(out_rel_for(Path::new("tags.md")), ("tags.html".to_string(), false));
+
+    let rels = ["a.markdown", "a.md", "index.md", "pages/index.md", "Notes/X.md", "notes/x.md", "a-2.md"]
+      .iter().map(|r| out_rel_for(Path::new(r))).collect();
+    assert_eq!(dedup_out_rels(rels), [
+      "a.html", "a-2.html", "pages/index-2.html", "pages/index.html", "Notes/X.html", "notes/x-2.html", "a-2-2.html",
+    ]);
+  }
+
+  #[test]
+  fn export_without_collisions() {
+    let root = std::env::temp_dir().join(format!("flymd-site-{}", std::process::id()));
+    let (lib, out) = (root.join("lib"), root.join("out"));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(lib.join("pages")).unwrap();
+    std::fs::write(lib.join("a.md"), "# A md").unwrap();
+    std::fs::write(lib.join("a.markdown"), "# A markdown").unwrap();
+    std::fs::write(lib.join("index.md"), "---\ntitle: Root index\n---\nroot").unwrap();
+    std::fs::write(lib.join("pages/index.md"), "---\ntitle: Pages index\n---\nsee [a](../a.md)").unwrap();
+    let req = SiteExportReq {
+      library_dir: lib.to_string_lossy().to_string(),
+      out_dir: out.to_string_lossy().to_string(),
+      title: Some("T".into()),
+      template_path: None,
+      include_drafts: false,
+      transform: TransformOptions::default(),
+      feed: None,
+    };
+    let res = export_site_blocking(&req).unwrap();
+    assert_eq!(res.pages, 4);
+    let read = |rel: &str| std::fs::read_to_string(out.join(rel)).unwrap();
+    assert!(read("a.html").contains("A markdown"));
+    assert!(read("a-2.html").contains("A md"));
+    assert!(read("pages/index.html").contains("<a href=\"../a-2.html\">a</a>"));
+    assert!(read("pages/index-2.html").contains("root"));
+    assert!(read("index.html").contains("Root index") && read("index.html").contains("Pages index"));
+    let _ = std::fs::remove_dir_all(&root);
+  }
+}
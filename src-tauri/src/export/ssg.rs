@@ -0,0 +1,564 @@
+// 导出到 Hexo / Hugo / Jekyll 工程：按各自的文件命名（slug、日期前缀）放置文章，
+// 转换 front matter（tags、categories、date、draft），复制图片到对应资源目录并改写地址，报告新增/变更。
+// 工程根目录下的 .flymd-export.json 记录每篇笔记写出的文件，slug 或图片变化后清理旧文件
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as Json;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::{encode_href, parse_date};
+use crate::fsutil::{sanitize_file_name, split_ext};
+use crate::markdown;
+use crate::publish::{post_meta, resolve_local_path, slugify, PublishDoc};
+use crate::transform::{self, TransformOptions};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Generator {
+  Hexo,
+  Hugo,
+  Jekyll,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SsgExportReq {
+  pub generator: Generator,
+  // 生成器工程根目录
+  pub project_dir: String,
+  // 要导出的笔记路径
+  pub files: Vec<String>,
+  // Hugo 的内容分区，缺省 posts
+  #[serde(default)]
+  pub section: Option<String>,
+  // 仅报告，不写入
+  #[serde(default)]
+  pub dry_run: bool,
+  // 覆盖工程中已有、但不是 flymd 导出的文件（内容不在 .flymd-export.json 记录中）；默认遇到时报错
+  #[serde(default)]
+  pub force: bool,
+  #[serde(default)]
+  pub transform: TransformOptions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportStatus {
+  New,
+  Changed,
+  Unchanged,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SsgExportItem {
+  pub source: String,
+  pub target: String,
+  pub status: ExportStatus,
+  pub images: usize,
+  // 上次导出写出、本次不再生成而删除的文件（dry_run 时为将要删除的文件）
+  pub removed: Vec<String>,
+}
+
+const MANIFEST_FILE: &str = ".flymd-export.json";
+
+// 笔记路径 -> 写出的文件（相对工程目录，/ 分隔）及其 sha256
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+  notes: HashMap<String, Vec<WrittenFile>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WrittenFile {
+  path: String,
+  sha256: String,
+}
+
+// 文章与资源在工程中的位置；asset_url 为正文中引用资源时的前缀
+struct Layout {
+  post: PathBuf,
+  asset_dir: PathBuf,
+  asset_url: String,
+}
+
+fn layout(req: &SsgExportReq, slug: &str, date: &chrono::NaiveDateTime, draft: bool) -> Layout {
+  let root = Path::new(&req.project_dir);
+  match req.generator {
+    // 草稿放 _drafts；图片放 source/images/<slug>/，以站点绝对路径引用
+    Generator::Hexo => Layout {
+      post: root.join("source").join(if draft { "_drafts" } else { "_posts" }).join(format!("{}.md", slug)),
+      asset_dir: root.join("source").join("images").join(slug),
+      asset_url: format!("/images/{}/", encode_href(slug)),
+    },
+    // 页面包（page bundle）：index.md 与图片同目录，相对引用
+    Generator::Hugo => {
+      let section = req.section.as_deref().map(str::trim).filter(|s| !s.is_empty()).unwrap_or("posts");
+      let dir = root.join("content").join(section).join(slug);
+      Layout { post: dir.join("index.md"), asset_dir: dir, asset_url: String::new() }
+    }
+    // _posts 文件名必须带日期前缀
+    Generator::Jekyll => Layout {
+      post: if draft {
+        root.join("_drafts").join(format!("{}.md", slug))
+      } else {
+        root.join("_posts").join(format!("{}-{}.md", date.format("%Y-%m-%d"), slug))
+      },
+      asset_dir: root.join("assets").join("images").join(slug),
+      asset_url: format!("/assets/images/{}/", encode_href(slug)),
+    },
+  }
+}
+
+fn format_date(gen: Generator, dt: &chrono::NaiveDateTime, off: Option<chrono::FixedOffset>) -> String {
+  match (gen, off) {
+    (Generator::Hugo, Some(o)) => format!("{}{}", dt.format("%Y-%m-%dT%H:%M:%S"), o),
+    (Generator::Hugo, None) => dt.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    (Generator::Jekyll, Some(o)) => format!("{} {}", dt.format("%Y-%m-%d %H:%M:%S"), o.to_string().replace(':', "")),
+    _ => dt.format("%Y-%m-%d %H:%M:%S").to_string(),
+  }
+}
+
+// 已由映射处理、或属于 flymd 自身的键，不原样带入；published 是 Hexo/Jekyll 自己的字段，原样保留
+const MAPPED_KEYS: &[&str] = &["title", "date", "created", "tags", "tag", "keywords", "categories", "category", "draft", "slug", "flymd_published"];
+
+fn yaml_str(s: &str) -> serde_yaml::Value { serde_yaml::Value::String(s.to_string()) }
+
+fn yaml_list(items: &[String]) -> serde_yaml::Value {
+  serde_yaml::Value::Sequence(items.iter().map(|s| yaml_str(s)).collect())
+}
+
+fn build_front_matter(
+  gen: Generator,
+  front: &serde_json::Map<String, Json>,
+  meta: &crate::publish::PostMeta,
+  date: &str,
+  slug: &str,
+) -> Result<String, String> {
+  let mut m = serde_yaml::Mapping::new();
+  if gen == Generator::Jekyll { m.insert(yaml_str("layout"), front.get("layout").and_then(|v| serde_yaml::to_value(v).ok()).unwrap_or_else(|| yaml_str("post"))); }
+  m.insert(yaml_str("title"), yaml_str(&meta.title));
+  m.insert(yaml_str("date"), yaml_str(date));
+  m.insert(yaml_str("tags"), yaml_list(&meta.tags));
+  m.insert(yaml_str("categories"), yaml_list(&meta.categories));
+  // Hugo 用 draft 字段与 slug；Hexo/Jekyll 以 _drafts 目录与文件名表达
+  if gen == Generator::Hugo {
+    m.insert(yaml_str("draft"), serde_yaml::Value::Bool(meta.draft));
+    m.insert(yaml_str("slug"), yaml_str(slug));
+  }
+  for (k, v) in front {
+    if MAPPED_KEYS.contains(&k.as_str()) || (gen == Generator::Jekyll && k == "layout") { continue; }
+    let v = serde_yaml::to_value(v).map_err(|e| format!("front matter error: {e}"))?;
+    m.insert(yaml_str(k), v);
+  }
+  let yaml = serde_yaml::to_string(&m).map_err(|e| format!("front matter error: {e}"))?;
+  Ok(format!("---\n{}---\n", yaml))
+}
+
+// slug：front matter slug > 标题 > 文件名；非 ASCII 标题保留清洗后的原文件名
+fn slug_for(meta: &crate::publish::PostMeta, src: &Path) -> String {
+  let stem = src.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+  [meta.slug.as_deref().unwrap_or(""), &meta.title, &stem]
+    .iter()
+    .map(|s| slugify(s))
+    .find(|s| !s.is_empty())
+    .unwrap_or_else(|| sanitize_file_name(&stem, "post").replace(' ', "-"))
+}
+
+fn file_sha256(path: &Path) -> Option<String> {
+  std::fs::read(path).ok().map(|d| sha256_hex(&d))
+}
+
+fn status_of(current: Option<&str>, sha256: &str) -> ExportStatus {
+  match current {
+    Some(c) if c == sha256 => ExportStatus::Unchanged,
+    Some(_) => ExportStatus::Changed,
+    None => ExportStatus::New,
+  }
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<(), String> {
+  if let Some(dir) = path.parent() {
+    std::fs::create_dir_all(dir).map_err(|e| format!("create dir {} error: {e}", dir.display()))?;
+  }
+  std::fs::write(path, data).map_err(|e| format!("write {} error: {e}", path.display()))
+}
+
+// 待写入的文件：文章内容或要复制的图片
+enum Content {
+  Text(String),
+  Copy(PathBuf),
+}
+
+struct Pending {
+  to: PathBuf,
+  content: Content,
+  sha256: String,
+  // 目标文件当前的 sha256（不存在为 None）
+  current: Option<String>,
+}
+
+// 导出计划：先为全部笔记确定 slug 与落点，wiki 链接才能互相引用
+struct Planned {
+  src: PathBuf,
+  front: serde_json::Map<String, Json>,
+  body: String,
+  meta: crate::publish::PostMeta,
+  date: String,
+  slug: String,
+  lay: Layout,
+  when: chrono::NaiveDateTime,
+  // Jekyll post_url 需要的文件名（不含扩展名）
+  post_name: String,
+}
+
+fn plan(req: &SsgExportReq, src: &Path) -> Result<Planned, String> {
+  let text = std::fs::read_to_string(src).map_err(|e| format!("read {} error: {e}", src.display()))?;
+  let (front, body) = markdown::parse_front_matter(&text);
+  let doc = PublishDoc { doc_path: Some(src.to_string_lossy().to_string()), ..Default::default() };
+  let meta = post_meta(&front, body, &doc);
+  // 无日期时取文件修改时间
+  let (dt, off) = meta.date.as_deref().and_then(parse_date).unwrap_or_else(|| {
+    let mtime = std::fs::metadata(src).and_then(|m| m.modified()).unwrap_or_else(|_| std::time::SystemTime::now());
+    (chrono::DateTime::<chrono::Utc>::from(mtime).naive_utc(), Some(chrono::FixedOffset::east_opt(0).unwrap()))
+  });
+  let slug = slug_for(&meta, src);
+  let lay = layout(req, &slug, &dt, meta.draft);
+  let post_name = lay.post.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+  Ok(Planned {
+    src: src.to_path_buf(),
+    body: body.to_string(),
+    front,
+    date: format_date(req.generator, &dt, off),
+    meta,
+    slug,
+    lay,
+    when: dt,
+    post_name,
+  })
+}
+
+// 同批笔记落到同一文章文件或图片目录时（标题/slug 相同，Jekyll 上日期也相同），
+// 先到者保留原 slug，其余加 -2、-3 后缀；大小写不同的路径在 Windows/macOS 上也是同一文件
+fn dedup_slugs(req: &SsgExportReq, plans: &mut [Planned]) {
+  let key = |p: &Path| p.to_string_lossy().to_lowercase();
+  let mut used = HashSet::new();
+  for p in plans.iter_mut() {
+    let base = p.slug.clone();
+    let mut n = 1;
+    while used.contains(&key(&p.lay.post)) || used.contains(&key(&p.lay.asset_dir)) {
+      n += 1;
+      p.slug = format!("{}-{}", base, n);
+      p.lay = layout(req, &p.slug, &p.when, p.meta.draft);
+    }
+    p.post_name = p.lay.post.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    used.insert(key(&p.lay.post));
+    used.insert(key(&p.lay.asset_dir));
+  }
+}
+
+// wiki 链接指向同批导出的文章时，改用生成器自己的站内引用语法，不依赖永久链接配置
+fn generator_ref(req: &SsgExportReq, p: &Planned) -> Option<String> {
+  match req.generator {
+    Generator::Hexo => Some(format!("{{% post_path {} %}}", p.slug)),
+    Generator::Hugo => {
+      let section = req.section.as_deref().map(str::trim).filter(|s| !s.is_empty()).unwrap_or("posts");
+      Some(format!("{{{{< ref \"/{}/{}\" >}}}}", section, p.slug))
+    }
+    Generator::Jekyll if !p.meta.draft => Some(format!("{{% post_url {} %}}", p.post_name)),
+    Generator::Jekyll => None,
+  }
+}
+
+fn sha256_hex(data: &[u8]) -> String { hex::encode(Sha256::digest(data)) }
+
+fn rel_to(root: &Path, path: &Path) -> String {
+  let rel = path.strip_prefix(root).unwrap_or(path);
+  rel.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect::<Vec<_>>().join("/")
+}
+
+// 生成一篇（不写入），返回它对应的文件（文章与图片）及待写入的内容
+fn export_one(req: &SsgExportReq, p: &Planned, refs: &HashMap<String, String>) -> Result<(SsgExportItem, Vec<WrittenFile>, Vec<Pending>), String> {
+  let resolve = |name: &str| refs.get(&name.to_lowercase()).cloned();
+  let body = transform::apply(&p.body, &req.transform, &resolve);
+  let lay = &p.lay;
+
+  // 图片：复制到资源目录，同名不同文件加序号
+  let mut names: HashMap<PathBuf, String> = HashMap::new();
+  let mut edits = Vec::new();
+  let mut copies = Vec::new();
+  for r in markdown::find_images(&body) {
+    let Some(path) = resolve_local_path(&r.url, p.src.parent()) else { continue };
+    let path = std::fs::canonicalize(&path).unwrap_or(path);
+    let name = match names.get(&path) {
+      Some(n) => n.clone(),
+      None => {
+        let raw = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let base = sanitize_file_name(&raw, "image");
+        let mut name = base.clone();
+        let mut i = 1;
+        while names.values().any(|n| *n == name) {
+          let (stem, ext) = split_ext(&base);
+          name = format!("{}-{}{}", stem, i, ext);
+          i += 1;
+        }
+        names.insert(path.clone(), name.clone());
+        copies.push((path, lay.asset_dir.join(&name)));
+        name
+      }
+    };
+    edits.push((r.range, format!("{}{}", lay.asset_url, encode_href(&name))));
+  }
+  let body = markdown::replace_ranges(&body, edits);
+  let fm = build_front_matter(req.generator, &p.front, &p.meta, &p.date, &p.slug)?;
+  let out = format!("{}\n{}", fm, body.trim_start_matches(['\r', '\n']));
+
+  let root = Path::new(&req.project_dir);
+  let post_sha = sha256_hex(out.as_bytes());
+  let mut pending = vec![Pending { to: lay.post.clone(), current: file_sha256(&lay.post), sha256: post_sha, content: Content::Text(out) }];
+  for (from, to) in copies {
+    let data = std::fs::read(&from).map_err(|e| format!("read {} error: {e}", from.display()))?;
+    pending.push(Pending { current: file_sha256(&to), sha256: sha256_hex(&data), to, content: Content::Copy(from) });
+  }
+  let files = pending.iter().map(|w| WrittenFile { path: rel_to(root, &w.to), sha256: w.sha256.clone() }).collect();
+  let mut status = status_of(pending[0].current.as_deref(), &pending[0].sha256);
+  if status == ExportStatus::Unchanged && pending.iter().any(|w| status_of(w.current.as_deref(), &w.sha256) != ExportStatus::Unchanged) {
+    status = ExportStatus::Changed;
+  }
+  let item = SsgExportItem {
+    source: p.src.to_string_lossy().to_string(),
+    target: lay.post.to_string_lossy().to_string(),
+    status,
+    images: pending.len() - 1,
+    removed: Vec::new(),
+  };
+  // 内容未变的文件不再写入
+  let pending = pending.into_iter().filter(|w| w.current.as_deref() != Some(w.sha256.as_str())).collect();
+  Ok((item, files, pending))
+}
+
+// 删除上次导出写出、本次不再生成的文件；导出后被手动修改过的文件保留不动
+fn remove_stale(root: &Path, old: &[WrittenFile], keep: &HashSet<String>, dry_run: bool) -> Result<Vec<String>, String> {
+  let mut removed = Vec::new();
+  for f in old.iter().filter(|f| !keep.contains(&f.path)) {
+    let path = root.join(&f.path);
+    let Ok(data) = std::fs::read(&path) else { continue };
+    if sha256_hex(&data) != f.sha256 { continue; }
+    if !dry_run {
+      std::fs::remove_file(&path).map_err(|e| format!("remove {} error: {e}", path.display()))?;
+      // 顺带清掉因此变空的目录（Hugo 页面包、图片目录），不越过工程根目录
+      let mut dir = path.parent();
+      while let Some(d) = dir.filter(|d| *d != root && d.starts_with(root)) {
+        if std::fs::remove_dir(d).is_err() { break; }
+        dir = d.parent();
+      }
+    }
+    removed.push(path.to_string_lossy().to_string());
+  }
+  Ok(removed)
+}
+
+pub fn export_ssg_blocking(req: &SsgExportReq) -> Result<Vec<SsgExportItem>, String> {
+  if !Path::new(&req.project_dir).is_dir() { return Err(format!("project dir not found: {}", req.project_dir)); }
+  let mut plans = req.files.iter().map(|f| plan(req, Path::new(f))).collect::<Result<Vec<_>, _>>()?;
+  dedup_slugs(req, &mut plans);
+  // 笔记名（文件名，忽略大小写）-> 生成器引用
+  let refs: HashMap<String, String> = plans.iter().filter_map(|p| {
+    let stem = p.src.file_stem()?.to_string_lossy().to_lowercase();
+    generator_ref(req, p).map(|r| (stem, r))
+  }).collect();
+  let results = plans.iter().map(|p| export_one(req, p, &refs)).collect::<Result<Vec<_>, _>>()?;
+
+  let root = Path::new(&req.project_dir);
+  let manifest_path = root.join(MANIFEST_FILE);
+  let mut manifest: Manifest = std::fs::read(&manifest_path).ok()
+    .and_then(|d| serde_json::from_slice(&d).ok())
+    .unwrap_or_default();
+
+  // 已有文件的内容不是 flymd 导出过的（手写的文章、导出后手动修改过的文件）时不覆盖，除非 force
+  let known: HashSet<(&str, &str)> = manifest.notes.values().flatten().map(|f| (f.path.as_str(), f.sha256.as_str())).collect();
+  let conflicts: Vec<String> = results.iter()
+    .flat_map(|(_, _, pending)| pending.iter())
+    .filter(|w| w.current.as_deref().map(|c| !known.contains(&(rel_to(root, &w.to).as_str(), c))).unwrap_or(false))
+    .map(|w| w.to.to_string_lossy().to_string())
+    .collect();
+  if !conflicts.is_empty() && !req.force {
+    return Err(format!("export would overwrite files not written by flymd (use force to replace them): {}", conflicts.join(", ")));
+  }
+  if !req.dry_run {
+    for w in results.iter().flat_map(|(_, _, pending)| pending.iter()) {
+      match &w.content {
+        Content::Text(t) => write_file(&w.to, t.as_bytes())?,
+        Content::Copy(from) => {
+          let data = std::fs::read(from).map_err(|e| format!("read {} error: {e}", from.display()))?;
+          write_file(&w.to, &data)?;
+        }
+      }
+    }
+  }
+
+  // 本批次生成的所有文件都不删除，即使它们原属于另一篇笔记
+  let keep: HashSet<String> = results.iter().flat_map(|(_, files, _)| files.iter().map(|f| f.path.clone())).collect();
+  let mut items = Vec::new();
+  for (mut item, files, _) in results {
+    if let Some(old) = manifest.notes.get(&item.source) {
+      item.removed = remove_stale(root, old, &keep, req.dry_run)?;
+    }
+    manifest.notes.insert(item.source.clone(), files);
+    items.push(item);
+  }
+  if !req.dry_run {
+    let data = serde_json::to_vec_pretty(&manifest).map_err(|e| format!("serialize manifest error: {e}"))?;
+    std::fs::write(&manifest_path, data).map_err(|e| format!("write {} error: {e}", manifest_path.display()))?;
+  }
+  Ok(items)
+}
+
+#[tauri::command]
+pub async fn export_ssg(req: SsgExportReq) -> Result<Vec<SsgExportItem>, String> {
+  tauri::async_runtime::spawn_blocking(move || export_ssg_blocking(&req))
+    .await
+    .map_err(|e| format!("join error: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  struct Dirs { root: PathBuf, notes: PathBuf, project: PathBuf }
+
+  fn dirs(name: &str) -> Dirs {
+    let root = std::env::temp_dir().join(format!("flymd-ssg-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let (notes, project) = (root.join("notes"), root.join("project"));
+    std::fs::create_dir_all(&notes).unwrap();
+    std::fs::create_dir_all(&project).unwrap();
+    Dirs { root, notes, project }
+  }
+
+  fn req(gen: Generator, d: &Dirs, files: &[&str]) -> SsgExportReq {
+    SsgExportReq {
+      generator: gen,
+      project_dir: d.project.to_string_lossy().to_string(),
+      files: files.iter().map(|f| d.notes.join(f).to_string_lossy().to_string()).collect(),
+      section: None,
+      dry_run: false,
+      force: false,
+      transform: TransformOptions::default(),
+    }
+  }
+
+  #[test]
+  fn keeps_published_flag() {
+    let front: serde_json::Map<String, Json> = serde_json::from_str(r#"{"title":"T","published":false,"flymd_published":[{"target":"x","id":"1"}],"layout":"page"}"#).unwrap();
+    let meta = post_meta(&front, "", &PublishDoc::default());
+    assert!(meta.draft);
+    let fm = build_front_matter(Generator::Jekyll, &front, &meta, "2025-01-01 00:00:00", "t").unwrap();
+    assert!(fm.contains("\npublished: false\n"));
+    assert!(fm.contains("layout: page"));
+    assert!(!fm.contains("flymd_published"));
+  }
+
+  #[test]
+  fn removes_stale_files_after_slug_change() {
+    let d = dirs("slug");
+    std::fs::write(d.notes.join("a.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+    std::fs::write(d.notes.join("n.md"), "---\ntitle: First\n---\n![](a.png)\n").unwrap();
+    let r = req(Generator::Hugo, &d, &["n.md"]);
+    let items = export_ssg_blocking(&r).unwrap();
+    assert_eq!(items[0].status, ExportStatus::New);
+    let old_bundle = d.project.join("content/posts/first");
+    assert!(old_bundle.join("index.md").is_file() && old_bundle.join("a.png").is_file());
+
+    // 改 slug：旧页面包整个删除
+    std::fs::write(d.notes.join("n.md"), "---\ntitle: First\nslug: renamed\n---\n![](a.png)\n").unwrap();
+    let mut dry = req(Generator::Hugo, &d, &["n.md"]);
+    dry.dry_run = true;
+    assert_eq!(export_ssg_blocking(&dry).unwrap()[0].removed.len(), 2);
+    assert!(old_bundle.is_dir());
+    let items = export_ssg_blocking(&r).unwrap();
+    assert_eq!(items[0].removed.len(), 2);
+    assert!(!old_bundle.exists());
+    assert!(d.project.join("content/posts/renamed/index.md").is_file());
+
+    // 再次导出无变化，不删任何文件
+    let items = export_ssg_blocking(&r).unwrap();
+    assert_eq!((items[0].status, items[0].removed.len()), (ExportStatus::Unchanged, 0));
+    let _ = std::fs::remove_dir_all(&d.root);
+  }
+
+  #[test]
+  fn keeps_edited_and_reused_files() {
+    let d = dirs("keep");
+    std::fs::write(d.notes.join("a.md"), "---\ntitle: Alpha\ndate: 2025-01-02\n---\nx\n").unwrap();
+    std::fs::write(d.notes.join("b.md"), "---\ntitle: Beta\ndate: 2025-01-02\n---\ny\n").unwrap();
+    let r = req(Generator::Jekyll, &d, &["a.md", "b.md"]);
+    export_ssg_blocking(&r).unwrap();
+    let alpha = d.project.join("_posts/2025-01-02-alpha.md");
+    let beta = d.project.join("_posts/2025-01-02-beta.md");
+
+    // a 改名且旧文件被手动改过：保留；b 改用 a 原来的 slug：手动改过的文件默认不覆盖
+    std::fs::write(&alpha, "edited by hand").unwrap();
+    std::fs::write(d.notes.join("a.md"), "---\ntitle: Alpha\nslug: a2\ndate: 2025-01-02\n---\nx\n").unwrap();
+    std::fs::write(d.notes.join("b.md"), "---\ntitle: Beta\nslug: alpha\ndate: 2025-01-02\n---\ny\n").unwrap();
+    let err = export_ssg_blocking(&r).unwrap_err();
+    assert!(err.contains("not written by flymd") && err.contains("2025-01-02-alpha.md"), "{err}");
+    assert_eq!(std::fs::read_to_string(&alpha).unwrap(), "edited by hand");
+    assert!(beta.is_file() && !d.project.join("_posts/2025-01-02-a2.md").exists());
+
+    // force：文件由 b 接管，不被删除
+    let r = SsgExportReq { force: true, ..r };
+    let items = export_ssg_blocking(&r).unwrap();
+    assert!(items[0].removed.is_empty());
+    assert_eq!(items[1].removed, [beta.to_string_lossy().to_string()]);
+    assert!(std::fs::read_to_string(&alpha).unwrap().contains("title: Beta"));
+    assert!(!beta.exists());
+    let _ = std::fs::remove_dir_all(&d.root);
+  }
+
+  #[test]
+  fn dedups_targets_in_one_batch() {
+    let d = dirs("dedup");
+    std::fs::write(d.notes.join("a.png"), b"\x89PNG\r\n\x1a\nA").unwrap();
+    std::fs::write(d.notes.join("b.png"), b"\x89PNG\r\n\x1a\nB").unwrap();
+    std::fs::write(d.notes.join("one.md"), "---\ntitle: Same\ndate: 2025-01-02\n---\n![](a.png)\n").unwrap();
+    std::fs::write(d.notes.join("two.md"), "---\ntitle: same\ndate: 2025-01-02\n---\n![](b.png)\n").unwrap();
+    std::fs::write(d.notes.join("three.md"), "---\ntitle: Other\nslug: same\ndate: 2025-03-04\n---\nz\n").unwrap();
+    let r = req(Generator::Jekyll, &d, &["one.md", "two.md", "three.md"]);
+    let items = export_ssg_blocking(&r).unwrap();
+    let names: Vec<String> = items.iter().map(|i| Path::new(&i.target).file_name().unwrap().to_string_lossy().to_string()).collect();
+    // 日期不同但图片目录相同的 three 同样换名
+    assert_eq!(names, ["2025-01-02-same.md", "2025-01-02-same-2.md", "2025-03-04-same-3.md"]);
+    assert!(items.iter().all(|i| i.status == ExportStatus::New));
+    assert_eq!(std::fs::read(d.project.join("assets/images/same/a.png")).unwrap(), b"\x89PNG\r\n\x1a\nA");
+    assert_eq!(std::fs::read(d.project.join("assets/images/same-2/b.png")).unwrap(), b"\x89PNG\r\n\x1a\nB");
+    assert!(std::fs::read_to_string(d.project.join("_posts/2025-01-02-same-2.md")).unwrap().contains("/assets/images/same-2/b.png"));
+    let _ = std::fs::remove_dir_all(&d.root);
+  }
+
+  #[test]
+  fn refuses_to_overwrite_foreign_files() {
+    let d = dirs("foreign");
+    let post = d.project.join("content/posts/hello/index.md");
+    std::fs::create_dir_all(post.parent().unwrap()).unwrap();
+    std::fs::write(&post, "hand written").unwrap();
+    std::fs::write(d.notes.join("h.md"), "---\ntitle: Hello\n---\nhi\n").unwrap();
+    let mut r = req(Generator::Hugo, &d, &["h.md"]);
+    r.dry_run = true;
+    assert!(export_ssg_blocking(&r).unwrap_err().contains("index.md"));
+    r.dry_run = false;
+    assert!(export_ssg_blocking(&r).is_err());
+    assert_eq!(std::fs::read_to_string(&post).unwrap(), "hand written");
+    assert!(!d.project.join(MANIFEST_FILE).exists());
+
+    r.force = true;
+    assert_eq!(export_ssg_blocking(&r).unwrap()[0].status, ExportStatus::Changed);
+    // 之后的导出：文件由 flymd 写出，不需要 force
+    r.force = false;
+    std::fs::write(d.notes.join("h.md"), "---\ntitle: Hello\n---\nhi again\n").unwrap();
+    assert_eq!(export_ssg_blocking(&r).unwrap()[0].status, ExportStatus::Changed);
+    assert!(std::fs::read_to_string(&post).unwrap().contains("hi again"));
+    let _ = std::fs::remove_dir_all(&d.root);
+  }
+}
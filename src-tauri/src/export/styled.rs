@@ -0,0 +1,631 @@
+// 复制为带样式的 HTML：用于微信公众号、知乎等会剔除 <style> 的编辑器
+// 主题 CSS 全部内联到元素 style 属性，代码高亮输出为带颜色的 span，外链转为文末脚注，本地图片上传到图床
+
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+use crate::export::escape_html;
+use crate::markdown;
+use crate::publish::{self, default_true, resolve_local_path, LocalImage, PublishDoc};
+use crate::upload_policy::UploadPolicy;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+  // 公众号：不允许外链，代码块空白需转义
+  #[default]
+  Wechat,
+  Zhihu,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StyledHtmlReq {
+  pub doc: PublishDoc,
+  #[serde(default)]
+  pub platform: Platform,
+  // 内置主题名，缺省 default
+  #[serde(default)]
+  pub theme: Option<String>,
+  // 追加在主题之后的自定义 CSS，选择器优先级相同时覆盖主题
+  #[serde(default)]
+  pub custom_css: Option<String>,
+  // 外链转为文末脚注；缺省时公众号开启、知乎关闭
+  #[serde(default)]
+  pub footnote_links: Option<bool>,
+  #[serde(default = "default_true")]
+  pub highlight: bool,
+  // 图床配置：本地图片上传后替换为外链；key 作为对象名模板（同前端 keyTemplate）
+  #[serde(default)]
+  pub image_host: Option<crate::UploadReq>,
+  // 写入剪贴板（text/html，纯文本为 Markdown）
+  #[serde(default = "default_true")]
+  pub copy: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StyledHtml {
+  pub html: String,
+  pub images_uploaded: usize,
+  // 未上传的本地图片（未配置图床），粘贴后将无法显示
+  pub local_images: Vec<String>,
+  pub copied: bool,
+}
+
+// (名称, CSS, 代码高亮主题)；规则按选择器优先级叠加，优先级相同时靠后的生效
+const THEMES: &[(&str, &str, &str)] = &[
+  ("default", THEME_DEFAULT, "InspiredGitHub"),
+  ("green", THEME_GREEN, "InspiredGitHub"),
+  ("ink", THEME_INK, "base16-ocean.dark"),
+];
+
+const THEME_BASE: &str = r#"
+.flymd { font-size: 16px; line-height: 1.75; color: #333; letter-spacing: 0.5px; word-break: break-word; padding: 0 8px; }
+p { margin: 1em 0; }
+h1, h2, h3, h4, h5, h6 { font-weight: bold; line-height: 1.4; margin: 1.6em 0 0.8em; color: #222; }
+h1 { font-size: 1.6em; }
+h2 { font-size: 1.4em; }
+h3 { font-size: 1.2em; }
+h4, h5, h6 { font-size: 1em; }
+strong { font-weight: bold; }
+em { font-style: italic; }
+del { text-decoration: line-through; color: #999; }
+ul, ol { margin: 1em 0; padding-left: 1.6em; }
+li { margin: 0.3em 0; }
+blockquote { margin: 1em 0; padding: 0.6em 1em; border-left: 4px solid #ddd; background: #f7f7f7; color: #666; }
+blockquote p { margin: 0.3em 0; }
+code { font-family: Menlo, Consolas, monospace; font-size: 0.9em; padding: 2px 4px; border-radius: 3px; background: #f2f2f2; color: #c7254e; }
+pre { margin: 1em 0; padding: 1em; border-radius: 6px; background: #f6f8fa; overflow-x: auto; line-height: 1.5; font-size: 13px; white-space: pre; }
+pre code { padding: 0; background: none; color: #333; font-size: 13px; white-space: pre; }
+img { display: block; max-width: 100%; margin: 1em auto; border-radius: 4px; }
+hr { border: none; border-top: 1px solid #e5e5e5; margin: 2em 0; }
+table { border-collapse: collapse; margin: 1em 0; width: 100%; font-size: 14px; }
+th, td { border: 1px solid #dfe2e5; padding: 6px 10px; }
+th { background: #f6f8fa; font-weight: bold; }
+sup.footnote-ref { font-size: 0.75em; color: #888; }
+.footnotes { margin-top: 2em; font-size: 13px; color: #888; }
+.footnotes-title { font-weight: bold; margin: 0.5em 0; }
+.footnote-item { margin: 0.2em 0; word-break: break-all; }
+.callout { margin: 1em 0; padding: 0.6em 1em; border-left: 4px solid #4a90e2; background: #f3f8fe; }
+.callout-title { font-weight: bold; margin: 0.3em 0; }
+"#;
+
+const THEME_DEFAULT: &str = r#"
+h2 { padding-bottom: 0.3em; border-bottom: 1px solid #eee; }
+a { color: #1e6bb8; text-decoration: none; border-bottom: 1px solid #1e6bb8; }
+"#;
+
+const THEME_GREEN: &str = r#"
+h1, h2 { color: #07a35a; }
+h2 { display: inline-block; padding: 0 0.2em; border-bottom: 3px solid #07c160; }
+h3 { padding-left: 8px; border-left: 4px solid #07c160; }
+a { color: #07a35a; text-decoration: none; }
+strong { color: #07a35a; }
+blockquote { border-left-color: #07c160; background: #f3fbf6; }
+code { color: #07a35a; background: #eef8f2; }
+.callout { border-left-color: #07c160; background: #f3fbf6; }
+"#;
+
+const THEME_INK: &str = r#"
+.flymd { color: #2c2c2c; }
+h1, h2, h3 { color: #111; }
+h2 { padding-left: 10px; border-left: 5px solid #333; }
+a { color: #333; text-decoration: underline; }
+blockquote { border-left-color: #333; background: #f5f5f5; }
+code { color: #b5422e; background: #f3f3f3; }
+pre { background: #2b303b; }
+pre code { color: #c0c5ce; }
+"#;
+
+pub fn theme_names() -> Vec<&'static str> {
+  THEMES.iter().map(|(n, _, _)| *n).collect()
+}
+
+// ---- CSS 解析与内联 ----
+
+// 简单选择器：tag、.class、tag.class、*；空格分隔的后代组合
+#[derive(Debug, Default)]
+struct Compound {
+  tag: Option<String>,
+  classes: Vec<String>,
+}
+
+struct Rule {
+  path: Vec<Compound>,
+  decls: Vec<(String, String)>,
+}
+
+impl Rule {
+  // (类选择器数, 标签选择器数)
+  fn specificity(&self) -> (usize, usize) {
+    self.path.iter().fold((0, 0), |(c, t), p| (c + p.classes.len(), t + p.tag.is_some() as usize))
+  }
+}
+
+fn parse_compound(s: &str) -> Option<Compound> {
+  let mut c = Compound::default();
+  let mut parts = s.split('.');
+  let tag = parts.next()?;
+  if !tag.is_empty() && tag != "*" {
+    if !tag.chars().all(|ch| ch.is_ascii_alphanumeric()) { return None; }
+    c.tag = Some(tag.to_ascii_lowercase());
+  }
+  for cls in parts {
+    if cls.is_empty() || !cls.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_') { return None; }
+    c.classes.push(cls.to_string());
+  }
+  Some(c)
+}
+
+fn parse_decls(s: &str) -> Vec<(String, String)> {
+  s.split(';')
+    .filter_map(|d| d.split_once(':'))
+    .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+    .filter(|(k, v)| !k.is_empty() && !v.is_empty())
+    .collect()
+}
+
+// 不支持的选择器（属性、伪类、子代/兄弟组合等）整条忽略
+fn parse_css(css: &str) -> Vec<Rule> {
+  let mut text = String::with_capacity(css.len());
+  let mut rest = css;
+  while let Some(i) = rest.find("/*") {
+    text.push_str(&rest[..i]);
+    rest = rest[i + 2..].find("*/").map(|j| &rest[i + 2 + j + 2..]).unwrap_or("");
+  }
+  text.push_str(rest);
+
+  let mut rules = Vec::new();
+  for block in text.split('}') {
+    let Some((sel, body)) = block.split_once('{') else { continue };
+    let decls = parse_decls(body);
+    if decls.is_empty() { continue; }
+    for one in sel.split(',') {
+      let path: Option<Vec<Compound>> = one.split_whitespace().map(parse_compound).collect();
+      if let Some(path) = path.filter(|p| !p.is_empty()) {
+        rules.push(Rule { path, decls: decls.clone() });
+      }
+    }
+  }
+  rules
+}
+
+struct Element {
+  tag: String,
+  classes: Vec<String>,
+}
+
+fn compound_matches(c: &Compound, el: &Element) -> bool {
+  c.tag.iter().all(|t| *t == el.tag) && c.classes.iter().all(|cls| el.classes.contains(cls))
+}
+
+fn rule_matches(rule: &Rule, el: &Element, ancestors: &[Element]) -> bool {
+  let Some((last, rest)) = rule.path.split_last() else { return false };
+  if !compound_matches(last, el) { return false; }
+  let mut anc = ancestors.iter().rev();
+  rest.iter().rev().all(|c| anc.any(|a| compound_matches(c, a)))
+}
+
+// 同名属性保留最后一次出现
+fn merge_decls(target: &mut Vec<(String, String)>, decls: &[(String, String)]) {
+  for (k, v) in decls {
+    target.retain(|(x, _)| x != k);
+    target.push((k.clone(), v.clone()));
+  }
+}
+
+// 解析开始标签内的属性：(名称, 值)
+fn parse_attrs(s: &str) -> Vec<(String, Option<String>)> {
+  let mut out = Vec::new();
+  let b = s.as_bytes();
+  let mut i = 0;
+  while i < b.len() {
+    while i < b.len() && (b[i].is_ascii_whitespace() || b[i] == b'/') { i += 1; }
+    let start = i;
+    while i < b.len() && !b[i].is_ascii_whitespace() && b[i] != b'=' && b[i] != b'/' { i += 1; }
+    if start == i { break; }
+    let name = s[start..i].to_ascii_lowercase();
+    while i < b.len() && b[i].is_ascii_whitespace() { i += 1; }
+    if i < b.len() && b[i] == b'=' {
+      i += 1;
+      while i < b.len() && b[i].is_ascii_whitespace() { i += 1; }
+      let value = if i < b.len() && (b[i] == b'"' || b[i] == b'\'') {
+        let q = b[i];
+        let vs = i + 1;
+        i = vs;
+        while i < b.len() && b[i] != q { i += 1; }
+        let v = &s[vs..i.min(b.len())];
+        i += 1;
+        v
+      } else {
+        let vs = i;
+        while i < b.len() && !b[i].is_ascii_whitespace() { i += 1; }
+        &s[vs..i]
+      };
+      out.push((name, Some(value.to_string())));
+    } else {
+      out.push((name, None));
+    }
+  }
+  out
+}
+
+const VOID_TAGS: &[&str] = &["area", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr"];
+
+// 开始标签的结束位置（跳过引号内的 '>'）
+fn tag_end(s: &str) -> Option<usize> {
+  let mut quote: Option<char> = None;
+  for (i, c) in s.char_indices() {
+    match (quote, c) {
+      (Some(q), c) if c == q => quote = None,
+      (Some(_), _) => {}
+      (None, '"' | '\'') => quote = Some(c),
+      (None, '>') => return Some(i),
+      _ => {}
+    }
+  }
+  None
+}
+
+// 遍历 HTML 标签，把匹配的规则写入 style 属性；元素原有的 style 放在最后（优先）
+fn inline_css(html: &str, rules: &[Rule]) -> String {
+  let mut out = String::with_capacity(html.len() * 2);
+  let mut stack: Vec<Element> = Vec::new();
+  let mut rest = html;
+  while let Some(i) = rest.find('<') {
+    out.push_str(&rest[..i]);
+    rest = &rest[i..];
+    if rest.starts_with("<!--") {
+      let end = rest.find("-->").map(|e| e + 3).unwrap_or(rest.len());
+      out.push_str(&rest[..end]);
+      rest = &rest[end..];
+      continue;
+    }
+    let Some(end) = tag_end(rest) else { break };
+    let inner = &rest[1..end];
+    rest = &rest[end + 1..];
+    if let Some(name) = inner.strip_prefix('/') {
+      let name = name.trim().to_ascii_lowercase();
+      if let Some(pos) = stack.iter().rposition(|e| e.tag == name) { stack.truncate(pos); }
+      out.push('<');
+      out.push_str(inner);
+      out.push('>');
+      continue;
+    }
+    if inner.starts_with('!') || inner.starts_with('?') {
+      out.push('<');
+      out.push_str(inner);
+      out.push('>');
+      continue;
+    }
+    let name_len = inner.find(|c: char| c.is_ascii_whitespace() || c == '/').unwrap_or(inner.len());
+    let tag = inner[..name_len].to_ascii_lowercase();
+    let attrs = parse_attrs(&inner[name_len..]);
+    let classes = attrs.iter().find(|(k, _)| k == "class").and_then(|(_, v)| v.as_deref())
+      .map(|v| v.split_whitespace().map(|s| s.to_string()).collect()).unwrap_or_default();
+    let el = Element { tag, classes };
+
+    let mut matched: Vec<&Rule> = rules.iter().filter(|r| rule_matches(r, &el, &stack)).collect();
+    matched.sort_by_key(|r| r.specificity());
+    let mut decls = Vec::new();
+    for r in matched { merge_decls(&mut decls, &r.decls); }
+    if let Some(own) = attrs.iter().find(|(k, _)| k == "style").and_then(|(_, v)| v.as_deref()) {
+      merge_decls(&mut decls, &parse_decls(own));
+    }
+
+    out.push('<');
+    out.push_str(&el.tag);
+    for (k, v) in attrs.iter().filter(|(k, _)| k != "style") {
+      out.push(' ');
+      out.push_str(k);
+      if let Some(v) = v {
+        out.push_str("=\"");
+        out.push_str(&v.replace('"', "&quot;"));
+        out.push('"');
+      }
+    }
+    if !decls.is_empty() {
+      let style: Vec<String> = decls.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+      out.push_str(" style=\"");
+      out.push_str(&style.join("; ").replace('"', "'"));
+      out.push('"');
+    }
+    let self_closing = inner.trim_end().ends_with('/');
+    if self_closing { out.push_str(" /"); }
+    out.push('>');
+    if !self_closing && !VOID_TAGS.contains(&el.tag.as_str()) { stack.push(el); }
+  }
+  out.push_str(rest);
+  out
+}
+
+// ---- 代码高亮 ----
+
+fn syntax_set() -> &'static SyntaxSet {
+  static SS: OnceLock<SyntaxSet> = OnceLock::new();
+  SS.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme_set() -> &'static ThemeSet {
+  static TS: OnceLock<ThemeSet> = OnceLock::new();
+  TS.get_or_init(ThemeSet::load_defaults)
+}
+
+// 代码文本转义；公众号会吞掉连续空白与换行，需转为 &nbsp; / <br>
+fn code_text(s: &str, keep_space: bool) -> String {
+  let e = escape_html(s);
+  if !keep_space { return e; }
+  e.replace('\t', "    ").replace(' ', "&nbsp;").replace("\r\n", "\n").replace('\n', "<br>")
+}
+
+fn highlight_code(code: &str, lang: &str, theme: &str, keep_space: bool) -> String {
+  let ss = syntax_set();
+  let syntax = ss.find_syntax_by_token(lang).filter(|_| !lang.is_empty());
+  let (Some(syntax), Some(th)) = (syntax, theme_set().themes.get(theme)) else {
+    return code_text(code, keep_space);
+  };
+  let mut h = HighlightLines::new(syntax, th);
+  let mut out = String::with_capacity(code.len() * 4);
+  for line in LinesWithEndings::from(code) {
+    let Ok(regions) = h.highlight_line(line, ss) else { return code_text(code, keep_space) };
+    for (style, text) in regions {
+      if text.trim().is_empty() {
+        out.push_str(&code_text(text, keep_space));
+        continue;
+      }
+      let c = style.foreground;
+      let mut css = format!("color: #{:02x}{:02x}{:02x}", c.r, c.g, c.b);
+      if style.font_style.contains(FontStyle::BOLD) { css.push_str("; font-weight: bold"); }
+      if style.font_style.contains(FontStyle::ITALIC) { css.push_str("; font-style: italic"); }
+      out.push_str(&format!("<span style=\"{}\">{}</span>", css, code_text(text, keep_space)));
+    }
+  }
+  out
+}
+
+// ---- 渲染 ----
+
+fn is_external(url: &str) -> bool {
+  let u = url.trim().to_ascii_lowercase();
+  u.starts_with("http://") || u.starts_with("https://")
+}
+
+pub struct RenderOptions<'a> {
+  pub platform: Platform,
+  pub theme: &'a str,
+  pub custom_css: Option<&'a str>,
+  pub footnote_links: bool,
+  pub highlight: bool,
+}
+
+// Markdown -> 内联样式的 HTML 片段（外层 <section class="flymd">）
+pub fn render(body: &str, opts: &RenderOptions) -> Result<String, String> {
+  let (_, css, code_theme) = THEMES.iter().find(|(n, _, _)| n.eq_ignore_ascii_case(opts.theme))
+    .ok_or_else(|| format!("unknown theme: {}", opts.theme))?;
+  let keep_space = opts.platform == Platform::Wechat;
+
+  let mut events: Vec<Event> = Vec::new();
+  // (地址, 链接文字)
+  let mut notes: Vec<(String, String)> = Vec::new();
+  let mut link: Option<(String, String)> = None;
+  let mut code: Option<(String, String)> = None;
+  for ev in Parser::new_ext(body, markdown::parser_options()) {
+    if let Some((lang, text)) = code.as_mut() {
+      match ev {
+        Event::Text(t) => text.push_str(&t),
+        Event::End(TagEnd::CodeBlock) => {
+          let inner = if opts.highlight { highlight_code(text, lang, code_theme, keep_space) } else { code_text(text, keep_space) };
+          let cls = if lang.is_empty() { String::new() } else { format!(" class=\"language-{}\"", escape_html(lang)) };
+          events.push(Event::Html(CowStr::from(format!("<pre><code{}>{}</code></pre>\n", cls, inner))));
+          code = None;
+        }
+        _ => {}
+      }
+      continue;
+    }
+    match ev {
+      Event::Start(Tag::CodeBlock(kind)) => {
+        let lang = match kind {
+          CodeBlockKind::Fenced(info) => info.split([' ', ',', '{']).next().unwrap_or("").to_string(),
+          CodeBlockKind::Indented => String::new(),
+        };
+        code = Some((lang, String::new()));
+      }
+      Event::Start(Tag::Link { ref dest_url, .. }) if opts.footnote_links && is_external(dest_url) => {
+        link = Some((dest_url.to_string(), String::new()));
+      }
+      Event::End(TagEnd::Link) if link.is_some() => {
+        let (url, label) = link.take().unwrap_or_default();
+        let n = match notes.iter().position(|(u, _)| *u == url) {
+          Some(i) => i + 1,
+          None => { notes.push((url, label)); notes.len() }
+        };
+        events.push(Event::Html(CowStr::from(format!("<sup class=\"footnote-ref\">[{}]</sup>", n))));
+      }
+      Event::Text(ref t) | Event::Code(ref t) if link.is_some() => {
+        if let Some((_, label)) = link.as_mut() { label.push_str(t); }
+        events.push(ev);
+      }
+      // 复选框在公众号中会被剔除，改为字符
+      Event::TaskListMarker(done) => events.push(Event::Text(CowStr::from(if done { "☑ " } else { "☐ " }))),
+      ev => events.push(ev),
+    }
+  }
+
+  let mut html = String::from("<section class=\"flymd\">\n");
+  pulldown_cmark::html::push_html(&mut html, events.into_iter());
+  if !notes.is_empty() {
+    html.push_str("<section class=\"footnotes\">\n<p class=\"footnotes-title\">参考链接</p>\n");
+    for (i, (url, label)) in notes.iter().enumerate() {
+      let label = label.trim();
+      let prefix = if label.is_empty() || label == url { String::new() } else { format!("{}: ", escape_html(label)) };
+      html.push_str(&format!("<p class=\"footnote-item\">[{}] {}<em>{}</em></p>\n", i + 1, prefix, escape_html(url)));
+    }
+    html.push_str("</section>\n");
+  }
+  html.push_str("</section>\n");
+
+  let mut rules = parse_css(THEME_BASE);
+  rules.extend(parse_css(css));
+  if let Some(extra) = opts.custom_css { rules.extend(parse_css(extra)); }
+  Ok(inline_css(&html, &rules))
+}
+
+// 图床对象名：与前端 keyTemplate（src/uploader/s3.ts）相同的占位符，时间取本地时间，{md5} 为内容的 MD5
+fn object_key(template: &str, name: &str, content_type: &str, bytes: &[u8], now: chrono::NaiveDateTime) -> String {
+  use chrono::{Datelike, Timelike};
+  use md5::Digest;
+  let (base, ext) = match name.rsplit_once('.') {
+    Some((b, e)) => (b, e.to_ascii_lowercase()),
+    None => (name, String::new()),
+  };
+  let ext = crate::attachment::ext_of(content_type).map(|e| e.to_string()).unwrap_or(ext);
+  let hash = hex::encode(md5::Md5::digest(bytes));
+  let tpl = if template.trim().is_empty() { "{year}/{month}{fileName}{md5}.{extName}" } else { template.trim() };
+  tpl
+    .replace("{year}", &now.year().to_string())
+    .replace("{month}", &format!("{:02}", now.month()))
+    .replace("{day}", &format!("{:02}", now.day()))
+    .replace("{hour}", &format!("{:02}", now.hour()))
+    .replace("{minute}", &format!("{:02}", now.minute()))
+    .replace("{second}", &format!("{:02}", now.second()))
+    .replace("{fileName}", base)
+    .replace("{extName}", &ext)
+    .replace("{md5}", &hash)
+    .trim_start_matches('/')
+    .to_string()
+}
+
+#[tauri::command]
+pub fn styled_html_themes() -> Vec<&'static str> {
+  theme_names()
+}
+
+#[tauri::command]
+pub async fn copy_styled_html(app: tauri::AppHandle, req: StyledHtmlReq) -> Result<StyledHtml, String> {
+  use tauri_plugin_clipboard_manager::ClipboardExt;
+
+  let prepared = publish::prepare(&req.doc, None).await?;
+  let doc_dir = req.doc.doc_dir();
+  let mut body = prepared.body;
+  let mut images_uploaded = 0;
+  if let Some(host) = &req.image_host {
+    let policy = UploadPolicy::from_profile(host.allowed_types.as_deref(), host.max_bytes);
+    let (b, n) = publish::upload_local_images(&body, doc_dir.as_deref(), |img: LocalImage| {
+      let policy = &policy;
+      async move {
+        let checked = policy.enforce(img.bytes, Some(&img.content_type))?;
+        let key = object_key(&host.key, &img.name, &checked.content_type, &checked.bytes, chrono::Local::now().naive_local());
+        let up = crate::UploadReq { key, bytes: Vec::new(), ..host.clone() };
+        crate::s3_put_object(&up, checked.content_type, checked.bytes).await
+      }
+    })
+    .await?;
+    body = b;
+    images_uploaded = n;
+  }
+  let mut local_images: Vec<String> = Vec::new();
+  for r in markdown::find_images(&body) {
+    if let Some(p) = resolve_local_path(&r.url, doc_dir.as_deref()) {
+      let s = p.to_string_lossy().to_string();
+      if !local_images.contains(&s) { local_images.push(s); }
+    }
+  }
+
+  let platform = req.platform;
+  let theme = req.theme.clone().filter(|t| !t.trim().is_empty()).unwrap_or_else(|| "default".into());
+  let custom_css = req.custom_css.clone();
+  let footnote_links = req.footnote_links.unwrap_or(platform == Platform::Wechat);
+  let highlight = req.highlight;
+  let md = body.clone();
+  let html = tauri::async_runtime::spawn_blocking(move || {
+    render(&md, &RenderOptions { platform, theme: theme.trim(), custom_css: custom_css.as_deref(), footnote_links, highlight })
+  })
+  .await
+  .map_err(|e| format!("join error: {e}"))??;
+
+  if req.copy {
+    app.clipboard().write_html(html.clone(), Some(body)).map_err(|e| format!("clipboard error: {e}"))?;
+  }
+  Ok(StyledHtml { html, images_uploaded, local_images, copied: req.copy })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn decls(rule: &Rule) -> Vec<(&str, &str)> {
+    rule.decls.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect()
+  }
+
+  #[test]
+  fn css_parser() {
+    let rules = parse_css("/* c { x: y } */ h1, .a.b { Color: red; ; margin:0 }\np a:hover { color: blue }\ndiv > p { color: green }\n* { }\nul.list li { padding: 0 /* x */ }");
+    let paths: Vec<Vec<(Option<&str>, Vec<&str>)>> = rules.iter()
+      .map(|r| r.path.iter().map(|c| (c.tag.as_deref(), c.classes.iter().map(|s| s.as_str()).collect())).collect())
+      .collect();
+    assert_eq!(paths, vec![
+      vec![(Some("h1"), vec![])],
+      vec![(None, vec!["a", "b"])],
+      vec![(Some("ul"), vec!["list"]), (Some("li"), vec![])],
+    ]);
+    assert_eq!(decls(&rules[0]), [("color", "red"), ("margin", "0")]);
+    assert_eq!(decls(&rules[2]), [("padding", "0")]);
+    assert_eq!(rules[1].specificity(), (2, 0));
+    assert_eq!(rules[2].specificity(), (1, 2));
+  }
+
+  #[test]
+  fn inline_specificity() {
+    let rules = parse_css(".x { color: blue } p.x { color: green } p { color: red; margin: 0 } p { margin: 1px } div p { font-size: 2px }");
+    let html = "<div><p class=\"x\" style=\"margin: 3px\">a<br>b</p><p>c</p></div><p>d</p>";
+    assert_eq!(inline_css(html, &rules), concat!(
+      "<div><p class=\"x\" style=\"font-size: 2px; color: green; margin: 3px\">a<br>b</p>",
+      "<p style=\"color: red; margin: 1px; font-size: 2px\">c</p></div>",
+      "<p style=\"color: red; margin: 1px\">d</p>",
+    ));
+    // 注释、属性中的 > 与引号原样保留
+    let html = "<!-- <p> --><img alt='a > b' src=x /><p title=\"q\">e</p>";
+    assert_eq!(inline_css(html, &parse_css("img { border: 0 } p { font-family: \"A B\" }")),
+      "<!-- <p> --><img alt=\"a > b\" src=\"x\" style=\"border: 0\" /><p title=\"q\" style=\"font-family: 'A B'\">e</p>");
+  }
+
+  #[test]
+  fn footnote_links() {
+    let body = "[one](https://a.com/x) [two](https://a.com/x) [local](#top) <https://b.org>\n";
+    let opts = RenderOptions { platform: Platform::Wechat, theme: "default", custom_css: None, footnote_links: true, highlight: false };
+    let html = render(body, &opts).unwrap();
+    assert!(html.contains("one<sup class=\"footnote-ref\""), "{html}");
+    assert_eq!(html.matches(">[1]</sup>").count(), 2);
+    assert_eq!(html.matches(">[2]</sup>").count(), 1);
+    assert!(html.contains("<a href=\"#top\""));
+    assert!(!html.contains("href=\"https://"));
+    assert!(html.contains(">[1] one: <em"), "{html}");
+    assert!(html.contains(">https://a.com/x</em>"));
+    // 文字即地址时不重复
+    assert!(html.contains(">[2] <em"));
+
+    let html = render(body, &RenderOptions { footnote_links: false, ..opts }).unwrap();
+    assert!(html.contains("href=\"https://a.com/x\"") && !html.contains("footnote"));
+    assert!(render(body, &RenderOptions { theme: "nope", ..opts }).is_err());
+  }
+
+  #[test]
+  fn object_keys() {
+    let now = chrono::NaiveDate::from_ymd_opt(2025, 3, 4).unwrap().and_hms_opt(5, 6, 7).unwrap();
+    assert_eq!(object_key("", "pic.PNG", "image/png", b"hello", now), "2025/03pic5d41402abc4b2a76b9719d911017c592.png");
+    assert_eq!(
+      object_key("/img/{year}-{month}-{day}/{hour}{minute}{second}/{fileName}.{extName}", "a.b.jpeg", "image/jpeg", b"", now),
+      "img/2025-03-04/050607/a.b.jpg"
+    );
+    // 内容类型未知时保留原扩展名（小写）；无扩展名时为空
+    assert_eq!(object_key("{md5}.{extName}", "x.WEBM", "video/x-unknown", b"", now), "d41d8cd98f00b204e9800998ecf8427e.webm");
+    assert_eq!(object_key("{fileName}.{extName}", "noext", "application/x-unknown", b"", now), "noext.");
+  }
+}
@@ -0,0 +1,87 @@
+// 文件名与落盘路径工具：清洗外部来源的文件名，生成不覆盖已有文件的目标路径
+
+use std::path::{Path, PathBuf};
+
+// Windows 保留设备名（不区分大小写，带扩展名同样保留）
+const RESERVED_NAMES: &[&str] = &[
+  "con", "prn", "aux", "nul",
+  "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8", "com9",
+  "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+// 清洗文件名：去除路径分隔符、控制字符与各平台非法字符，避免 ../ 穿越与保留名
+pub fn sanitize_file_name(raw: &str, fallback: &str) -> String {
+  let decoded = percent_encoding::percent_decode_str(raw).decode_utf8_lossy().to_string();
+  // 只取最后一段，防止携带目录
+  let last = decoded.rsplit(['/', '\\']).next().unwrap_or("");
+  let mut name: String = last
+    .chars()
+    .map(|c| if c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*') { '_' } else { c })
+    .collect();
+  // Windows 不允许结尾为点或空格；开头的点会变成隐藏文件
+  name = name.trim().trim_matches('.').trim().to_string();
+  if name.chars().count() > 180 {
+    let (stem, ext) = split_ext(&name);
+    let stem: String = stem.chars().take(160).collect();
+    name = format!("{}{}", stem, ext);
+  }
+  let stem_lower = split_ext(&name).0.to_ascii_lowercase();
+  if name.is_empty() || RESERVED_NAMES.contains(&stem_lower.as_str()) {
+    return if name.is_empty() { fallback.to_string() } else { format!("_{}", name) };
+  }
+  name
+}
+
+// 拆分为 (主名, ".扩展名")；无扩展名时第二项为空
+pub fn split_ext(name: &str) -> (&str, &str) {
+  match name.rfind('.') {
+    Some(i) if i > 0 => (&name[..i], &name[i..]),
+    _ => (name, ""),
+  }
+}
+
+// 目录下依次尝试的文件名：name.ext -> name (1).ext -> name (2).ext ...
+pub fn numbered_paths<'a>(dir: &'a Path, name: &'a str) -> impl Iterator<Item = PathBuf> + 'a {
+  let (stem, ext) = split_ext(name);
+  (0u32..).map(move |i| if i == 0 { dir.join(name) } else { dir.join(format!("{} ({}){}", stem, i, ext)) })
+}
+
+// 以不覆盖已有文件的方式新建文件；create_new 保证检查与创建是一步完成的，
+// 不会在 exists() 与写入之间被其他写入者抢先
+pub fn create_non_clobbering(dir: &Path, name: &str) -> std::io::Result<(PathBuf, std::fs::File)> {
+  for cand in numbered_paths(dir, name).take(10_000) {
+    match std::fs::OpenOptions::new().write(true).create_new(true).open(&cand) {
+      Ok(f) => return Ok((cand, f)),
+      Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+      Err(e) => return Err(e),
+    }
+  }
+  Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("too many files named like {}", name)))
+}
+
+// 目录所在分区对当前用户可用的空间（字节）；无法获取时返回 None，调用方不做限制
+#[cfg(unix)]
+pub fn available_space(dir: &Path) -> Option<u64> {
+  use std::os::unix::ffi::OsStrExt;
+  let c = std::ffi::CString::new(dir.as_os_str().as_bytes()).ok()?;
+  let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+  if unsafe { libc::statvfs(c.as_ptr(), &mut st) } != 0 { return None; }
+  #[allow(clippy::useless_conversion)]
+  Some(u64::from(st.f_bavail).saturating_mul(u64::from(st.f_frsize)))
+}
+
+#[cfg(windows)]
+pub fn available_space(dir: &Path) -> Option<u64> {
+  use std::os::windows::ffi::OsStrExt;
+  let wide: Vec<u16> = dir.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+  let mut free = 0u64;
+  let ok = unsafe {
+    windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW(wide.as_ptr(), &mut free, std::ptr::null_mut(), std::ptr::null_mut())
+  };
+  if ok == 0 { None } else { Some(free) }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn available_space(_dir: &Path) -> Option<u64> {
+  None
+}
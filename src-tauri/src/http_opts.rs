@@ -0,0 +1,237 @@
+// 可配置的 HTTP 选项（按次调用或随发布配置传入）：超时、附加请求头、Basic/Bearer 认证、Cookie、代理、自定义 CA
+// 以及区分配置、网络、TLS、HTTP 状态的结构化错误
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, COOKIE};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpOptions {
+  // 秒；缺省或 0 时使用调用方的默认值
+  #[serde(default)]
+  pub timeout_secs: Option<u64>,
+  // 附加请求头，如 Cloudflare Access 的 CF-Access-Client-Id / CF-Access-Client-Secret
+  #[serde(default)]
+  pub headers: HashMap<String, String>,
+  #[serde(default)]
+  pub user_agent: Option<String>,
+  // 站点前置的认证（反向代理 Basic 认证、网关 Token 等）
+  #[serde(default)]
+  pub auth: Option<HttpAuth>,
+  // 预置 Cookie："a=1; b=2"
+  #[serde(default)]
+  pub cookies: Option<String>,
+  // 启用进程内共享的 Cookie Jar，保存服务端 Set-Cookie 供后续请求使用
+  #[serde(default)]
+  pub cookie_jar: bool,
+  // 代理地址，如 http://127.0.0.1:7890
+  #[serde(default)]
+  pub proxy: Option<String>,
+  // 自定义 CA：PEM 文本或证书文件路径（PEM/DER），用于自签名证书的站点
+  #[serde(default)]
+  pub ca_cert: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum HttpAuth {
+  Basic {
+    username: String,
+    #[serde(default)]
+    password: String,
+  },
+  Bearer {
+    token: String,
+  },
+}
+
+impl HttpAuth {
+  fn header_value(&self) -> String {
+    use base64::Engine;
+    match self {
+      HttpAuth::Basic { username, password } => {
+        format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password)))
+      }
+      HttpAuth::Bearer { token } => format!("Bearer {}", token.trim()),
+    }
+  }
+}
+
+// 结构化错误：前端按 kind 区分（config / network / tls / http）
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum HttpError {
+  Config { message: String },
+  Network { message: String },
+  Tls { message: String },
+  Http { status: u16, body: String },
+}
+
+impl std::fmt::Display for HttpError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      HttpError::Config { message } => write!(f, "http config error: {}", message),
+      HttpError::Network { message } => write!(f, "network error: {}", message),
+      HttpError::Tls { message } => write!(f, "tls error: {}", message),
+      HttpError::Http { status, body } => write!(f, "HTTP {}: {}", status, body),
+    }
+  }
+}
+
+impl From<HttpError> for String {
+  fn from(e: HttpError) -> String { e.to_string() }
+}
+
+// reqwest 的顶层信息很笼统（error sending request），拼上完整的 source 链
+fn error_chain(e: &dyn std::error::Error) -> String {
+  let mut parts = vec![e.to_string()];
+  let mut cur = e.source();
+  while let Some(s) = cur {
+    let m = s.to_string();
+    if !parts.iter().any(|p| p.contains(&m)) { parts.push(m); }
+    cur = s.source();
+  }
+  parts.join(": ")
+}
+
+// 沿 source 链查找 rustls 的错误；io::Error 的 source() 会跳过它包装的那层错误，要经 get_ref 进入
+fn tls_cause<'a>(e: &'a (dyn std::error::Error + 'static)) -> Option<&'a rustls::Error> {
+  let mut cur = Some(e);
+  while let Some(err) = cur {
+    if let Some(t) = err.downcast_ref::<rustls::Error>() { return Some(t); }
+    cur = match err.downcast_ref::<std::io::Error>().and_then(|io| io.get_ref()) {
+      Some(inner) => Some(inner as &(dyn std::error::Error + 'static)),
+      None => err.source(),
+    };
+  }
+  None
+}
+
+impl From<reqwest::Error> for HttpError {
+  fn from(e: reqwest::Error) -> Self {
+    let message = error_chain(&e);
+    if e.is_builder() {
+      HttpError::Config { message }
+    } else if tls_cause(&e).is_some() {
+      HttpError::Tls { message }
+    } else if let Some(s) = e.status() {
+      HttpError::Http { status: s.as_u16(), body: message }
+    } else if e.is_timeout() {
+      HttpError::Network { message: format!("timed out: {}", message) }
+    } else {
+      HttpError::Network { message }
+    }
+  }
+}
+
+fn config_err(message: String) -> HttpError { HttpError::Config { message } }
+
+static COOKIE_JAR: OnceLock<Arc<reqwest::cookie::Jar>> = OnceLock::new();
+
+fn load_ca(src: &str) -> Result<Vec<reqwest::Certificate>, HttpError> {
+  let src = src.trim();
+  let bytes = if src.contains("-----BEGIN") {
+    src.as_bytes().to_vec()
+  } else {
+    std::fs::read(src).map_err(|e| config_err(format!("read ca {} error: {e}", src)))?
+  };
+  if bytes.windows(10).any(|w| w == b"-----BEGIN") {
+    reqwest::Certificate::from_pem_bundle(&bytes).map_err(|e| config_err(format!("invalid ca pem: {e}")))
+  } else {
+    reqwest::Certificate::from_der(&bytes).map(|c| vec![c]).map_err(|e| config_err(format!("invalid ca der: {e}")))
+  }
+}
+
+impl HttpOptions {
+  // 构建客户端；url 用于把预置 Cookie 写入共享 Jar 时确定域名
+  pub fn client(&self, url: &str, default_timeout: Duration, default_ua: &str) -> Result<reqwest::Client, HttpError> {
+    let timeout = self.timeout_secs.filter(|s| *s > 0).map(Duration::from_secs).unwrap_or(default_timeout);
+    let ua = self.user_agent.as_deref().map(str::trim).filter(|s| !s.is_empty()).unwrap_or(default_ua);
+    let mut b = reqwest::Client::builder().timeout(timeout).user_agent(ua);
+
+    let mut headers = HeaderMap::new();
+    for (k, v) in &self.headers {
+      let name = HeaderName::from_bytes(k.trim().as_bytes()).map_err(|e| config_err(format!("invalid header name {}: {e}", k)))?;
+      let value = HeaderValue::from_str(v.trim()).map_err(|e| config_err(format!("invalid header value for {}: {e}", k)))?;
+      headers.insert(name, value);
+    }
+    if let Some(auth) = &self.auth {
+      let mut value = HeaderValue::from_str(&auth.header_value()).map_err(|e| config_err(format!("invalid auth: {e}")))?;
+      value.set_sensitive(true);
+      headers.insert(AUTHORIZATION, value);
+    }
+    let cookies = self.cookies.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    if self.cookie_jar {
+      let jar = COOKIE_JAR.get_or_init(|| Arc::new(reqwest::cookie::Jar::default())).clone();
+      // 启用 Jar 时 Cookie 头由 Jar 生成，预置值需先放入 Jar
+      if let (Some(c), Ok(u)) = (cookies, url::Url::parse(url)) {
+        for part in c.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+          jar.add_cookie_str(&format!("{}; Path=/", part), &u);
+        }
+      }
+      b = b.cookie_provider(jar);
+    } else if let Some(c) = cookies {
+      headers.insert(COOKIE, HeaderValue::from_str(c).map_err(|e| config_err(format!("invalid cookies: {e}")))?);
+    }
+    b = b.default_headers(headers);
+
+    if let Some(p) = self.proxy.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+      b = b.proxy(reqwest::Proxy::all(p).map_err(|e| config_err(format!("invalid proxy {}: {e}", p)))?);
+    }
+    if let Some(ca) = self.ca_cert.as_deref().filter(|s| !s.trim().is_empty()) {
+      for cert in load_ca(ca)? { b = b.add_root_certificate(cert); }
+    }
+    b.build().map_err(HttpError::from)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Write;
+
+  fn client() -> reqwest::Client {
+    HttpOptions::default().client("http://127.0.0.1", Duration::from_secs(5), "flymd-test").unwrap()
+  }
+
+  #[test]
+  fn finds_tls_cause_through_io_error() {
+    let io = std::io::Error::new(std::io::ErrorKind::InvalidData, rustls::Error::InvalidCertificate(rustls::CertificateError::UnknownIssuer));
+    assert!(matches!(tls_cause(&io), Some(rustls::Error::InvalidCertificate(_))));
+    // hyper 会把 io::Error 再包一层
+    let nested = std::io::Error::new(std::io::ErrorKind::ConnectionAborted, io);
+    assert!(tls_cause(&nested).is_some());
+    let plain = std::io::Error::new(std::io::ErrorKind::InvalidData, "certificate expired (just text)");
+    assert!(tls_cause(&plain).is_none());
+  }
+
+  #[tokio::test]
+  async fn classifies_errors() {
+    // 地址中带 certificate/tls 字样的普通连接失败不算 TLS 错误
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = closed.local_addr().unwrap().port();
+    drop(closed);
+    let e = client().get(format!("http://127.0.0.1:{}/tls/certificate", port)).send().await.unwrap_err();
+    assert!(matches!(HttpError::from(e), HttpError::Network { .. }));
+
+    // 以 https 连接明文 HTTP 服务：握手失败归为 TLS
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+      if let Ok((mut s, _)) = listener.accept() {
+        let _ = s.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n");
+      }
+    });
+    let e = client().get(format!("https://127.0.0.1:{}/", port)).send().await.unwrap_err();
+    match HttpError::from(e) {
+      HttpError::Tls { .. } => {}
+      other => panic!("expected tls error, got {:?}", other),
+    }
+
+    let e = client().get("http://[::1").send().await.unwrap_err();
+    assert!(matches!(HttpError::from(e), HttpError::Config { .. }));
+  }
+}
@@ -12,8 +12,21 @@ use sha2::Digest;
 use chrono::{DateTime, Utc};
 use std::time::Duration;
 
-
-#[derive(Debug, Deserialize)]
+mod upload_policy;
+mod attachment;
+mod fsutil;
+mod http_opts;
+mod xmlrpc;
+mod markdown;
+mod transform;
+mod publish;
+mod export;
+mod semver;
+mod updater;
+#[cfg(test)]
+mod test_http;
+
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct UploadReq {
   access_key_id: String,
@@ -32,7 +45,13 @@ struct UploadReq {
   key: String,
   #[serde(default)]
   content_type: Option<String>,
-  // 前端可传 Uint8Array -> Vec<u8>
+  // 图床配置中的上传策略：允许的 MIME 列表与大小上限（缺省使用内置默认值）
+  #[serde(default)]
+  allowed_types: Option<Vec<String>>,
+  #[serde(default)]
+  max_bytes: Option<u64>,
+  // 前端可传 Uint8Array -> Vec<u8>；仅作为图床配置传入时可省略
+  #[serde(default)]
   bytes: Vec<u8>,
 }
 
@@ -44,6 +63,8 @@ impl UploadReq {
 struct UploadResp {
   key: String,
   public_url: String,
+  // 实际上传使用的类型（按文件头嗅探）
+  content_type: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -72,7 +93,107 @@ struct PresignResp {
 }
 
 #[tauri::command]
-async fn upload_to_s3(req: UploadReq) -> Result<UploadResp, String> {
+async fn upload_to_s3(mut req: UploadReq) -> Result<UploadResp, String> {
+  // 先按策略校验：真实类型、白名单、大小；SVG 清洗后再上传
+  let policy = upload_policy::UploadPolicy::from_profile(req.allowed_types.as_deref(), req.max_bytes);
+  let checked = policy.enforce(std::mem::take(&mut req.bytes), req.content_type.as_deref())?;
+  let public_url = s3_put_object(&req, checked.content_type.clone(), checked.bytes).await?;
+  Ok(UploadResp { key: req.key, public_url, content_type: checked.content_type })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttachmentUploadReq {
+  #[serde(flatten)]
+  upload: UploadReq,
+  // 原始文件名：用于插入的链接文字及 OLE/Office 类型细化
+  file_name: String,
+  // 拖入文件的本地路径：未传 bytes 时由后端读取，避免大文件以数字数组经 IPC 传输
+  #[serde(default)]
+  src_path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AttachmentResp {
+  // 图床上传时为对象 key；本地保存时为绝对路径
+  key: String,
+  url: String,
+  kind: attachment::AttachmentKind,
+  content_type: String,
+  markdown: String,
+}
+
+// 非图片附件（PDF/Office/压缩包/音视频）上传到图床，返回与类型匹配的 Markdown
+#[tauri::command]
+async fn upload_attachment(mut req: AttachmentUploadReq) -> Result<AttachmentResp, String> {
+  let mut bytes = std::mem::take(&mut req.upload.bytes);
+  if bytes.is_empty() {
+    if let Some(p) = req.src_path.clone() {
+      bytes = tauri::async_runtime::spawn_blocking(move || std::fs::read(p))
+        .await
+        .map_err(|e| format!("join error: {e}"))?
+        .map_err(|e| format!("read error: {e}"))?;
+    }
+  }
+  let up = &req.upload;
+  let (info, bytes) = attachment::check_attachment(bytes, &req.file_name, up.content_type.as_deref(), up.allowed_types.as_deref(), up.max_bytes)?;
+  let public_url = s3_put_object(up, info.content_type.clone(), bytes).await?;
+  let markdown = attachment::markdown_for(info.kind, &req.file_name, &public_url);
+  Ok(AttachmentResp { key: up.key.clone(), url: public_url, kind: info.kind, content_type: info.content_type, markdown })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LocalAttachmentReq {
+  // 当前文档路径：附件保存到其同级的 attachments/ 目录
+  doc_path: String,
+  file_name: String,
+  // 拖入文件的本地路径与内容二选一
+  #[serde(default)]
+  src_path: Option<String>,
+  #[serde(default)]
+  bytes: Option<Vec<u8>>,
+  #[serde(default)]
+  dir_name: Option<String>,
+  #[serde(default)]
+  max_bytes: Option<u64>,
+}
+
+// 未配置图床时：复制到文档旁的 attachments/ 目录（不覆盖同名文件），返回相对路径链接
+#[tauri::command]
+async fn save_attachment_local(req: LocalAttachmentReq) -> Result<AttachmentResp, String> {
+  use std::path::PathBuf;
+  tauri::async_runtime::spawn_blocking(move || {
+    let bytes = match (&req.bytes, &req.src_path) {
+      (Some(b), _) if !b.is_empty() => b.clone(),
+      (_, Some(p)) => std::fs::read(p).map_err(|e| format!("read error: {e}"))?,
+      _ => return Err("no attachment content".to_string()),
+    };
+    let (info, bytes) = attachment::check_attachment(bytes, &req.file_name, None, None, req.max_bytes)?;
+    let doc_dir = PathBuf::from(&req.doc_path).parent().map(|p| p.to_path_buf()).ok_or_else(|| "invalid doc path".to_string())?;
+    let dir_name = fsutil::sanitize_file_name(req.dir_name.as_deref().unwrap_or("attachments"), "attachments");
+    let dir = doc_dir.join(&dir_name);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("create_dir_all error: {e}"))?;
+    let fallback = format!("attachment.{}", info.ext);
+    let name = fsutil::sanitize_file_name(&req.file_name, &fallback);
+    let (target, mut f) = fsutil::create_non_clobbering(&dir, &name).map_err(|e| format!("create file error: {e}"))?;
+    if let Err(e) = std::io::Write::write_all(&mut f, &bytes) {
+      drop(f);
+      let _ = std::fs::remove_file(&target);
+      return Err(format!("write error: {e}"));
+    }
+    let saved_name = target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or(name);
+    let rel = format!("{}/{}", dir_name, saved_name);
+    let markdown = attachment::markdown_for(info.kind, &req.file_name, &rel);
+    Ok(AttachmentResp { key: target.to_string_lossy().to_string(), url: rel, kind: info.kind, content_type: info.content_type, markdown })
+  })
+  .await
+  .map_err(|e| format!("join error: {e}"))?
+}
+
+// 直传对象并返回外链；类型与内容须已通过上传策略校验
+async fn s3_put_object(req: &UploadReq, content_type: String, bytes: Vec<u8>) -> Result<String, String> {
   // 使用 AWS SDK for Rust 直传，行为与 PicList（SDK）一致；仅构建机需工具链，用户零依赖。
   use aws_sdk_s3 as s3;
   use aws_config::meta::region::RegionProviderChain;
@@ -106,8 +227,8 @@ async fn upload_to_s3(req: UploadReq) -> Result<UploadResp, String> {
     .put_object()
     .bucket(req.bucket.clone())
     .key(req.key.clone())
-    .body(ByteStream::from(req.bytes.clone()));
-  if let Some(ct) = &req.content_type { if !ct.is_empty() { put = put.content_type(ct); } }
+    .body(ByteStream::from(bytes))
+    .content_type(content_type);
   if req.acl_public_read { put = put.acl(ObjectCannedAcl::PublicRead); }
   put.send().await.map_err(|e| format!("put_object error: {e}"))?;
 
@@ -133,7 +254,7 @@ async fn upload_to_s3(req: UploadReq) -> Result<UploadResp, String> {
     if req.force_path_style { format!("https://s3.amazonaws.com/{}/{}", req.bucket, key_enc) } else { format!("https://{}.s3.amazonaws.com/{}", req.bucket, key_enc) }
   };
 
-  Ok(UploadResp { key: req.key, public_url })
+  Ok(public_url)
 }
 
 #[tauri::command]
@@ -267,31 +388,42 @@ async fn presign_put(req: PresignReq) -> Result<PresignResp, String> {
 struct XmlHttpReq {
   url: String,
   xml: String,
+  // 超时、认证、Cookie、代理、自定义 CA 等，缺省与原行为一致
+  #[serde(default)]
+  options: http_opts::HttpOptions,
 }
 
 #[tauri::command]
-async fn http_xmlrpc_post(req: XmlHttpReq) -> Result<String, String> {
-  let client = reqwest::Client::builder()
-    .timeout(Duration::from_secs(20))
-    .build()
-    .map_err(|e| format!("client error: {e}"))?;
+async fn http_xmlrpc_post(req: XmlHttpReq) -> Result<String, http_opts::HttpError> {
+  let client = req.options.client(&req.url, Duration::from_secs(20), "flymd-typecho-publisher/0.1")?;
   let res = client
     .post(&req.url)
     .header("Content-Type", "text/xml; charset=UTF-8")
     .header("Accept", "text/xml, */*;q=0.1")
-    .header("User-Agent", "flymd-typecho-publisher/0.1")
     .body(req.xml)
     .send()
-    .await
-    .map_err(|e| format!("send error: {e}"))?;
+    .await?;
   let status = res.status();
-  let text = res.text().await.map_err(|e| format!("read error: {e}"))?;
+  let text = res.text().await.map_err(|e| http_opts::HttpError::Network { message: format!("read error: {e}") })?;
   if !status.is_success() {
-    return Err(format!("HTTP {}: {}", status.as_u16(), text));
+    return Err(http_opts::HttpError::Http { status: status.as_u16(), body: text });
   }
   Ok(text)
 }
 
+// 类型化 XML-RPC 调用：参数为 JSON（特殊值见 xmlrpc 模块约定），返回 JSON 或结构化 fault
+#[tauri::command]
+async fn xmlrpc_call(url: String, method: String, params: Option<Vec<serde_json::Value>>, options: Option<http_opts::HttpOptions>) -> Result<serde_json::Value, xmlrpc::XmlRpcError> {
+  let params = params
+    .unwrap_or_default()
+    .iter()
+    .map(xmlrpc::Value::from_json)
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|message| xmlrpc::XmlRpcError::Parse { message })?;
+  let v = xmlrpc::call(&url, &method, &params, &options.unwrap_or_default()).await?;
+  Ok(v.to_json())
+}
+
 fn main() {
   tauri::Builder::default()
     .manage(PendingOpenPath::default())
@@ -300,9 +432,12 @@ fn main() {
     .plugin(tauri_plugin_store::Builder::default().build())
     .plugin(tauri_plugin_opener::init())
     .plugin(tauri_plugin_http::init())
+    .plugin(tauri_plugin_clipboard_manager::init())
     .plugin(tauri_plugin_window_state::Builder::default().build())
     .invoke_handler(tauri::generate_handler![
       upload_to_s3,
+      upload_attachment,
+      save_attachment_local,
       presign_put,
       move_to_trash,
       force_remove_path,
@@ -310,9 +445,41 @@ fn main() {
       write_text_file_any,
       get_pending_open_path,
       http_xmlrpc_post,
-      check_update,
-      download_file,
-      run_installer,
+      xmlrpc_call,
+      publish::metaweblog::metaweblog_get_users_blogs,
+      publish::metaweblog::metaweblog_get_categories,
+      publish::metaweblog::metaweblog_get_post,
+      publish::metaweblog::metaweblog_delete_post,
+      publish::metaweblog::metaweblog_new_media_object,
+      publish::metaweblog::metaweblog_publish,
+      publish::wordpress::wordpress_list_terms,
+      publish::wordpress::wordpress_create_term,
+      publish::wordpress::wordpress_upload_media,
+      publish::wordpress::wordpress_publish,
+      publish::ghost::ghost_list_tags,
+      publish::ghost::ghost_upload_image,
+      publish::ghost::ghost_publish,
+      publish::halo::halo_list_terms,
+      publish::halo::halo_upload_attachment,
+      publish::halo::halo_publish,
+      publish::state::publish_status,
+      publish::publish_preview,
+      export::site::export_site,
+      export::ssg::export_ssg,
+      export::feed::export_feed,
+      export::styled::styled_html_themes,
+      export::styled::copy_styled_html,
+      updater::check_update,
+      updater::download_file,
+      updater::cancel_download,
+      updater::restart_after_update,
+      updater::settings::get_update_settings,
+      updater::settings::set_update_settings,
+      updater::settings::skip_update_version,
+      updater::settings::remind_update_later,
+      updater::run_installer,
+      updater::rollback_update,
+      updater::scheduler::get_update_status,
       // Android SAF 命令
       android_pick_document,
       android_create_document,
@@ -352,6 +519,8 @@ fn main() {
           }
         }
       }
+      // 后台定时检查更新
+      updater::scheduler::start(app.handle().clone());
       // 其它初始化逻辑
       if let Some(win) = app.get_webview_window("main") {
         #[cfg(target_os = "windows")]
@@ -375,258 +544,6 @@ fn main() {
     .expect("error while running tauri application");
 }
 
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct UpdateAssetInfo {
-  name: String,
-  size: u64,
-  direct_url: String,
-  proxy_url: String,
-}
-
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct CheckUpdateResp {
-  has_update: bool,
-  current: String,
-  latest: String,
-  release_name: String,
-  notes: String,
-  html_url: String,
-  // Windows 推荐资产
-  asset_win: Option<UpdateAssetInfo>,
-  // Linux 双资产
-  asset_linux_appimage: Option<UpdateAssetInfo>,
-  asset_linux_deb: Option<UpdateAssetInfo>,
-}
-
-fn norm_ver(v: &str) -> (i64, i64, i64, i64) {
-  // 版本比较：major.minor.patch + 权重（fix>无后缀>预发行）
-  let s = v.trim().trim_start_matches('v');
-  let mut parts = s.splitn(2, '-');
-  let core = parts.next().unwrap_or("");
-  let suffix = parts.next().unwrap_or("").to_ascii_lowercase();
-  let mut nums = core.split('.').take(3).map(|x| x.parse::<i64>().unwrap_or(0)).collect::<Vec<_>>();
-  while nums.len() < 3 { nums.push(0); }
-  let weight = if suffix.starts_with("fix") { 2 } else if suffix.is_empty() { 1 } else { 0 };
-  (nums[0], nums[1], nums[2], weight)
-}
-
-fn is_better(a: &(i64,i64,i64,i64), b: &(i64,i64,i64,i64)) -> bool {
-  // a > b ?
-  a.0 > b.0 || (a.0==b.0 && (a.1 > b.1 || (a.1==b.1 && (a.2 > b.2 || (a.2==b.2 && a.3 > b.3)))))
-}
-
-#[derive(Debug, Deserialize)]
-struct GhAsset {
-  name: String,
-  browser_download_url: String,
-  size: Option<u64>,
-  #[allow(dead_code)]
-  content_type: Option<String>,
-}
-#[derive(Debug, Deserialize)]
-struct GhRelease {
-  tag_name: String,
-  name: Option<String>,
-  body: Option<String>,
-  draft: bool,
-  prerelease: bool,
-  html_url: String,
-  assets: Vec<GhAsset>,
-}
-
-fn gh_proxy_url(raw: &str) -> String {
-  // 代理前缀：按“https://gh-proxy.comb/原始URL”拼接
-  let prefix = "https://gh-proxy.com/";
-  if raw.starts_with(prefix) { raw.to_string() } else { format!("{}{}", prefix, raw) }
-}
-
-fn os_arch_tag() -> (&'static str, &'static str) {
-  let os = {
-    #[cfg(target_os = "windows")] { "windows" }
-    #[cfg(target_os = "linux")] { "linux" }
-    #[cfg(target_os = "macos")] { "macos" }
-    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))] { "other" }
-  };
-  let arch = {
-    #[cfg(target_arch = "x86_64")] { "x86_64" }
-    #[cfg(target_arch = "aarch64")] { "aarch64" }
-    #[cfg(target_arch = "x86")] { "x86" }
-    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "x86")))] { "other" }
-  };
-  (os, arch)
-}
-
-fn match_linux_assets(assets: &[GhAsset]) -> (Option<&GhAsset>, Option<&GhAsset>) {
-  // 返回 (AppImage, Deb)
-  let mut appimage: Option<&GhAsset> = None;
-  let mut deb: Option<&GhAsset> = None;
-  for a in assets {
-    let n = a.name.to_ascii_lowercase();
-    // 排除 ARM 相关
-    let is_arm = n.contains("arm64") || n.contains("aarch64") || n.contains("armv7");
-    if is_arm { continue; }
-    if n.ends_with(".appimage") && (n.contains("x86_64") || n.contains("amd64")) {
-      if appimage.is_none() { appimage = Some(a); }
-    } else if n.ends_with(".deb") && (n.contains("x86_64") || n.contains("amd64")) {
-      if deb.is_none() { deb = Some(a); }
-    }
-  }
-  (appimage, deb)
-}
-
-fn match_windows_asset(assets: &[GhAsset]) -> Option<&GhAsset> {
-  for a in assets {
-    let n = a.name.to_ascii_lowercase();
-    let is_arm = n.contains("arm64") || n.contains("aarch64") || n.contains("armv7");
-    if is_arm { continue; }
-    if (n.ends_with(".exe") || n.ends_with(".msi")) && (n.contains("x64") || n.contains("x86_64") || n.contains("amd64")) {
-      return Some(a);
-    }
-  }
-  None
-}
-
-#[tauri::command]
-async fn check_update(_force: Option<bool>, include_prerelease: Option<bool>) -> Result<CheckUpdateResp, String> {
-  // 当前版本：与 tauri.conf.json 一致（构建时可由环境注入，这里直接读取 Cargo.toml 同步版本）
-  let current = env!("CARGO_PKG_VERSION").to_string();
-  let (os_tag, _arch_tag) = os_arch_tag();
-
-  // 节流留空：简单实现始终请求（前端可决定调用频率）
-
-  let url = "https://api.github.com/repos/flyhunterl/flymd/releases";
-  let client = reqwest::Client::builder()
-    .user_agent("flymd-updater")
-    .build()
-    .map_err(|e| format!("build client error: {e}"))?;
-  let resp = client
-    .get(url)
-    .header("Accept", "application/vnd.github+json")
-    .send().await.map_err(|e| format!("request error: {e}"))?;
-  if !resp.status().is_success() { return Err(format!("http status {}", resp.status())); }
-  let releases: Vec<GhRelease> = resp.json().await.map_err(|e| format!("json error: {e}"))?;
-  let include_pre = include_prerelease.unwrap_or(false);
-  let latest = releases.into_iter().find(|r| !r.draft && (include_pre || !r.prerelease))
-    .ok_or_else(|| "no release found".to_string())?;
-
-  let latest_tag = latest.tag_name.trim().to_string();
-  let n_cur = norm_ver(&current);
-  let n_new = norm_ver(&latest_tag);
-  let has_update = is_better(&n_new, &n_cur);
-
-  // 组装资产信息
-  let mut asset_win = None;
-  let mut asset_linux_appimage = None;
-  let mut asset_linux_deb = None;
-  if os_tag == "windows" {
-    if let Some(a) = match_windows_asset(&latest.assets) {
-      asset_win = Some(UpdateAssetInfo{
-        name: a.name.clone(),
-        size: a.size.unwrap_or(0),
-        direct_url: a.browser_download_url.clone(),
-        proxy_url: gh_proxy_url(&a.browser_download_url),
-      });
-    }
-  } else if os_tag == "linux" {
-    let (ai, deb) = match_linux_assets(&latest.assets);
-    if let Some(a) = ai {
-      asset_linux_appimage = Some(UpdateAssetInfo{
-        name: a.name.clone(),
-        size: a.size.unwrap_or(0),
-        direct_url: a.browser_download_url.clone(),
-        proxy_url: gh_proxy_url(&a.browser_download_url),
-      });
-    }
-    if let Some(a) = deb {
-      asset_linux_deb = Some(UpdateAssetInfo{
-        name: a.name.clone(),
-        size: a.size.unwrap_or(0),
-        direct_url: a.browser_download_url.clone(),
-        proxy_url: gh_proxy_url(&a.browser_download_url),
-      });
-    }
-  }
-
-  let notes = latest.body.unwrap_or_default();
-  let name = latest.name.unwrap_or_else(|| latest_tag.clone());
-
-  Ok(CheckUpdateResp{
-    has_update,
-    current,
-    latest: latest_tag,
-    release_name: name,
-    notes,
-    html_url: latest.html_url,
-    asset_win,
-    asset_linux_appimage,
-    asset_linux_deb,
-  })
-}
-
-#[tauri::command]
-#[allow(unused_assignments)]
-async fn download_file(url: String, use_proxy: Option<bool>) -> Result<String, String> {
-  let client = reqwest::Client::builder()
-    .user_agent("flymd-updater")
-    .build()
-    .map_err(|e| format!("build client error: {e}"))?;
-
-  // 解析文件名
-  let (direct, proxy) = {
-    let u = url::Url::parse(&url).map_err(|e| format!("invalid url: {e}"))?;
-    let fname = u
-      .path_segments()
-      .and_then(|mut s| s.next_back())
-      .unwrap_or("download.bin")
-      .to_string();
-        // 保存到用户下载目录（不可用时回退到临时目录）
-    #[cfg(target_os = "windows")]
-    let base_download = std::env::var("USERPROFILE")
-      .map(|p| std::path::PathBuf::from(p).join("Downloads"))
-      .unwrap_or_else(|_| std::env::temp_dir());
-    #[cfg(not(target_os = "windows"))]
-    let base_download = std::env::var("HOME")
-      .map(|p| std::path::PathBuf::from(p).join("Downloads"))
-      .unwrap_or_else(|_| std::env::temp_dir());
-    let mut path = base_download.clone();
-    path.push(&fname);
-    let direct = (u, path);
-    let proxy = (
-      url::Url::parse(&gh_proxy_url(&url)).map_err(|e| format!("invalid proxy url: {e}"))?,
-      base_download.join(&fname)
-    );
-    (direct, proxy)
-  };
-
-  // 下载函数
-  async fn do_fetch(client: &reqwest::Client, url: &url::Url, save: &std::path::Path) -> Result<(), String> {
-    let resp = client.get(url.clone()).send().await.map_err(|e| format!("request error: {e}"))?;
-    if !resp.status().is_success() { return Err(format!("http status {}", resp.status())); }
-    let mut f = std::fs::File::create(save).map_err(|e| format!("create file error: {e}"))?;
-    let mut stream = resp.bytes_stream();
-    use futures_util::StreamExt;
-    while let Some(chunk) = stream.next().await {
-      let bytes = chunk.map_err(|e| format!("read chunk error: {e}"))?;
-      std::io::Write::write_all(&mut f, &bytes).map_err(|e| format!("write error: {e}"))?;
-    }
-    Ok(())
-  }
-
-  let want_proxy = use_proxy.unwrap_or(false);
-  let mut last_err: Option<String> = None;
-  if want_proxy {
-    if let Err(e) = do_fetch(&client, &proxy.0, &proxy.1).await { last_err = Some(e); } else { return Ok(proxy.1.to_string_lossy().to_string()); }
-    // 代理失败 -> 尝试直连
-    if let Err(e) = do_fetch(&client, &direct.0, &direct.1).await { last_err = Some(e); } else { return Ok(direct.1.to_string_lossy().to_string()); }
-  } else {
-    if let Err(e) = do_fetch(&client, &direct.0, &direct.1).await { last_err = Some(e); } else { return Ok(direct.1.to_string_lossy().to_string()); }
-    // 直连失败 -> 尝试代理
-    if let Err(e) = do_fetch(&client, &proxy.0, &proxy.1).await { last_err = Some(e); } else { return Ok(proxy.1.to_string_lossy().to_string()); }
-  }
-  Err(last_err.unwrap_or_else(|| "download failed".into()))
-}
 
 #[tauri::command]
 async fn read_text_file_any(path: String) -> Result<String, String> {
@@ -711,32 +628,6 @@ async fn force_remove_path(path: String) -> Result<(), String> {
   Ok(())
 }
 
-#[tauri::command]
-async fn run_installer(path: String) -> Result<(), String> {
-  #[cfg(target_os = "windows")]
-  {
-    use std::process::Command;
-    // 使用 PowerShell 以管理员权限启动安装程序
-    let status = Command::new("powershell")
-      .args([
-        "-NoProfile",
-        "-Command",
-        "Start-Process",
-        "-FilePath",
-        &path,
-        "-Verb",
-        "runas",
-      ])
-      .status()
-      .map_err(|e| format!("spawn error: {e}"))?;
-    let _ = status; // 忽略返回码，由安装器自行处理
-    Ok(())
-  }
-  #[cfg(not(target_os = "windows"))]
-  {
-    Err("run_installer only supports Windows".into())
-  }
-}
 
 // ============ Android SAF 文件操作命令（移动端专用） ============
 // 这些命令在 Android 上通过 JNI 调用原生 SAF API
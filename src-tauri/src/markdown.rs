@@ -0,0 +1,170 @@
+// Markdown 公共工具：Front Matter 拆分/解析、渲染 HTML、图片引用定位
+// 发布器与导出共用，渲染规则与预览保持一致（表格、脚注、删除线、任务列表）
+
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use serde_json::{Map, Value as Json};
+use std::ops::Range;
+
+pub fn parser_options() -> Options {
+  Options::ENABLE_TABLES
+    | Options::ENABLE_FOOTNOTES
+    | Options::ENABLE_STRIKETHROUGH
+    | Options::ENABLE_TASKLISTS
+    | Options::ENABLE_HEADING_ATTRIBUTES
+}
+
+// 拆分 Front Matter：返回 (YAML 文本, 正文, 正文在原文中的起始偏移)
+pub fn split_front_matter(text: &str) -> (Option<&str>, &str, usize) {
+  let bom = if text.starts_with('\u{feff}') { '\u{feff}'.len_utf8() } else { 0 };
+  let src = &text[bom..];
+  let first_nl = match src.find('\n') { Some(i) => i, None => return (None, text, 0) };
+  if src[..first_nl].trim_end() != "---" { return (None, text, 0); }
+  let yaml_start = first_nl + 1;
+  let mut pos = yaml_start;
+  while pos <= src.len() {
+    let line_end = src[pos..].find('\n').map(|i| pos + i).unwrap_or(src.len());
+    let line = src[pos..line_end].trim_end();
+    if line == "---" || line == "..." {
+      let body_start = (line_end + 1).min(src.len());
+      return (Some(&src[yaml_start..pos]), &src[body_start..], bom + body_start);
+    }
+    if line_end >= src.len() { break; }
+    pos = line_end + 1;
+  }
+  (None, text, 0)
+}
+
+// 解析 Front Matter 为 JSON 对象；无或解析失败时返回空对象
+pub fn parse_front_matter(text: &str) -> (Map<String, Json>, &str) {
+  let (yaml, body, _) = split_front_matter(text);
+  let map = yaml
+    .and_then(|y| serde_yaml::from_str::<Json>(y).ok())
+    .and_then(|v| match v { Json::Object(m) => Some(m), _ => None })
+    .unwrap_or_default();
+  (map, body)
+}
+
+pub fn render_html(body: &str) -> String {
+  let parser = Parser::new_ext(body, parser_options());
+  let mut out = String::with_capacity(body.len() * 3 / 2);
+  pulldown_cmark::html::push_html(&mut out, parser);
+  out
+}
+
+// 正文中的一处图片/链接地址：range 为地址在正文中的字节区间，便于原位替换
+#[derive(Debug, Clone)]
+pub struct ImageRef {
+  pub range: Range<usize>,
+  pub url: String,
+}
+
+// 内联图片/链接源码中地址的区间（位于 "](" 之后）；引用式（![a][id]）返回 None
+fn dest_range(body: &str, range: &Range<usize>) -> Option<Range<usize>> {
+  let src = &body[range.clone()];
+  let open = src.find("](")?;
+  let after = open + 2;
+  let seg = &src[after..];
+  let seg_trim = seg.trim_start();
+  let lead = seg.len() - seg_trim.len();
+  let (start, len) = if let Some(rest) = seg_trim.strip_prefix('<') {
+    (after + lead + 1, rest.find('>').unwrap_or(0))
+  } else {
+    (after + lead, seg_trim.find(|c: char| c.is_whitespace() || c == ')').unwrap_or(seg_trim.len()))
+  };
+  if len == 0 { return None; }
+  Some(range.start + start..range.start + start + len)
+}
+
+// 定位 ![]() 与 <img src> 中的图片地址
+pub fn find_images(body: &str) -> Vec<ImageRef> {
+  let mut refs = Vec::new();
+  for (ev, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+    match ev {
+      Event::Start(Tag::Image { dest_url, .. }) => {
+        if let Some(abs) = dest_range(body, &range) {
+          refs.push(ImageRef { range: abs, url: dest_url.to_string() });
+        }
+      }
+      Event::Html(html) | Event::InlineHtml(html) => {
+        let lower = html.to_ascii_lowercase();
+        let mut from = 0;
+        while let Some(i) = lower[from..].find("<img") {
+          let tag_start = from + i;
+          let tag_end = lower[tag_start..].find('>').map(|e| tag_start + e).unwrap_or(lower.len());
+          if let Some(r) = attr_value_range(&lower[tag_start..tag_end], "src") {
+            let abs = range.start + tag_start + r.start..range.start + tag_start + r.end;
+            refs.push(ImageRef { url: body[abs.clone()].to_string(), range: abs });
+          }
+          from = tag_end;
+        }
+      }
+      _ => {}
+    }
+  }
+  refs
+}
+
+// 定位内联链接 []() 的地址（不含图片与自动链接）
+pub fn find_links(body: &str) -> Vec<ImageRef> {
+  Parser::new_ext(body, parser_options())
+    .into_offset_iter()
+    .filter_map(|(ev, range)| match ev {
+      Event::Start(Tag::Link { link_type: pulldown_cmark::LinkType::Inline, dest_url, .. }) => {
+        // 链接文本中可能含图片，取最后一个 "](" 才是本链接的地址
+        let src = &body[range.clone()];
+        let open = src.rfind("](")?;
+        dest_range(body, &(range.start + open..range.end)).map(|abs| ImageRef { range: abs, url: dest_url.to_string() })
+      }
+      _ => None,
+    })
+    .collect()
+}
+
+// 在（已小写的）标签文本中查找属性值区间
+fn attr_value_range(tag: &str, name: &str) -> Option<Range<usize>> {
+  let mut from = 0;
+  while let Some(i) = tag[from..].find(name) {
+    let at = from + i;
+    from = at + name.len();
+    let before_ok = at > 0 && tag.as_bytes()[at - 1].is_ascii_whitespace();
+    let rest = tag[from..].trim_start();
+    if !before_ok || !rest.starts_with('=') { continue; }
+    let val = rest[1..].trim_start();
+    let val_start = tag.len() - val.len();
+    return match val.chars().next() {
+      Some(q @ ('"' | '\'')) => val[1..].find(q).map(|e| val_start + 1..val_start + 1 + e),
+      Some(_) => Some(val_start..val_start + val.find(|c: char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(val.len())),
+      None => None,
+    };
+  }
+  None
+}
+
+// 按区间替换图片地址（从后往前替换，保持前面区间有效）
+pub fn replace_ranges(body: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+  edits.sort_by(|a, b| b.0.start.cmp(&a.0.start));
+  let mut out = body.to_string();
+  let mut last_start = usize::MAX;
+  for (r, v) in edits {
+    // 重叠区间跳过
+    if r.end > last_start { continue; }
+    out.replace_range(r.clone(), &v);
+    last_start = r.start;
+  }
+  out
+}
+
+// 首个一级标题作为标题兜底
+pub fn first_heading(body: &str) -> Option<String> {
+  let mut in_h1 = false;
+  let mut title = String::new();
+  for ev in Parser::new_ext(body, parser_options()) {
+    match ev {
+      Event::Start(Tag::Heading { level: pulldown_cmark::HeadingLevel::H1, .. }) => in_h1 = true,
+      Event::End(pulldown_cmark::TagEnd::Heading(_)) if in_h1 => return Some(title.trim().to_string()),
+      Event::Text(t) | Event::Code(t) if in_h1 => title.push_str(&t),
+      _ => {}
+    }
+  }
+  None
+}
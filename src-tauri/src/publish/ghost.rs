@@ -0,0 +1,325 @@
+// Ghost Admin API 发布：Admin API Key（id:secret）签发 5 分钟有效的 HS256 JWT
+// 正文可按 HTML 提交（Ghost 转换为 lexical），或以 Markdown 卡片形式写入 lexical/mobiledoc
+
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as Json};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{http_client, send_error, map_names, prepare, upload_local_images, utc_time, Prepared, PublishDoc, PublishResult};
+use crate::http_opts::HttpOptions;
+use crate::markdown;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GhostSource {
+  #[default]
+  Html,
+  Lexical,
+  Mobiledoc,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GhostConfig {
+  // 站点 API 地址，如 https://blog.example.com
+  pub api_url: String,
+  // 后台 Integrations 中的 Admin API Key，形如 "<id>:<hex secret>"
+  pub admin_key: String,
+  #[serde(default)]
+  pub source: GhostSource,
+  // front matter 标签名 -> Ghost 标签名
+  #[serde(default)]
+  pub tag_map: HashMap<String, String>,
+  // 超时、代理、自定义 CA、附加头等
+  #[serde(default)]
+  pub http: HttpOptions,
+}
+
+impl GhostConfig {
+  // 发布状态记录中的目标标识
+  pub fn target(&self) -> String { super::state::target_id("ghost", &self.api_url) }
+
+  // 按站点地址与 http 选项构建客户端
+  fn client(&self) -> Result<reqwest::Client, String> { http_client(&self.http, &self.api_url) }
+
+  fn api(&self, path: &str) -> String {
+    format!("{}/ghost/api/admin/{}", self.api_url.trim().trim_end_matches('/'), path.trim_start_matches('/'))
+  }
+
+  async fn send(&self, rb: reqwest::RequestBuilder) -> Result<Json, String> {
+    let token = admin_token(&self.admin_key)?;
+    let res = rb
+      .header("Authorization", format!("Ghost {}", token))
+      .header("Accept-Version", "v5.0")
+      .send()
+      .await
+      .map_err(send_error)?;
+    let status = res.status();
+    let text = res.text().await.map_err(|e| format!("read error: {e}"))?;
+    let body: Json = serde_json::from_str(&text).unwrap_or(Json::String(text));
+    if !status.is_success() {
+      // Ghost 错误体：{"errors": [{"message": "...", "context": "..."}]}
+      let msg = body.get("errors").and_then(|e| e.get(0)).map(|e| {
+        let m = e.get("message").and_then(|x| x.as_str()).unwrap_or("");
+        let c = e.get("context").and_then(|x| x.as_str()).unwrap_or("");
+        format!("{} {}", m, c).trim().to_string()
+      }).unwrap_or_else(|| body.to_string());
+      return Err(format!("HTTP {}: {}", status.as_u16(), msg));
+    }
+    Ok(body)
+  }
+}
+
+// Ghost Admin JWT：header 含 kid，aud 为 /admin/，有效期 5 分钟
+pub fn admin_token(admin_key: &str) -> Result<String, String> {
+  let (id, secret) = admin_key.trim().split_once(':').ok_or_else(|| "invalid admin api key (expect id:secret)".to_string())?;
+  let secret = hex::decode(secret).map_err(|e| format!("invalid admin api key secret: {e}"))?;
+  let now = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| format!("clock error: {e}"))?.as_secs();
+  let b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+  let header = json!({ "alg": "HS256", "typ": "JWT", "kid": id });
+  let payload = json!({ "iat": now, "exp": now + 300, "aud": "/admin/" });
+  let signing_input = format!("{}.{}", b64.encode(header.to_string()), b64.encode(payload.to_string()));
+  let mut mac = Hmac::<Sha256>::new_from_slice(&secret).map_err(|e| format!("hmac error: {e}"))?;
+  mac.update(signing_input.as_bytes());
+  Ok(format!("{}.{}", signing_input, b64.encode(mac.finalize().into_bytes())))
+}
+
+pub async fn upload_image(cfg: &GhostConfig, name: &str, content_type: &str, bytes: Vec<u8>) -> Result<String, String> {
+  let part = reqwest::multipart::Part::bytes(bytes)
+    .file_name(name.to_string())
+    .mime_str(content_type)
+    .map_err(|e| format!("invalid content type: {e}"))?;
+  let form = reqwest::multipart::Form::new()
+    .part("file", part)
+    .text("purpose", "image")
+    .text("ref", name.to_string());
+  let client = cfg.client()?;
+  let v = cfg.send(client.post(cfg.api("images/upload/")).multipart(form)).await?;
+  v.get("images").and_then(|i| i.get(0)).and_then(|i| i.get("url")).and_then(|u| u.as_str())
+    .map(|s| s.to_string())
+    .ok_or_else(|| "image upload returned no url".to_string())
+}
+
+// Markdown 卡片：Ghost 编辑器中保持可编辑的 Markdown 源
+fn lexical_markdown(md: &str) -> String {
+  json!({
+    "root": {
+      "children": [{ "type": "markdown", "version": 1, "markdown": md }],
+      "direction": null, "format": "", "indent": 0, "type": "root", "version": 1
+    }
+  }).to_string()
+}
+
+fn mobiledoc_markdown(md: &str) -> String {
+  json!({
+    "version": "0.3.1", "atoms": [], "markups": [],
+    "cards": [["markdown", { "markdown": md }]],
+    "sections": [[10, 0]]
+  }).to_string()
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GhostPublishReq {
+  #[serde(flatten)]
+  pub doc: PublishDoc,
+  #[serde(default)]
+  pub post_id: Option<String>,
+  // draft / published；缺省按 front matter 的 draft 决定
+  #[serde(default)]
+  pub status: Option<String>,
+  // 定时发布时间（RFC3339），设置后状态为 scheduled
+  #[serde(default)]
+  pub schedule_at: Option<String>,
+  #[serde(default = "super::default_true")]
+  pub upload_images: bool,
+}
+
+// Ghost 的 published_at（UTC）；时区规则见 utc_time
+fn iso_utc(s: &str) -> Option<String> {
+  utc_time(s).map(|t| t.format("%Y-%m-%dT%H:%M:%S.000Z").to_string())
+}
+
+pub async fn publish(cfg: &GhostConfig, req: &GhostPublishReq) -> Result<PublishResult, String> {
+  // 定时时间无法识别时报错，不能退回为立即发布
+  let schedule = match req.schedule_at.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+    Some(s) => Some(iso_utc(s).ok_or_else(|| format!("invalid schedule_at: {}", s))?),
+    None => None,
+  };
+  let Prepared { meta, body } = prepare(&req.doc, Some(&cfg.target())).await?;
+  let doc_dir = req.doc.doc_dir();
+  let (body, images_uploaded) = if req.upload_images {
+    upload_local_images(&body, doc_dir.as_deref(), |img| async move {
+      upload_image(cfg, &img.name, &img.content_type, img.bytes).await
+    }).await?
+  } else {
+    (body, 0)
+  };
+
+  let tags: Vec<Json> = map_names(&meta.tags, &cfg.tag_map).into_iter().map(|t| json!({ "name": t })).collect();
+  let mut post = json!({ "title": meta.title, "tags": tags });
+  match cfg.source {
+    GhostSource::Html => post["html"] = json!(markdown::render_html(&body)),
+    GhostSource::Lexical => post["lexical"] = json!(lexical_markdown(&body)),
+    GhostSource::Mobiledoc => post["mobiledoc"] = json!(mobiledoc_markdown(&body)),
+  }
+  let status = match (&schedule, req.status.as_deref()) {
+    (Some(_), _) => "scheduled".to_string(),
+    (None, Some(s)) if !s.trim().is_empty() => s.trim().to_ascii_lowercase(),
+    _ => if meta.draft { "draft".into() } else { "published".into() },
+  };
+  post["status"] = json!(status);
+  if let Some(d) = schedule.or_else(|| meta.date.as_deref().and_then(iso_utc)) { post["published_at"] = json!(d); }
+  if let Some(slug) = &meta.slug { post["slug"] = json!(slug); }
+  if let Some(ex) = &meta.excerpt { post["custom_excerpt"] = json!(ex); }
+  let query = if cfg.source == GhostSource::Html { "?source=html" } else { "" };
+
+  let client = cfg.client()?;
+  let existing = req.post_id.as_ref().map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+  let created = existing.is_none();
+  let v = match &existing {
+    Some(id) => {
+      // 更新需携带服务端当前的 updated_at 做冲突检测
+      let cur = cfg.send(client.get(cfg.api(&format!("posts/{}/", id)))).await?;
+      let updated_at = cur.get("posts").and_then(|p| p.get(0)).and_then(|p| p.get("updated_at")).cloned().unwrap_or(Json::Null);
+      post["updated_at"] = updated_at;
+      cfg.send(client.put(cfg.api(&format!("posts/{}/{}", id, query))).json(&json!({ "posts": [post] }))).await?
+    }
+    None => cfg.send(client.post(cfg.api(&format!("posts/{}", query))).json(&json!({ "posts": [post] }))).await?,
+  };
+  let p = v.get("posts").and_then(|p| p.get(0)).ok_or_else(|| "unexpected ghost response".to_string())?;
+  Ok(PublishResult {
+    post_id: p.get("id").and_then(|x| x.as_str()).map(|s| s.to_string()).or(existing).unwrap_or_default(),
+    url: p.get("url").and_then(|x| x.as_str()).unwrap_or("").to_string(),
+    created,
+    images_uploaded,
+    document: None,
+  })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GhostTag {
+  pub id: String,
+  pub name: String,
+  pub slug: String,
+}
+
+#[tauri::command]
+pub async fn ghost_list_tags(cfg: GhostConfig) -> Result<Vec<GhostTag>, String> {
+  let client = cfg.client()?;
+  let v = cfg.send(client.get(cfg.api("tags/")).query(&[("limit", "all")])).await?;
+  let s = |t: &Json, k: &str| t.get(k).and_then(|x| x.as_str()).unwrap_or("").to_string();
+  Ok(v.get("tags").and_then(|t| t.as_array()).map(|a| a.iter().map(|t| GhostTag { id: s(t, "id"), name: s(t, "name"), slug: s(t, "slug") }).collect()).unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn ghost_upload_image(cfg: GhostConfig, path: String) -> Result<String, String> {
+  let p = std::path::PathBuf::from(&path);
+  let bytes = super::read_file(p.clone()).await?;
+  let checked = crate::upload_policy::UploadPolicy::default().enforce(bytes, None)?;
+  let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "image".into());
+  upload_image(&cfg, &name, &checked.content_type, checked.bytes).await
+}
+
+#[tauri::command]
+pub async fn ghost_publish(cfg: GhostConfig, mut req: GhostPublishReq) -> Result<PublishResult, String> {
+  let target = cfg.target();
+  req.post_id = super::state::resolve_post_id(&req.doc, &target, req.post_id.take()).await?;
+  let mut res = publish(&cfg, &req).await?;
+  super::state::record(&req.doc, &target, &mut res).await?;
+  Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_http::{serve, Response};
+
+  const KEY: &str = "6489f0e1a2b3c4d5e6f70809:0a0b0c0d0e0f101112131415161718191a1b1c1d1e1f202122232425262728292a";
+
+  fn config(api_url: String, source: GhostSource) -> GhostConfig {
+    let tag_map = HashMap::from([("rust".to_string(), "Rust 编程".to_string())]);
+    GhostConfig { api_url, admin_key: KEY.into(), source, tag_map, http: HttpOptions::default() }
+  }
+
+  #[test]
+  fn token() {
+    let t = admin_token(KEY).unwrap();
+    let parts: Vec<&str> = t.split('.').collect();
+    assert_eq!(parts.len(), 3);
+    let b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    let header: Json = serde_json::from_slice(&b64.decode(parts[0]).unwrap()).unwrap();
+    let payload: Json = serde_json::from_slice(&b64.decode(parts[1]).unwrap()).unwrap();
+    assert_eq!(header["kid"], "6489f0e1a2b3c4d5e6f70809");
+    assert_eq!(payload["aud"], "/admin/");
+    assert_eq!(payload["exp"].as_u64().unwrap() - payload["iat"].as_u64().unwrap(), 300);
+    let mut mac = Hmac::<Sha256>::new_from_slice(&hex::decode(KEY.split_once(':').unwrap().1).unwrap()).unwrap();
+    mac.update(format!("{}.{}", parts[0], parts[1]).as_bytes());
+    mac.verify_slice(&b64.decode(parts[2]).unwrap()).unwrap();
+    assert!(admin_token("no-colon").is_err());
+    assert!(admin_token("id:not-hex").is_err());
+  }
+
+  #[tokio::test]
+  async fn create_and_update() {
+    let srv = serve(|req| match (req.method.as_str(), req.path.as_str()) {
+      ("GET", "/ghost/api/admin/posts/p1/") => Response::new(200, "application/json", r#"{"posts":[{"id":"p1","updated_at":"2025-01-01T00:00:00.000Z"}]}"#),
+      ("POST", "/ghost/api/admin/posts/?source=html") | ("PUT", "/ghost/api/admin/posts/p1/") =>
+        Response::new(200, "application/json", r#"{"posts":[{"id":"p1","url":"https://g.example/hello/"}]}"#),
+      _ => Response::new(404, "application/json", r#"{"errors":[{"message":"Resource not found","context":"no route"}]}"#),
+    });
+    let doc = PublishDoc { content: Some("---\ntitle: Hello\ntags: [rust, misc, Rust]\ndate: 2025-02-03T04:05:06+08:00\n---\n# Hi\n".into()), ..Default::default() };
+    let req = GhostPublishReq { doc: doc.clone(), post_id: None, status: None, schedule_at: None, upload_images: false };
+    let res = publish(&config(srv.url("/"), GhostSource::Html), &req).await.unwrap();
+    assert_eq!((res.post_id.as_str(), res.url.as_str(), res.created), ("p1", "https://g.example/hello/", true));
+
+    let reqs = srv.requests();
+    assert!(reqs[0].header("authorization").unwrap().starts_with("Ghost "));
+    assert_eq!(reqs[0].header("accept-version"), Some("v5.0"));
+    let body: Json = serde_json::from_slice(&reqs[0].body).unwrap();
+    let post = &body["posts"][0];
+    assert_eq!(post["tags"], json!([{ "name": "Rust 编程" }, { "name": "misc" }]));
+    assert_eq!(post["status"], "published");
+    assert_eq!(post["published_at"], "2025-02-02T20:05:06.000Z");
+    assert!(post["html"].as_str().unwrap().contains("<h1>Hi</h1>"));
+
+    // 更新：先取 updated_at，再以 lexical Markdown 卡片 PUT
+    let req = GhostPublishReq { doc, post_id: Some("p1".into()), status: Some("Draft".into()), schedule_at: None, upload_images: false };
+    let res = publish(&config(srv.url(""), GhostSource::Lexical), &req).await.unwrap();
+    assert!(!res.created);
+    let reqs = srv.requests();
+    assert_eq!((reqs[1].method.as_str(), reqs[2].method.as_str()), ("GET", "PUT"));
+    let body: Json = serde_json::from_slice(&reqs[2].body).unwrap();
+    let post = &body["posts"][0];
+    assert_eq!(post["updated_at"], "2025-01-01T00:00:00.000Z");
+    assert_eq!(post["status"], "draft");
+    let lexical: Json = serde_json::from_str(post["lexical"].as_str().unwrap()).unwrap();
+    assert_eq!(lexical["root"]["children"][0]["markdown"], "# Hi\n");
+
+    let req = GhostPublishReq { doc: PublishDoc { content: Some("x".into()), ..Default::default() }, post_id: Some("gone".into()), status: None, schedule_at: None, upload_images: false };
+    let err = publish(&config(srv.url(""), GhostSource::Html), &req).await.unwrap_err();
+    assert_eq!(err, "HTTP 404: Resource not found no route");
+  }
+
+  #[tokio::test]
+  async fn scheduling() {
+    let srv = serve(|_| Response::new(200, "application/json", r#"{"posts":[{"id":"p2","url":"https://g.example/s/"}]}"#));
+    let doc = PublishDoc { content: Some("---\ntitle: Later\n---\nbody".into()), ..Default::default() };
+    let req = |at: &str| GhostPublishReq { doc: doc.clone(), post_id: None, status: Some("published".into()), schedule_at: Some(at.into()), upload_images: false };
+    publish(&config(srv.url(""), GhostSource::Html), &req("2099-01-01 08:00")).await.unwrap();
+    let body: Json = serde_json::from_slice(&srv.requests()[0].body).unwrap();
+    assert_eq!(body["posts"][0]["status"], "scheduled");
+    // 不带时区的定时时间按本机时区换算
+    assert_eq!(body["posts"][0]["published_at"].as_str(), iso_utc("2099-01-01 08:00").as_deref());
+
+    // 无法识别的定时时间：报错，不会以 published 发出
+    let err = publish(&config(srv.url(""), GhostSource::Html), &req("tomorrow-ish")).await.unwrap_err();
+    assert_eq!(err, "invalid schedule_at: tomorrow-ish");
+    assert_eq!(srv.requests().len(), 1);
+  }
+}
@@ -0,0 +1,391 @@
+// Halo 2.x 发布：个人令牌（PAT）Bearer 认证
+// 正文以 Markdown 原文 + 渲染后的 HTML 一并提交（rawType = markdown），标签/分类按显示名映射为资源 name
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as Json};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{http_client, send_error, map_names, prepare, slugify, upload_local_images, utc_time, Prepared, PublishDoc, PublishResult};
+use crate::http_opts::HttpOptions;
+use crate::markdown;
+
+const CONSOLE_API: &str = "apis/api.console.halo.run/v1alpha1";
+const CONTENT_API: &str = "apis/content.halo.run/v1alpha1";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HaloConfig {
+  // 站点根地址，如 https://halo.example.com
+  pub site_url: String,
+  // 后台“个人中心 -> 个人令牌”生成
+  pub token: String,
+  // 附件存储策略，缺省为内置的本地存储
+  #[serde(default)]
+  pub policy_name: Option<String>,
+  #[serde(default)]
+  pub group_name: Option<String>,
+  // front matter 标签名 -> Halo 标签显示名
+  #[serde(default)]
+  pub tag_map: HashMap<String, String>,
+  // 超时、代理、自定义 CA、附加头等
+  #[serde(default)]
+  pub http: HttpOptions,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HaloTermKind {
+  Tags,
+  Categories,
+}
+
+impl HaloTermKind {
+  fn path(self) -> &'static str {
+    match self { HaloTermKind::Tags => "tags", HaloTermKind::Categories => "categories" }
+  }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HaloTerm {
+  // 资源 name（文章中引用的是它，而非显示名）
+  pub name: String,
+  pub display_name: String,
+  pub slug: String,
+}
+
+impl HaloConfig {
+  // 发布状态记录中的目标标识
+  pub fn target(&self) -> String { super::state::target_id("halo", &self.site_url) }
+
+  // 按站点地址与 http 选项构建客户端
+  fn client(&self) -> Result<reqwest::Client, String> { http_client(&self.http, &self.site_url) }
+
+  fn base(&self) -> &str { self.site_url.trim().trim_end_matches('/') }
+
+  fn api(&self, group: &str, path: &str) -> String {
+    format!("{}/{}/{}", self.base(), group, path.trim_start_matches('/'))
+  }
+
+  // 附件/文章的 permalink 可能是站内相对路径
+  fn absolute(&self, link: &str) -> String {
+    if link.starts_with('/') { format!("{}{}", self.base(), link) } else { link.to_string() }
+  }
+
+  async fn send(&self, rb: reqwest::RequestBuilder) -> Result<Json, String> {
+    let res = rb
+      .bearer_auth(self.token.trim())
+      .header("Accept", "application/json")
+      .send()
+      .await
+      .map_err(send_error)?;
+    let status = res.status();
+    let text = res.text().await.map_err(|e| format!("read error: {e}"))?;
+    let body: Json = serde_json::from_str(&text).unwrap_or(Json::String(text));
+    if !status.is_success() {
+      // Halo 错误体（ProblemDetail）：{"title": "...", "detail": "...", "status": 400}
+      let s = |k: &str| body.get(k).and_then(|x| x.as_str()).unwrap_or("").to_string();
+      let msg = format!("{} {}", s("title"), s("detail")).trim().to_string();
+      let msg = if msg.is_empty() { body.to_string() } else { msg };
+      return Err(format!("HTTP {}: {}", status.as_u16(), msg));
+    }
+    Ok(body)
+  }
+}
+
+// 新文章的资源 name：与 Halo 控制台一致使用 UUID 形式
+fn new_name(seed: &str) -> String {
+  let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+  let h = hex::encode(Sha256::digest(format!("{}:{}", seed, nanos).as_bytes()));
+  format!("{}-{}-4{}-a{}-{}", &h[0..8], &h[8..12], &h[13..16], &h[17..20], &h[20..32])
+}
+
+fn to_term(v: &Json) -> HaloTerm {
+  let s = |p: &str| v.pointer(p).and_then(|x| x.as_str()).unwrap_or("").to_string();
+  HaloTerm { name: s("/metadata/name"), display_name: s("/spec/displayName"), slug: s("/spec/slug") }
+}
+
+pub async fn list_terms(cfg: &HaloConfig, kind: HaloTermKind) -> Result<Vec<HaloTerm>, String> {
+  let client = cfg.client()?;
+  let v = cfg.send(client.get(cfg.api(CONTENT_API, kind.path()))).await?;
+  Ok(v.get("items").and_then(|i| i.as_array()).map(|a| a.iter().map(to_term).collect()).unwrap_or_default())
+}
+
+pub async fn create_term(cfg: &HaloConfig, kind: HaloTermKind, display_name: &str) -> Result<HaloTerm, String> {
+  let slug = match slugify(display_name) { s if s.is_empty() => display_name.trim().to_string(), s => s };
+  let (kind_name, prefix, spec) = match kind {
+    HaloTermKind::Tags => ("Tag", "tag-", json!({ "displayName": display_name, "slug": slug, "color": "#ffffff", "cover": "" })),
+    HaloTermKind::Categories => ("Category", "category-", json!({
+      "displayName": display_name, "slug": slug, "description": "", "cover": "", "template": "", "priority": 0, "children": []
+    })),
+  };
+  let body = json!({
+    "apiVersion": "content.halo.run/v1alpha1",
+    "kind": kind_name,
+    "metadata": { "name": "", "generateName": prefix },
+    "spec": spec,
+  });
+  let client = cfg.client()?;
+  let v = cfg.send(client.post(cfg.api(CONTENT_API, kind.path())).json(&body)).await?;
+  Ok(to_term(&v))
+}
+
+// 显示名 -> 资源 name：忽略大小写匹配显示名或 slug，不存在则创建
+pub async fn ensure_terms(cfg: &HaloConfig, kind: HaloTermKind, names: &[String]) -> Result<Vec<String>, String> {
+  if names.is_empty() { return Ok(Vec::new()); }
+  let existing = list_terms(cfg, kind).await?;
+  let mut out = Vec::new();
+  for n in names {
+    let name = match existing.iter().find(|t| t.display_name.eq_ignore_ascii_case(n) || t.slug.eq_ignore_ascii_case(n)) {
+      Some(t) => t.name.clone(),
+      None => create_term(cfg, kind, n).await?.name,
+    };
+    if !name.is_empty() && !out.contains(&name) { out.push(name); }
+  }
+  Ok(out)
+}
+
+pub async fn upload_attachment(cfg: &HaloConfig, name: &str, content_type: &str, bytes: Vec<u8>) -> Result<String, String> {
+  let part = reqwest::multipart::Part::bytes(bytes)
+    .file_name(name.to_string())
+    .mime_str(content_type)
+    .map_err(|e| format!("invalid content type: {e}"))?;
+  let policy = cfg.policy_name.clone().filter(|s| !s.trim().is_empty()).unwrap_or_else(|| "default-policy".into());
+  let mut form = reqwest::multipart::Form::new().part("file", part).text("policyName", policy);
+  if let Some(g) = cfg.group_name.clone().filter(|s| !s.trim().is_empty()) { form = form.text("groupName", g); }
+  let client = cfg.client()?;
+  let v = cfg.send(client.post(cfg.api(CONSOLE_API, "attachments/upload")).multipart(form)).await?;
+  v.pointer("/status/permalink")
+    .or_else(|| v.pointer("/metadata/annotations/storage.halo.run~1uri"))
+    .and_then(|u| u.as_str())
+    .map(|u| cfg.absolute(u))
+    .ok_or_else(|| "attachment upload returned no permalink".to_string())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HaloPublishReq {
+  #[serde(flatten)]
+  pub doc: PublishDoc,
+  // 文章资源 name：有则更新
+  #[serde(default)]
+  pub post_id: Option<String>,
+  // false 时仅保存草稿；缺省按 front matter 的 draft 决定
+  #[serde(default)]
+  pub publish: Option<bool>,
+  #[serde(default = "super::default_true")]
+  pub upload_images: bool,
+}
+
+// Halo 的 publishTime（UTC）；时区规则见 utc_time
+fn halo_time(s: &str) -> Option<String> {
+  utc_time(s).map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+}
+
+// 发布：上传图片 -> 映射标签/分类 -> 创建（或更新 spec 与内容）-> 发布/撤回 -> 取 permalink
+pub async fn publish(cfg: &HaloConfig, req: &HaloPublishReq) -> Result<PublishResult, String> {
+  let Prepared { meta, body } = prepare(&req.doc, Some(&cfg.target())).await?;
+  let doc_dir = req.doc.doc_dir();
+  let (body, images_uploaded) = if req.upload_images {
+    upload_local_images(&body, doc_dir.as_deref(), |img| async move {
+      upload_attachment(cfg, &img.name, &img.content_type, img.bytes).await
+    }).await?
+  } else {
+    (body, 0)
+  };
+
+  let tags = ensure_terms(cfg, HaloTermKind::Tags, &map_names(&meta.tags, &cfg.tag_map)).await?;
+  let categories = ensure_terms(cfg, HaloTermKind::Categories, &meta.categories).await?;
+  let content = json!({ "raw": body, "content": markdown::render_html(&body), "rawType": "markdown" });
+  let publish = req.publish.unwrap_or(!meta.draft);
+  let client = cfg.client()?;
+  let existing = req.post_id.as_ref().map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+  let created = existing.is_none();
+
+  let name = match existing {
+    Some(name) => {
+      // 先取回完整资源（含 metadata.version），只改需要的 spec 字段
+      let url = cfg.api(CONTENT_API, &format!("posts/{}", name));
+      let mut post = cfg.send(client.get(&url)).await?;
+      let spec = post.get_mut("spec").and_then(|s| s.as_object_mut()).ok_or_else(|| "unexpected halo post".to_string())?;
+      spec.insert("title".into(), json!(meta.title));
+      spec.insert("tags".into(), json!(tags));
+      spec.insert("categories".into(), json!(categories));
+      if let Some(slug) = &meta.slug { spec.insert("slug".into(), json!(slug)); }
+      if let Some(ex) = &meta.excerpt { spec.insert("excerpt".into(), json!({ "autoGenerate": false, "raw": ex })); }
+      if let Some(t) = meta.date.as_deref().and_then(halo_time) { spec.insert("publishTime".into(), json!(t)); }
+      cfg.send(client.put(&url).json(&post)).await?;
+      cfg.send(client.put(cfg.api(CONSOLE_API, &format!("posts/{}/content", name))).json(&content)).await?;
+      name
+    }
+    None => {
+      let name = new_name(&meta.title);
+      let slug = meta.slug.clone().unwrap_or_else(|| match slugify(&meta.title) { s if s.is_empty() => name.clone(), s => s });
+      let mut spec = json!({
+        "title": meta.title, "slug": slug, "template": "", "cover": "", "deleted": false, "publish": false,
+        "pinned": false, "allowComment": true, "visible": "PUBLIC", "priority": 0,
+        "excerpt": { "autoGenerate": meta.excerpt.is_none(), "raw": meta.excerpt.clone().unwrap_or_default() },
+        "categories": categories, "tags": tags, "htmlMetas": [],
+      });
+      if let Some(t) = meta.date.as_deref().and_then(halo_time) { spec["publishTime"] = json!(t); }
+      let body = json!({
+        "post": {
+          "apiVersion": "content.halo.run/v1alpha1",
+          "kind": "Post",
+          "metadata": { "name": name, "annotations": {} },
+          "spec": spec,
+        },
+        "content": content,
+      });
+      cfg.send(client.post(cfg.api(CONSOLE_API, "posts")).json(&body)).await?;
+      name
+    }
+  };
+
+  let action = if publish { "publish" } else { "unpublish" };
+  // 新建草稿无需撤回
+  if publish || !created {
+    cfg.send(client.put(cfg.api(CONSOLE_API, &format!("posts/{}/{}", name, action)))).await?;
+  }
+  // permalink 获取失败不影响发布结果
+  let url = match cfg.send(client.get(cfg.api(CONTENT_API, &format!("posts/{}", name)))).await {
+    Ok(p) => p.pointer("/status/permalink").and_then(|u| u.as_str()).map(|u| cfg.absolute(u)).unwrap_or_default(),
+    Err(_) => String::new(),
+  };
+  Ok(PublishResult { post_id: name, url, created, images_uploaded, document: None })
+}
+
+#[tauri::command]
+pub async fn halo_list_terms(cfg: HaloConfig, kind: HaloTermKind) -> Result<Vec<HaloTerm>, String> {
+  list_terms(&cfg, kind).await
+}
+
+#[tauri::command]
+pub async fn halo_upload_attachment(cfg: HaloConfig, path: String) -> Result<String, String> {
+  let p = std::path::PathBuf::from(&path);
+  let bytes = super::read_file(p.clone()).await?;
+  let checked = crate::upload_policy::UploadPolicy::default().enforce(bytes, None)?;
+  let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "file".into());
+  upload_attachment(&cfg, &name, &checked.content_type, checked.bytes).await
+}
+
+#[tauri::command]
+pub async fn halo_publish(cfg: HaloConfig, mut req: HaloPublishReq) -> Result<PublishResult, String> {
+  let target = cfg.target();
+  req.post_id = super::state::resolve_post_id(&req.doc, &target, req.post_id.take()).await?;
+  let mut res = publish(&cfg, &req).await?;
+  super::state::record(&req.doc, &target, &mut res).await?;
+  Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_http::{serve, Response};
+
+  fn json_resp(v: Json) -> Response {
+    Response::new(200, "application/json", v.to_string())
+  }
+
+  // 仿 Halo：已有标签 Rust，无分类；文章 p-old 已存在
+  fn site() -> crate::test_http::Server {
+    serve(|req| {
+      let path = req.path.trim_start_matches('/');
+      match (req.method.as_str(), path) {
+        ("GET", "apis/content.halo.run/v1alpha1/tags") => json_resp(json!({ "items": [
+          { "metadata": { "name": "tag-abc" }, "spec": { "displayName": "Rust", "slug": "rust" } }
+        ] })),
+        ("GET", "apis/content.halo.run/v1alpha1/categories") => json_resp(json!({ "items": [] })),
+        ("POST", "apis/content.halo.run/v1alpha1/tags") | ("POST", "apis/content.halo.run/v1alpha1/categories") => {
+          let v: Json = serde_json::from_slice(&req.body).unwrap();
+          let name = format!("{}new", v["metadata"]["generateName"].as_str().unwrap());
+          json_resp(json!({ "metadata": { "name": name }, "spec": v["spec"] }))
+        }
+        ("GET", p) if p.starts_with("apis/content.halo.run/v1alpha1/posts/") => json_resp(json!({
+          "metadata": { "name": p.rsplit('/').next().unwrap(), "version": 3 },
+          "spec": { "title": "old", "tags": [], "categories": [], "visible": "PUBLIC" },
+          "status": { "permalink": "/archives/hello" },
+        })),
+        ("POST", "apis/api.console.halo.run/v1alpha1/posts") | ("PUT", _) => json_resp(json!({})),
+        _ => Response::new(400, "application/json", r#"{"title":"Bad Request","detail":"unexpected call","status":400}"#),
+      }
+    })
+  }
+
+  fn config(site_url: String) -> HaloConfig {
+    let tag_map = HashMap::from([("rs".to_string(), "rust".to_string())]);
+    HaloConfig { site_url, token: " pat_xyz ".into(), policy_name: None, group_name: None, tag_map, http: HttpOptions::default() }
+  }
+
+  fn doc(front: &str) -> PublishDoc {
+    PublishDoc { content: Some(format!("---\n{}\n---\nbody *x*\n", front)), ..Default::default() }
+  }
+
+  #[test]
+  fn names_and_times() {
+    let n = new_name("t");
+    let groups: Vec<usize> = n.split('-').map(|g| g.len()).collect();
+    assert_eq!(groups, [8, 4, 4, 4, 12]);
+    assert!(n.split('-').nth(2).unwrap().starts_with('4'));
+    assert_eq!(halo_time("2025-01-02T03:04:05+08:00").as_deref(), Some("2025-01-01T19:04:05Z"));
+    let local = utc_time("2025-01-02").unwrap().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    assert_eq!(halo_time("2025-01-02"), Some(local));
+  }
+
+  #[tokio::test]
+  async fn create_and_publish() {
+    let srv = site();
+    let req = HaloPublishReq { doc: doc("title: Hello World\ntags: [rs, misc]\ncategories: 笔记"), post_id: None, publish: None, upload_images: false };
+    let res = publish(&config(srv.url("/")), &req).await.unwrap();
+    assert!(res.created);
+    assert_eq!(res.url, format!("{}/archives/hello", srv.base));
+
+    let reqs = srv.requests();
+    assert!(reqs.iter().all(|r| r.header("authorization") == Some("Bearer pat_xyz")));
+    let calls: Vec<String> = reqs.iter().map(|r| format!("{} {}", r.method, r.path)).collect();
+    assert_eq!(calls[..4], [
+      "GET /apis/content.halo.run/v1alpha1/tags",
+      "POST /apis/content.halo.run/v1alpha1/tags",
+      "GET /apis/content.halo.run/v1alpha1/categories",
+      "POST /apis/content.halo.run/v1alpha1/categories",
+    ]);
+    let created: Json = serde_json::from_slice(&reqs[4].body).unwrap();
+    let spec = &created["post"]["spec"];
+    assert_eq!(spec["tags"], json!(["tag-abc", "tag-new"]));
+    assert_eq!(spec["categories"], json!(["category-new"]));
+    assert_eq!(spec["slug"], "hello-world");
+    assert_eq!(spec["publish"], false);
+    assert_eq!(created["post"]["metadata"]["name"], json!(res.post_id));
+    assert_eq!(created["content"]["raw"], "body *x*\n");
+    assert_eq!(created["content"]["rawType"], "markdown");
+    assert_eq!(calls[5], format!("PUT /apis/api.console.halo.run/v1alpha1/posts/{}/publish", res.post_id));
+  }
+
+  #[tokio::test]
+  async fn update_and_unpublish() {
+    let srv = site();
+    let req = HaloPublishReq { doc: doc("title: New\ndraft: true\nexcerpt: short"), post_id: Some("p-old".into()), publish: None, upload_images: false };
+    let res = publish(&config(srv.url("")), &req).await.unwrap();
+    assert_eq!((res.post_id.as_str(), res.created), ("p-old", false));
+    let reqs = srv.requests();
+    let calls: Vec<String> = reqs.iter().map(|r| format!("{} {}", r.method, r.path)).collect();
+    assert_eq!(calls, [
+      "GET /apis/content.halo.run/v1alpha1/posts/p-old",
+      "PUT /apis/content.halo.run/v1alpha1/posts/p-old",
+      "PUT /apis/api.console.halo.run/v1alpha1/posts/p-old/content",
+      "PUT /apis/api.console.halo.run/v1alpha1/posts/p-old/unpublish",
+      "GET /apis/content.halo.run/v1alpha1/posts/p-old",
+    ]);
+    // 保留服务端的 metadata.version 与未改动的 spec 字段
+    let put: Json = serde_json::from_slice(&reqs[1].body).unwrap();
+    assert_eq!(put["metadata"]["version"], 3);
+    assert_eq!(put["spec"]["visible"], "PUBLIC");
+    assert_eq!(put["spec"]["title"], "New");
+    assert_eq!(put["spec"]["excerpt"], json!({ "autoGenerate": false, "raw": "short" }));
+
+    let err = list_terms(&config(srv.url("/nope")), HaloTermKind::Tags).await.unwrap_err();
+    assert_eq!(err, "HTTP 400: Bad Request unexpected call");
+  }
+}
@@ -0,0 +1,350 @@
+// MetaWeblog 发布（Typecho / WordPress / 博客园等）
+// 基于 xmlrpc 模块：getUsersBlogs、getCategories、newPost、editPost、getPost、deletePost、newMediaObject
+
+use serde::{Deserialize, Serialize};
+
+use super::{prepare, upload_local_images, utc_time, Prepared, PublishDoc, PublishResult};
+use crate::http_opts::HttpOptions;
+use crate::markdown;
+use crate::xmlrpc::{self, Value};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Flavor {
+  // 发送渲染后的 HTML
+  #[default]
+  Generic,
+  // 发送 Markdown，并加 <!--markdown--> 标记
+  Typecho,
+  Wordpress,
+  // 发送 Markdown，分类中加入 [Markdown]
+  Cnblogs,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetaWeblogConfig {
+  // XML-RPC 入口，如 https://example.com/action/xmlrpc
+  pub endpoint: String,
+  pub username: String,
+  pub password: String,
+  // 缺省时取 getUsersBlogs 的第一个博客
+  #[serde(default)]
+  pub blog_id: Option<String>,
+  #[serde(default)]
+  pub flavor: Flavor,
+  // 超时、代理、自定义 CA、附加头等
+  #[serde(default)]
+  pub http: HttpOptions,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlogInfo {
+  pub blog_id: String,
+  pub blog_name: String,
+  pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryInfo {
+  pub id: String,
+  pub name: String,
+  pub description: String,
+}
+
+// blogger.* 接口需要的 appKey，服务端普遍忽略
+const APP_KEY: &str = "flymd";
+
+impl MetaWeblogConfig {
+  // 发布状态记录中的目标标识
+  pub fn target(&self) -> String { super::state::target_id("metaweblog", &self.endpoint) }
+
+  fn user(&self) -> Value { Value::String(self.username.clone()) }
+  fn pass(&self) -> Value { Value::String(self.password.clone()) }
+
+  async fn call(&self, method: &str, params: Vec<Value>) -> Result<Value, String> {
+    xmlrpc::call(&self.endpoint, method, &params, &self.http).await.map_err(String::from)
+  }
+
+  async fn blog_id(&self) -> Result<String, String> {
+    if let Some(id) = self.blog_id.as_ref().filter(|s| !s.trim().is_empty()) { return Ok(id.trim().to_string()); }
+    get_users_blogs(self).await?
+      .into_iter()
+      .next()
+      .map(|b| b.blog_id)
+      .ok_or_else(|| "no blog found for this account".to_string())
+  }
+}
+
+pub async fn get_users_blogs(cfg: &MetaWeblogConfig) -> Result<Vec<BlogInfo>, String> {
+  let v = cfg.call("blogger.getUsersBlogs", vec![Value::String(APP_KEY.into()), cfg.user(), cfg.pass()]).await?;
+  let Value::Array(items) = v else { return Err("unexpected getUsersBlogs response".into()) };
+  Ok(items.iter().map(|b| BlogInfo {
+    blog_id: b.get("blogid").and_then(|x| x.as_string()).unwrap_or_default(),
+    blog_name: b.get("blogName").and_then(|x| x.as_string()).unwrap_or_default(),
+    url: b.get("url").and_then(|x| x.as_string()).unwrap_or_default(),
+  }).collect())
+}
+
+pub async fn get_categories(cfg: &MetaWeblogConfig) -> Result<Vec<CategoryInfo>, String> {
+  let blog_id = cfg.blog_id().await?;
+  let v = cfg.call("metaWeblog.getCategories", vec![Value::String(blog_id), cfg.user(), cfg.pass()]).await?;
+  let Value::Array(items) = v else { return Err("unexpected getCategories response".into()) };
+  Ok(items.iter().map(|c| {
+    let s = |k: &str| c.get(k).and_then(|x| x.as_string());
+    CategoryInfo {
+      id: s("categoryId").unwrap_or_default(),
+      // WordPress 用 categoryName，部分实现仅有 title/description
+      name: s("categoryName").or_else(|| s("title")).or_else(|| s("description")).unwrap_or_default(),
+      description: s("description").unwrap_or_default(),
+    }
+  }).collect())
+}
+
+pub async fn new_post(cfg: &MetaWeblogConfig, post: Value, publish: bool) -> Result<String, String> {
+  let blog_id = cfg.blog_id().await?;
+  let v = cfg.call("metaWeblog.newPost", vec![Value::String(blog_id), cfg.user(), cfg.pass(), post, Value::Bool(publish)]).await?;
+  v.as_string().ok_or_else(|| "unexpected newPost response".to_string())
+}
+
+pub async fn edit_post(cfg: &MetaWeblogConfig, post_id: &str, post: Value, publish: bool) -> Result<bool, String> {
+  let v = cfg.call("metaWeblog.editPost", vec![Value::String(post_id.into()), cfg.user(), cfg.pass(), post, Value::Bool(publish)]).await?;
+  Ok(!matches!(v, Value::Bool(false)))
+}
+
+pub async fn get_post(cfg: &MetaWeblogConfig, post_id: &str) -> Result<Value, String> {
+  cfg.call("metaWeblog.getPost", vec![Value::String(post_id.into()), cfg.user(), cfg.pass()]).await
+}
+
+pub async fn delete_post(cfg: &MetaWeblogConfig, post_id: &str) -> Result<bool, String> {
+  let v = cfg.call("blogger.deletePost", vec![Value::String(APP_KEY.into()), Value::String(post_id.into()), cfg.user(), cfg.pass(), Value::Bool(true)]).await?;
+  Ok(!matches!(v, Value::Bool(false)))
+}
+
+pub async fn new_media_object(cfg: &MetaWeblogConfig, name: &str, content_type: &str, bytes: Vec<u8>) -> Result<String, String> {
+  let blog_id = cfg.blog_id().await?;
+  let file = Value::Struct(vec![
+    ("name".into(), Value::String(name.into())),
+    ("type".into(), Value::String(content_type.into())),
+    ("bits".into(), Value::Base64(bytes)),
+    ("overwrite".into(), Value::Bool(false)),
+  ]);
+  let v = cfg.call("metaWeblog.newMediaObject", vec![Value::String(blog_id), cfg.user(), cfg.pass(), file]).await?;
+  v.get("url").and_then(|u| u.as_string()).ok_or_else(|| "newMediaObject returned no url".to_string())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetaWeblogPublishReq {
+  #[serde(flatten)]
+  pub doc: PublishDoc,
+  // 已发布文章的 id：有则 editPost，无则 newPost
+  #[serde(default)]
+  pub post_id: Option<String>,
+  // false 时保存为草稿；缺省按 front matter 的 draft 决定
+  #[serde(default)]
+  pub publish: Option<bool>,
+  #[serde(default = "super::default_true")]
+  pub upload_images: bool,
+}
+
+// 由文档构建 MetaWeblog post 结构，按站点类型决定发送 Markdown 还是 HTML
+fn build_post(flavor: Flavor, meta: &super::PostMeta, body: &str) -> Value {
+  let mut categories = meta.categories.clone();
+  let description = match flavor {
+    Flavor::Typecho => format!("<!--markdown-->{}", body),
+    Flavor::Cnblogs => {
+      if !categories.iter().any(|c| c == "[Markdown]") { categories.insert(0, "[Markdown]".into()); }
+      body.to_string()
+    }
+    Flavor::Generic | Flavor::Wordpress => markdown::render_html(body),
+  };
+  let mut members = vec![
+    ("title".to_string(), Value::String(meta.title.clone())),
+    ("description".to_string(), Value::String(description)),
+    ("categories".to_string(), Value::Array(categories.into_iter().map(Value::String).collect())),
+    ("mt_keywords".to_string(), Value::String(meta.tags.join(","))),
+  ];
+  if let Some(slug) = &meta.slug { members.push(("wp_slug".into(), Value::String(slug.clone()))); }
+  if let Some(ex) = &meta.excerpt { members.push(("mt_excerpt".into(), Value::String(ex.clone()))); }
+  if let Some(date) = meta.date.as_deref().and_then(xmlrpc_date) { members.push(("dateCreated".into(), Value::DateTime(date))); }
+  Value::Struct(members)
+}
+
+// front matter 日期转为 XML-RPC dateTime（UTC）；时区规则见 utc_time
+fn xmlrpc_date(s: &str) -> Option<String> {
+  utc_time(s).map(|t| t.format("%Y%m%dT%H:%M:%S").to_string())
+}
+
+// 发布：上传本地图片 -> newPost/editPost -> getPost 取文章链接
+pub async fn publish(cfg: &MetaWeblogConfig, req: &MetaWeblogPublishReq) -> Result<PublishResult, String> {
+  let Prepared { meta, body } = prepare(&req.doc, Some(&cfg.target())).await?;
+  let doc_dir = req.doc.doc_dir();
+  let (body, images_uploaded) = if req.upload_images {
+    upload_local_images(&body, doc_dir.as_deref(), |img| async move {
+      new_media_object(cfg, &img.name, &img.content_type, img.bytes).await
+    }).await?
+  } else {
+    (body, 0)
+  };
+  let post = build_post(cfg.flavor, &meta, &body);
+  let publish = req.publish.unwrap_or(!meta.draft);
+  let existing = req.post_id.as_ref().map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+  let (post_id, created) = match existing {
+    Some(id) => { edit_post(cfg, &id, post, publish).await?; (id, false) }
+    None => (new_post(cfg, post, publish).await?, true),
+  };
+  // 链接获取失败不影响发布结果
+  let url = match get_post(cfg, &post_id).await {
+    Ok(p) => p.get("link").or_else(|| p.get("permaLink")).and_then(|u| u.as_string()).unwrap_or_default(),
+    Err(_) => String::new(),
+  };
+  Ok(PublishResult { post_id, url, created, images_uploaded, document: None })
+}
+
+#[tauri::command]
+pub async fn metaweblog_get_users_blogs(cfg: MetaWeblogConfig) -> Result<Vec<BlogInfo>, String> {
+  get_users_blogs(&cfg).await
+}
+
+#[tauri::command]
+pub async fn metaweblog_get_categories(cfg: MetaWeblogConfig) -> Result<Vec<CategoryInfo>, String> {
+  get_categories(&cfg).await
+}
+
+#[tauri::command]
+pub async fn metaweblog_get_post(cfg: MetaWeblogConfig, post_id: String) -> Result<serde_json::Value, String> {
+  Ok(get_post(&cfg, &post_id).await?.to_json())
+}
+
+#[tauri::command]
+pub async fn metaweblog_delete_post(cfg: MetaWeblogConfig, post_id: String) -> Result<bool, String> {
+  delete_post(&cfg, &post_id).await
+}
+
+// 单独上传一个本地文件（按上传策略校验类型与大小），返回站点上的 URL
+#[tauri::command]
+pub async fn metaweblog_new_media_object(cfg: MetaWeblogConfig, path: String) -> Result<String, String> {
+  let p = std::path::PathBuf::from(&path);
+  let bytes = super::read_file(p.clone()).await?;
+  let checked = crate::upload_policy::UploadPolicy::default().enforce(bytes, None)?;
+  let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "file".into());
+  new_media_object(&cfg, &name, &checked.content_type, checked.bytes).await
+}
+
+#[tauri::command]
+pub async fn metaweblog_publish(cfg: MetaWeblogConfig, mut req: MetaWeblogPublishReq) -> Result<PublishResult, String> {
+  let target = cfg.target();
+  req.post_id = super::state::resolve_post_id(&req.doc, &target, req.post_id.take()).await?;
+  let mut res = publish(&cfg, &req).await?;
+  super::state::record(&req.doc, &target, &mut res).await?;
+  Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_http::{serve, Response};
+
+  fn ok(value: &str) -> Response {
+    Response::new(200, "text/xml", format!("<?xml version=\"1.0\"?><methodResponse><params><param><value>{}</value></param></params></methodResponse>", value))
+  }
+
+  fn method_of(body: &str) -> String {
+    body.split("<methodName>").nth(1).and_then(|s| s.split("</methodName>").next()).unwrap_or_default().to_string()
+  }
+
+  // 仿 Typecho/WordPress 的 xmlrpc 入口
+  fn blog() -> crate::test_http::Server {
+    serve(|req| match method_of(&req.text()).as_str() {
+      "blogger.getUsersBlogs" => ok("<array><data><value><struct>\
+        <member><name>blogid</name><value><int>7</int></value></member>\
+        <member><name>blogName</name><value><string>My &amp; Blog</string></value></member>\
+        <member><name>url</name><value><string>https://b.example/</string></value></member>\
+        </struct></value></data></array>"),
+      "metaWeblog.getCategories" => ok("<array><data><value><struct>\
+        <member><name>categoryId</name><value><string>3</string></value></member>\
+        <member><name>title</name><value><string>随笔</string></value></member>\
+        </struct></value></data></array>"),
+      "metaWeblog.newMediaObject" => ok("<struct><member><name>url</name><value><string>https://b.example/up/a.png</string></value></member></struct>"),
+      "metaWeblog.newPost" => ok("<string>42</string>"),
+      "metaWeblog.editPost" => ok("<boolean>1</boolean>"),
+      "metaWeblog.getPost" => ok("<struct><member><name>link</name><value><string>https://b.example/p/42</string></value></member></struct>"),
+      _ => Response::new(200, "text/xml", "<methodResponse><fault><value><struct>\
+        <member><name>faultCode</name><value><int>-32601</int></value></member>\
+        <member><name>faultString</name><value><string>server error. requested method does not exist.</string></value></member>\
+        </struct></value></fault></methodResponse>"),
+    })
+  }
+
+  fn config(endpoint: String, flavor: Flavor) -> MetaWeblogConfig {
+    MetaWeblogConfig { endpoint, username: "u".into(), password: "p&w".into(), blog_id: None, flavor, http: HttpOptions::default() }
+  }
+
+  #[tokio::test]
+  async fn blogs_and_categories() {
+    let srv = blog();
+    let cfg = config(srv.url("/xmlrpc"), Flavor::Generic);
+    let blogs = get_users_blogs(&cfg).await.unwrap();
+    assert_eq!((blogs[0].blog_id.as_str(), blogs[0].blog_name.as_str()), ("7", "My & Blog"));
+    let cats = get_categories(&cfg).await.unwrap();
+    assert_eq!((cats[0].id.as_str(), cats[0].name.as_str()), ("3", "随笔"));
+    let reqs = srv.requests();
+    assert!(reqs.iter().all(|r| r.method == "POST" && r.path == "/xmlrpc"));
+    assert!(reqs.iter().all(|r| r.header("content-type").unwrap_or("").starts_with("text/xml")));
+    // 未配置 blog_id 时先取第一个博客，密码按 XML 转义发送
+    assert!(reqs[2].text().contains("<param><value><string>7</string></value></param><param><value><string>u</string></value></param><param><value><string>p&amp;w</string></value></param>"));
+  }
+
+  #[tokio::test]
+  async fn publish_uploads_images_and_creates_post() {
+    let srv = blog();
+    let dir = std::env::temp_dir().join(format!("flymd-mwb-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    // 最小 PNG 头即可通过类型嗅探
+    std::fs::write(dir.join("a.png"), b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+    let doc = PublishDoc {
+      doc_path: Some(dir.join("post.md").to_string_lossy().to_string()),
+      content: Some("---\ntitle: Hello\ntags: [x, y]\ndate: 2025-01-02 03:04\n---\n\n![](a.png) and ![again](a.png)\n".into()),
+      ..Default::default()
+    };
+    let req = MetaWeblogPublishReq { doc, post_id: None, publish: None, upload_images: true };
+    let cfg = MetaWeblogConfig { blog_id: Some("9".into()), ..config(srv.url("/xmlrpc"), Flavor::Typecho) };
+    let res = publish(&cfg, &req).await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert_eq!((res.post_id.as_str(), res.url.as_str(), res.created, res.images_uploaded), ("42", "https://b.example/p/42", true, 1));
+
+    let reqs = srv.requests();
+    let methods: Vec<String> = reqs.iter().map(|r| method_of(&r.text())).collect();
+    assert_eq!(methods, ["metaWeblog.newMediaObject", "metaWeblog.newPost", "metaWeblog.getPost"]);
+    let post = reqs[1].text();
+    assert!(post.contains("<string>&lt;!--markdown--&gt;\n![](https://b.example/up/a.png) and ![again](https://b.example/up/a.png)"));
+    assert!(post.contains("<name>mt_keywords</name><value><string>x,y</string></value>"));
+    assert!(post.contains(&format!("<dateTime.iso8601>{}</dateTime.iso8601>", xmlrpc_date("2025-01-02 03:04").unwrap())));
+    assert!(post.ends_with("<value><boolean>1</boolean></value></param></params></methodCall>"));
+  }
+
+  #[tokio::test]
+  async fn edit_and_errors() {
+    let srv = blog();
+    let cfg = config(srv.url("/xmlrpc"), Flavor::Cnblogs);
+    let doc = PublishDoc { content: Some("---\ntitle: T\ndraft: true\n---\nbody".into()), ..Default::default() };
+    let req = MetaWeblogPublishReq { doc, post_id: Some(" 5 ".into()), publish: None, upload_images: false };
+    let res = publish(&cfg, &req).await.unwrap();
+    assert_eq!((res.post_id.as_str(), res.created), ("5", false));
+    let edit = srv.requests()[0].text();
+    assert_eq!(method_of(&edit), "metaWeblog.editPost");
+    assert!(edit.contains("<name>categories</name><value><array><data><value><string>[Markdown]</string></value>"));
+    // draft: true 保存为草稿
+    assert!(edit.ends_with("<value><boolean>0</boolean></value></param></params></methodCall>"));
+
+    let err = delete_post(&cfg, "5").await.unwrap_err();
+    assert!(err.contains("-32601"), "{}", err);
+
+    let down = serve(|_| Response::new(502, "text/html", "bad gateway"));
+    let err = get_users_blogs(&config(down.url("/xmlrpc"), Flavor::Generic)).await.unwrap_err();
+    assert!(err.starts_with("HTTP 502"), "{}", err);
+  }
+}
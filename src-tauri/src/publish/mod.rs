@@ -0,0 +1,301 @@
+// 发布器公共部分：待发布文档的读取、文章元信息（Front Matter）、本地图片上传与地址回填
+
+pub mod ghost;
+pub mod halo;
+pub mod metaweblog;
+pub mod state;
+pub mod wordpress;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as Json};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::http_opts::{HttpError, HttpOptions};
+use crate::markdown;
+use crate::transform::{self, TransformOptions};
+use crate::upload_policy::UploadPolicy;
+
+// 前端传入的待发布文档：优先使用 content（可能尚未保存），doc_path 用于解析相对图片路径
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishDoc {
+  #[serde(default)]
+  pub doc_path: Option<String>,
+  #[serde(default)]
+  pub content: Option<String>,
+  // 发布前的正文转换
+  #[serde(default)]
+  pub transform: TransformOptions,
+}
+
+impl PublishDoc {
+  pub async fn load(&self) -> Result<String, String> {
+    if let Some(c) = &self.content { return Ok(c.clone()); }
+    let p = self.doc_path.clone().ok_or_else(|| "no document content".to_string())?;
+    let bytes = read_file(PathBuf::from(p)).await?;
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+  }
+
+  pub fn doc_dir(&self) -> Option<PathBuf> {
+    self.doc_path.as_ref().and_then(|p| Path::new(p).parent().map(|d| d.to_path_buf()))
+  }
+
+  fn file_stem(&self) -> Option<String> {
+    self.doc_path.as_ref().and_then(|p| Path::new(p).file_stem().map(|s| s.to_string_lossy().to_string()))
+  }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostMeta {
+  pub title: String,
+  pub tags: Vec<String>,
+  pub categories: Vec<String>,
+  pub slug: Option<String>,
+  pub excerpt: Option<String>,
+  pub date: Option<String>,
+  // 最后修改时间（updated / lastmod / modified）
+  pub updated: Option<String>,
+  pub draft: bool,
+}
+
+// 字符串或数组统一为列表："a, b" / [a, b]
+fn string_list(v: Option<&Json>) -> Vec<String> {
+  match v {
+    Some(Json::Array(a)) => a.iter().filter_map(|x| match x {
+      Json::String(s) => Some(s.trim().to_string()),
+      Json::Number(n) => Some(n.to_string()),
+      _ => None,
+    }).filter(|s| !s.is_empty()).collect(),
+    Some(Json::String(s)) => s.split([',', '，']).map(|x| x.trim().to_string()).filter(|x| !x.is_empty()).collect(),
+    _ => Vec::new(),
+  }
+}
+
+fn string_field(front: &Map<String, Json>, keys: &[&str]) -> Option<String> {
+  keys.iter().find_map(|k| match front.get(*k) {
+    Some(Json::String(s)) if !s.trim().is_empty() => Some(s.trim().to_string()),
+    Some(Json::Number(n)) => Some(n.to_string()),
+    _ => None,
+  })
+}
+
+// 标题优先级：front matter title > 首个一级标题 > 文件名
+pub fn post_meta(front: &Map<String, Json>, body: &str, doc: &PublishDoc) -> PostMeta {
+  let title = string_field(front, &["title"])
+    .or_else(|| markdown::first_heading(body))
+    .or_else(|| doc.file_stem())
+    .unwrap_or_else(|| "Untitled".to_string());
+  let mut tags = string_list(front.get("tags"));
+  if tags.is_empty() { tags = string_list(front.get("tag").or_else(|| front.get("keywords"))); }
+  let mut categories = string_list(front.get("categories"));
+  if categories.is_empty() { categories = string_list(front.get("category")); }
+  let flag = |k: &str| match front.get(k) {
+    Some(Json::Bool(b)) => Some(*b),
+    Some(Json::String(s)) => Some(s.eq_ignore_ascii_case("true") || s.eq_ignore_ascii_case("yes")),
+    _ => None,
+  };
+  // Jekyll/Hexo 用 published: false 表示草稿
+  let draft = flag("draft").unwrap_or(false) || flag("published") == Some(false);
+  PostMeta {
+    title,
+    tags,
+    categories,
+    slug: string_field(front, &["slug", "permalink"]),
+    excerpt: string_field(front, &["excerpt", "summary", "description"]),
+    date: string_field(front, &["date", "created"]),
+    updated: string_field(front, &["updated", "lastmod", "modified"]),
+    draft,
+  }
+}
+
+pub fn default_true() -> bool { true }
+
+// 各发布器共用的时间规则（front matter 的 date 与定时发布时间）：带时区偏移的按偏移换算为 UTC，
+// 不带时区的（YYYY-MM-DD[ HH:MM[:SS]]）按本机时区理解；夏令时切换处不存在或重复的时刻取较早的解释
+pub fn utc_time(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+  use chrono::{Offset, TimeZone};
+  let (naive, off) = crate::export::parse_date(s)?;
+  let off = off.unwrap_or_else(|| match chrono::Local.from_local_datetime(&naive).earliest() {
+    Some(t) => t.offset().fix(),
+    None => chrono::Local.offset_from_utc_datetime(&naive).fix(),
+  });
+  off.from_local_datetime(&naive).single().map(|t| t.with_timezone(&chrono::Utc))
+}
+
+// 经过转换流水线、待发送的文档
+pub struct Prepared {
+  pub meta: PostMeta,
+  pub body: String,
+}
+
+// [[wiki 链接]]：在文档目录下找同名笔记，取其发布到同一目标时记录的链接
+pub fn wiki_resolver(doc_dir: Option<PathBuf>, target: Option<String>) -> impl Fn(&str) -> Option<String> {
+  move |name: &str| {
+    let (dir, target) = (doc_dir.as_ref()?, target.as_deref()?);
+    let file = ["md", "markdown"].iter().map(|ext| dir.join(format!("{}.{}", name, ext))).find(|p| p.is_file())?;
+    let text = std::fs::read_to_string(file).ok()?;
+    state::find_entry(&text, target).map(|e| e.url).filter(|u| !u.is_empty())
+  }
+}
+
+// 读取文档 -> 解析元信息 -> 执行转换流水线；target 用于解析 wiki 链接
+pub async fn prepare(doc: &PublishDoc, target: Option<&str>) -> Result<Prepared, String> {
+  let text = doc.load().await?;
+  let (front, body) = markdown::parse_front_matter(&text);
+  let meta = post_meta(&front, body, doc);
+  let resolve = wiki_resolver(doc.doc_dir(), target.map(|t| t.to_string()));
+  let body = transform::apply(body, &doc.transform, &resolve);
+  Ok(Prepared { meta, body })
+}
+
+// 按映射表改名（忽略大小写），未命中的原样保留；结果去重
+pub fn map_names(names: &[String], map: &HashMap<String, String>) -> Vec<String> {
+  let mut out: Vec<String> = Vec::new();
+  for n in names {
+    let mapped = map.iter().find(|(k, _)| k.eq_ignore_ascii_case(n)).map(|(_, v)| v.trim().to_string()).unwrap_or_else(|| n.clone());
+    if !mapped.is_empty() && !out.iter().any(|x| x.eq_ignore_ascii_case(&mapped)) { out.push(mapped); }
+  }
+  out
+}
+
+// 生成 URL 友好的 slug：ASCII 字母数字保留，其余折叠为 '-'；中文等非 ASCII 标题可能得到空串
+pub fn slugify(s: &str) -> String {
+  let mut out = String::new();
+  for c in s.chars() {
+    if c.is_ascii_alphanumeric() { out.push(c.to_ascii_lowercase()); }
+    else if !out.is_empty() && !out.ends_with('-') { out.push('-'); }
+  }
+  out.trim_end_matches('-').to_string()
+}
+
+// 发布器共用的 HTTP 客户端：默认 60 秒超时，可由发布配置中的 http 选项覆盖
+pub fn http_client(opts: &HttpOptions, base_url: &str) -> Result<reqwest::Client, String> {
+  opts.client(base_url, Duration::from_secs(60), concat!("flymd/", env!("CARGO_PKG_VERSION"))).map_err(String::from)
+}
+
+// 发送失败时按网络/TLS 等分类给出错误信息
+pub fn send_error(e: reqwest::Error) -> String {
+  HttpError::from(e).to_string()
+}
+
+// 后台线程读取文件，避免阻塞异步运行时
+pub async fn read_file(path: PathBuf) -> Result<Vec<u8>, String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    std::fs::read(&path).map_err(|e| format!("read {} error: {e}", path.display()))
+  })
+  .await
+  .map_err(|e| format!("join error: {e}"))?
+}
+
+// 正文中引用的本地图片（已按上传策略校验）
+#[derive(Debug)]
+pub struct LocalImage {
+  pub name: String,
+  pub content_type: String,
+  pub bytes: Vec<u8>,
+}
+
+// 将图片地址解析为本地文件路径；远程地址与 data: 返回 None
+pub fn resolve_local_path(url: &str, doc_dir: Option<&Path>) -> Option<PathBuf> {
+  let u = url.trim();
+  let lower = u.to_ascii_lowercase();
+  if lower.starts_with("http://") || lower.starts_with("https://") || lower.starts_with("data:") || lower.starts_with("//") || u.is_empty() {
+    return None;
+  }
+  let raw = if lower.starts_with("file://") {
+    url::Url::parse(u).ok()?.to_file_path().ok()?
+  } else {
+    let decoded = percent_encoding::percent_decode_str(u).decode_utf8_lossy().to_string();
+    let p = PathBuf::from(&decoded);
+    if p.is_absolute() { p } else { doc_dir?.join(p) }
+  };
+  if raw.is_file() { Some(raw) } else { None }
+}
+
+// 上传正文中的本地图片并回填外链；同一文件只上传一次。返回 (新正文, 上传数量)
+pub async fn upload_local_images<F, Fut>(body: &str, doc_dir: Option<&Path>, upload: F) -> Result<(String, usize), String>
+where
+  F: Fn(LocalImage) -> Fut,
+  Fut: Future<Output = Result<String, String>>,
+{
+  let refs = markdown::find_images(body);
+  let mut uploaded: HashMap<PathBuf, String> = HashMap::new();
+  let mut edits = Vec::new();
+  let policy = UploadPolicy::default();
+  for r in refs {
+    let Some(path) = resolve_local_path(&r.url, doc_dir) else { continue };
+    if let Some(u) = uploaded.get(&path) {
+      edits.push((r.range, u.clone()));
+      continue;
+    }
+    let bytes = read_file(path.clone()).await?;
+    let checked = policy.enforce(bytes, None).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "image".into());
+    let url = upload(LocalImage { name, content_type: checked.content_type, bytes: checked.bytes }).await?;
+    uploaded.insert(path, url.clone());
+    edits.push((r.range, url));
+  }
+  Ok((markdown::replace_ranges(body, edits), uploaded.len()))
+}
+
+// 发布结果：post_id 与 url 供再次发布时更新同一篇文章
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishResult {
+  pub post_id: String,
+  pub url: String,
+  pub created: bool,
+  pub images_uploaded: usize,
+  // 写入 published 记录后的文档全文
+  pub document: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishPreview {
+  pub meta: PostMeta,
+  // 转换后将要发送的 Markdown 与对应 HTML
+  pub markdown: String,
+  pub html: String,
+  // 发布时会被上传的本地图片
+  pub local_images: Vec<String>,
+}
+
+// 预览转换流水线的输出：与发布时发送的内容一致（图片地址在上传后才会被替换）
+#[tauri::command]
+pub async fn publish_preview(doc: PublishDoc, target: Option<String>) -> Result<PublishPreview, String> {
+  let Prepared { meta, body } = prepare(&doc, target.as_deref()).await?;
+  let doc_dir = doc.doc_dir();
+  let mut local_images: Vec<String> = Vec::new();
+  for r in markdown::find_images(&body) {
+    if let Some(p) = resolve_local_path(&r.url, doc_dir.as_deref()) {
+      let s = p.to_string_lossy().to_string();
+      if !local_images.contains(&s) { local_images.push(s); }
+    }
+  }
+  Ok(PublishPreview { html: markdown::render_html(&body), markdown: body, meta, local_images })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeZone;
+
+  #[test]
+  fn times() {
+    let t = |s: &str| utc_time(s).map(|d| d.format("%Y-%m-%dT%H:%M:%SZ").to_string());
+    assert_eq!(t("2025-01-02T03:04:05+08:00").as_deref(), Some("2025-01-01T19:04:05Z"));
+    assert_eq!(t("2025-01-02T03:04:05Z").as_deref(), Some("2025-01-02T03:04:05Z"));
+    // 不带时区的时间按本机时区
+    let local = |y, mo, d, h, mi| chrono::Local.with_ymd_and_hms(y, mo, d, h, mi, 0).earliest().unwrap().with_timezone(&chrono::Utc);
+    assert_eq!(utc_time("2025-03-01 08:30"), Some(local(2025, 3, 1, 8, 30)));
+    assert_eq!(utc_time("2025-03-01T08:30:00"), Some(local(2025, 3, 1, 8, 30)));
+    assert_eq!(utc_time(" 2025-03-01 "), Some(local(2025, 3, 1, 0, 0)));
+    assert_eq!(utc_time("next tuesday"), None);
+    assert_eq!(utc_time(""), None);
+  }
+}
@@ -0,0 +1,241 @@
+// 发布状态记录：在文档 Front Matter 中维护 flymd_published: 列表（目标、文章 id、链接、发布时内容哈希）
+// 再次发布同一目标时据此更新而非新建；只做文本级改写，不动 Front Matter 的其余部分。
+// 不用 published: 作键名：Jekyll/Hexo 等用它表示是否发布（published: false），不能被覆盖
+
+use serde::Serialize;
+use serde_json::Value as Json;
+use sha2::{Digest, Sha256};
+use std::ops::Range;
+use std::path::PathBuf;
+
+use super::{PublishDoc, PublishResult};
+use crate::markdown;
+
+const FIELD: &str = "flymd_published";
+const KEY: &str = "flymd_published:";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishedEntry {
+  // 发布目标标识，如 wordpress:https://example.com
+  pub target: String,
+  pub id: String,
+  pub url: String,
+  pub hash: String,
+  pub at: String,
+}
+
+// 目标标识：类型 + 去掉尾部斜杠的地址
+pub fn target_id(kind: &str, endpoint: &str) -> String {
+  format!("{}:{}", kind, endpoint.trim().trim_end_matches('/'))
+}
+
+// 记录块在 YAML 文本中的区间：顶格的 flymd_published: 行及其后缩进行/列表项
+fn block_range(yaml: &str) -> Option<Range<usize>> {
+  let mut start = None;
+  let mut pos = 0;
+  for line in yaml.split_inclusive('\n') {
+    let end = pos + line.len();
+    match start {
+      None if line.starts_with(KEY) => start = Some(pos),
+      Some(s) => {
+        let t = line.trim_end();
+        let continues = t.is_empty() || line.starts_with(' ') || line.starts_with('\t') || line.starts_with("- ") || t == "-";
+        if !continues { return Some(s..pos); }
+      }
+      None => {}
+    }
+    pos = end;
+  }
+  start.map(|s| s..yaml.len())
+}
+
+// 只解析记录块本身（兼容单个对象的写法），Front Matter 中其他键有 YAML 错误时也能读到记录；
+// 没有记录块时为空列表，记录块本身无法解析时报错
+fn parse_block(text: &str) -> Result<Vec<PublishedEntry>, String> {
+  let (yaml, _, _) = markdown::split_front_matter(text);
+  let Some(block) = yaml.and_then(|y| block_range(y).map(|r| &y[r])) else { return Ok(Vec::new()) };
+  let v: Json = serde_yaml::from_str(block).map_err(|e| format!("invalid {} block: {e}", FIELD))?;
+  let items = match v.get(FIELD) {
+    Some(Json::Array(a)) => a.clone(),
+    Some(v @ Json::Object(_)) => vec![v.clone()],
+    Some(Json::Null) | None => Vec::new(),
+    Some(_) => return Err(format!("invalid {} block: not a list", FIELD)),
+  };
+  Ok(items.iter().filter_map(|v| {
+    let s = |k: &str| match v.get(k) {
+      Some(Json::String(s)) => s.trim().to_string(),
+      Some(Json::Number(n)) => n.to_string(),
+      _ => String::new(),
+    };
+    let e = PublishedEntry { target: s("target"), id: s("id"), url: s("url"), hash: s("hash"), at: s("at") };
+    if e.target.is_empty() || e.id.is_empty() { None } else { Some(e) }
+  }).collect())
+}
+
+pub fn read_entries(text: &str) -> Vec<PublishedEntry> {
+  parse_block(text).unwrap_or_default()
+}
+
+pub fn find_entry(text: &str, target: &str) -> Option<PublishedEntry> {
+  read_entries(text).into_iter().find(|e| e.target == target)
+}
+
+// 内容哈希：去掉记录块后的 Front Matter + 正文，换行统一为 \n
+pub fn content_hash(text: &str) -> String {
+  let (yaml, body, _) = markdown::split_front_matter(text);
+  let mut front = yaml.unwrap_or("").to_string();
+  if let Some(r) = block_range(&front) { front.replace_range(r, ""); }
+  let mut h = Sha256::new();
+  h.update(front.replace("\r\n", "\n").trim().as_bytes());
+  h.update(b"\n---\n");
+  h.update(body.replace("\r\n", "\n").as_bytes());
+  hex::encode(h.finalize())
+}
+
+// JSON 字符串即合法的 YAML 双引号标量
+fn quote(s: &str) -> String {
+  serde_json::to_string(s).unwrap_or_else(|_| "\"\"".into())
+}
+
+fn render_block(entries: &[PublishedEntry], nl: &str) -> String {
+  let mut out = format!("{}{}", KEY, nl);
+  for e in entries {
+    out.push_str(&format!("  - target: {}{}", quote(&e.target), nl));
+    for (k, v) in [("id", &e.id), ("url", &e.url), ("hash", &e.hash), ("at", &e.at)] {
+      out.push_str(&format!("    {}: {}{}", k, quote(v), nl));
+    }
+  }
+  out
+}
+
+// 写入（按 target 覆盖或追加）一条记录，返回改写后的全文；
+// 已有记录块无法解析时拒绝改写，避免丢掉其他目标的记录
+pub fn upsert_entry(text: &str, entry: PublishedEntry) -> Result<String, String> {
+  let nl = if text.contains("\r\n") { "\r\n" } else { "\n" };
+  let mut entries = parse_block(text)?;
+  match entries.iter_mut().find(|e| e.target == entry.target) {
+    Some(e) => *e = entry,
+    None => entries.push(entry),
+  }
+  let block = render_block(&entries, nl);
+  let (yaml, _, _) = markdown::split_front_matter(text);
+  let Some(yaml) = yaml else {
+    return Ok(format!("---{nl}{block}---{nl}{text}"));
+  };
+  // yaml 是 text 的子切片，由指针差得到其偏移
+  let base = yaml.as_ptr() as usize - text.as_ptr() as usize;
+  let mut out = text.to_string();
+  match block_range(yaml) {
+    Some(r) => out.replace_range(base + r.start..base + r.end, &block),
+    None => {
+      let sep = if yaml.is_empty() || yaml.ends_with('\n') { "" } else { nl };
+      out.insert_str(base + yaml.len(), &format!("{sep}{block}"));
+    }
+  }
+  Ok(out)
+}
+
+// 未显式指定文章 id 时，取该目标上次发布的 id
+pub async fn resolve_post_id(doc: &PublishDoc, target: &str, explicit: Option<String>) -> Result<Option<String>, String> {
+  if let Some(id) = explicit.filter(|s| !s.trim().is_empty()) { return Ok(Some(id)); }
+  let text = doc.load().await?;
+  Ok(find_entry(&text, target).map(|e| e.id))
+}
+
+// 发布成功后记录状态：返回新全文；仅当文档直接来自磁盘（未传 content）时写回文件，
+// 否则由前端把 document 合并进编辑器，避免覆盖未保存的修改
+pub async fn record(doc: &PublishDoc, target: &str, res: &mut PublishResult) -> Result<(), String> {
+  let text = doc.load().await?;
+  let at = chrono::DateTime::<chrono::Utc>::from(std::time::SystemTime::now()).format("%Y-%m-%dT%H:%M:%SZ").to_string();
+  let entry = PublishedEntry { target: target.to_string(), id: res.post_id.clone(), url: res.url.clone(), hash: content_hash(&text), at };
+  // 文章已发出：记录失败时在错误中带上 id 与链接，便于手动补记
+  let updated = upsert_entry(&text, entry)
+    .map_err(|e| format!("published as {} ({}) but not recorded: {e}", res.post_id, res.url))?;
+  if doc.content.is_none() {
+    if let Some(p) = doc.doc_path.clone() {
+      let data = updated.clone();
+      tauri::async_runtime::spawn_blocking(move || {
+        std::fs::write(PathBuf::from(&p), data).map_err(|e| format!("write {} error: {e}", p))
+      })
+      .await
+      .map_err(|e| format!("join error: {e}"))??;
+    }
+  }
+  res.document = Some(updated);
+  Ok(())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishStatus {
+  #[serde(flatten)]
+  pub entry: PublishedEntry,
+  // 当前内容与上次发布时不同
+  pub changed: bool,
+}
+
+#[tauri::command]
+pub async fn publish_status(doc: PublishDoc) -> Result<Vec<PublishStatus>, String> {
+  let text = doc.load().await?;
+  let hash = content_hash(&text);
+  Ok(read_entries(&text).into_iter().map(|e| PublishStatus { changed: e.hash != hash, entry: e }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entry(target: &str, id: &str) -> PublishedEntry {
+    PublishedEntry { target: target.into(), id: id.into(), url: format!("https://x/{}", id), hash: "h".into(), at: "2025-01-01T00:00:00Z".into() }
+  }
+
+  #[test]
+  fn keeps_scalar_published() {
+    let text = "---\ntitle: A\npublished: false\ntags: [x]\n---\nbody\n";
+    let out = upsert_entry(text, entry("wordpress:https://w", "1")).unwrap();
+    assert!(out.starts_with("---\ntitle: A\npublished: false\ntags: [x]\nflymd_published:\n  - target: \"wordpress:https://w\"\n    id: \"1\"\n"));
+    assert!(out.ends_with("---\nbody\n"));
+    assert_eq!(find_entry(&out, "wordpress:https://w").map(|e| e.id).as_deref(), Some("1"));
+
+    // 记录块放在 published: 之前时也只替换自己
+    let text = "---\nflymd_published:\n  - target: \"a\"\n    id: \"1\"\npublished: true\n---\nbody\n";
+    let out = upsert_entry(text, entry("a", "2")).unwrap();
+    assert!(out.contains("\npublished: true\n"));
+    assert_eq!(read_entries(&out).len(), 1);
+    assert_eq!(find_entry(&out, "a").map(|e| e.id).as_deref(), Some("2"));
+  }
+
+  #[test]
+  fn upsert_and_hash() {
+    let text = "body only\r\n";
+    let once = upsert_entry(text, entry("a", "1")).unwrap();
+    assert!(once.starts_with("---\r\nflymd_published:\r\n"));
+    let twice = upsert_entry(&once, entry("b", "9")).unwrap();
+    let ids: Vec<String> = read_entries(&twice).into_iter().map(|e| e.id).collect();
+    assert_eq!(ids, ["1", "9"]);
+    // 记录本身不影响内容哈希；published: 属于正文元信息，参与哈希
+    assert_eq!(content_hash(&twice), content_hash(&once));
+    let a = "---\ntitle: A\n---\nbody\n";
+    assert_eq!(content_hash(a), content_hash(&upsert_entry(a, entry("a", "1")).unwrap()));
+    assert_ne!(content_hash(a), content_hash("---\ntitle: A\npublished: false\n---\nbody\n"));
+  }
+
+  #[test]
+  fn invalid_front_matter() {
+    // 其他键的 YAML 有误：仍能读到并保留已有记录
+    let text = "---\ntitle: [unclosed\nflymd_published:\n  - target: \"a\"\n    id: \"1\"\n---\nbody\n";
+    assert_eq!(find_entry(text, "a").map(|e| e.id).as_deref(), Some("1"));
+    let out = upsert_entry(text, entry("b", "2")).unwrap();
+    let ids: Vec<String> = read_entries(&out).into_iter().map(|e| e.id).collect();
+    assert_eq!(ids, ["1", "2"]);
+    assert!(out.starts_with("---\ntitle: [unclosed\nflymd_published:\n"));
+
+    // 记录块本身无法解析：拒绝改写
+    let text = "---\nflymd_published:\n  - target: \"a\n    id: [\n---\nbody\n";
+    assert!(read_entries(text).is_empty());
+    assert!(upsert_entry(text, entry("b", "2")).unwrap_err().starts_with("invalid flymd_published block"));
+    assert!(upsert_entry("---\nflymd_published: 3\n---\n", entry("b", "2")).is_err());
+    assert_eq!(read_entries("---\nflymd_published:\n---\n").len(), 0);
+  }
+}
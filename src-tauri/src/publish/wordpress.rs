@@ -0,0 +1,317 @@
+// WordPress REST API 发布（/wp-json/wp/v2），使用“应用程序密码”Basic 认证
+// 适用于关闭了 XML-RPC 的站点；正文在 Rust 侧渲染为 HTML
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as Json};
+
+use super::{http_client, send_error, prepare, upload_local_images, utc_time, Prepared, PublishDoc, PublishResult};
+use crate::http_opts::HttpOptions;
+use crate::markdown;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordPressConfig {
+  // 站点根地址，如 https://example.com
+  pub site_url: String,
+  pub username: String,
+  // 后台“用户 -> 应用程序密码”生成，形如 "abcd efgh ijkl ..."
+  pub app_password: String,
+  // 超时、代理、自定义 CA、附加头等
+  #[serde(default)]
+  pub http: HttpOptions,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WpTerm {
+  pub id: i64,
+  pub name: String,
+  pub slug: String,
+  pub count: i64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TermKind {
+  Categories,
+  Tags,
+}
+
+impl TermKind {
+  fn path(self) -> &'static str {
+    match self { TermKind::Categories => "categories", TermKind::Tags => "tags" }
+  }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WpMedia {
+  pub id: i64,
+  pub url: String,
+}
+
+impl WordPressConfig {
+  // 发布状态记录中的目标标识
+  pub fn target(&self) -> String { super::state::target_id("wordpress", &self.site_url) }
+
+  // 按站点地址与 http 选项构建客户端
+  fn client(&self) -> Result<reqwest::Client, String> { http_client(&self.http, &self.site_url) }
+
+  fn api(&self, path: &str) -> String {
+    format!("{}/wp-json/wp/v2/{}", self.site_url.trim().trim_end_matches('/'), path.trim_start_matches('/'))
+  }
+
+  fn auth(&self) -> String {
+    let raw = format!("{}:{}", self.username.trim(), self.app_password.trim());
+    format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(raw))
+  }
+
+  async fn send(&self, rb: reqwest::RequestBuilder) -> Result<Json, String> {
+    let res = rb
+      .header("Authorization", self.auth())
+      .header("Accept", "application/json")
+      .send()
+      .await
+      .map_err(send_error)?;
+    let status = res.status();
+    let text = res.text().await.map_err(|e| format!("read error: {e}"))?;
+    let body: Json = serde_json::from_str(&text).unwrap_or(Json::String(text));
+    if !status.is_success() {
+      // WP 错误体：{"code": "...", "message": "...", "data": {...}}
+      let code = body.get("code").and_then(|c| c.as_str()).unwrap_or("");
+      let msg = body.get("message").and_then(|c| c.as_str()).map(|s| s.to_string()).unwrap_or_else(|| body.to_string());
+      return Err(format!("HTTP {}: {} {}", status.as_u16(), code, msg).trim_end().to_string());
+    }
+    Ok(body)
+  }
+}
+
+fn to_term(v: &Json) -> WpTerm {
+  WpTerm {
+    id: v.get("id").and_then(|x| x.as_i64()).unwrap_or(0),
+    name: v.get("name").and_then(|x| x.as_str()).unwrap_or("").to_string(),
+    slug: v.get("slug").and_then(|x| x.as_str()).unwrap_or("").to_string(),
+    count: v.get("count").and_then(|x| x.as_i64()).unwrap_or(0),
+  }
+}
+
+pub async fn list_terms(cfg: &WordPressConfig, kind: TermKind, search: Option<&str>) -> Result<Vec<WpTerm>, String> {
+  let client = cfg.client()?;
+  let mut rb = client.get(cfg.api(kind.path())).query(&[("per_page", "100"), ("hide_empty", "false")]);
+  if let Some(s) = search.filter(|s| !s.trim().is_empty()) { rb = rb.query(&[("search", s.trim())]); }
+  let v = cfg.send(rb).await?;
+  Ok(v.as_array().map(|a| a.iter().map(to_term).collect()).unwrap_or_default())
+}
+
+pub async fn create_term(cfg: &WordPressConfig, kind: TermKind, name: &str) -> Result<WpTerm, String> {
+  let client = cfg.client()?;
+  let v = cfg.send(client.post(cfg.api(kind.path())).json(&json!({ "name": name }))).await?;
+  Ok(to_term(&v))
+}
+
+// 名称 -> id：先精确查找（忽略大小写），不存在则创建
+pub async fn ensure_terms(cfg: &WordPressConfig, kind: TermKind, names: &[String]) -> Result<Vec<i64>, String> {
+  let mut ids = Vec::new();
+  for name in names {
+    let found = list_terms(cfg, kind, Some(name)).await?
+      .into_iter()
+      .find(|t| t.name.eq_ignore_ascii_case(name) || t.slug.eq_ignore_ascii_case(name));
+    let id = match found {
+      Some(t) => t.id,
+      None => create_term(cfg, kind, name).await?.id,
+    };
+    if id > 0 && !ids.contains(&id) { ids.push(id); }
+  }
+  Ok(ids)
+}
+
+pub async fn upload_media(cfg: &WordPressConfig, name: &str, content_type: &str, bytes: Vec<u8>) -> Result<WpMedia, String> {
+  let client = cfg.client()?;
+  // 文件名放入 Content-Disposition，需转义引号
+  let disposition = format!("attachment; filename=\"{}\"", name.replace('"', "_"));
+  let rb = client
+    .post(cfg.api("media"))
+    .header("Content-Type", content_type)
+    .header("Content-Disposition", disposition)
+    .body(bytes);
+  let v = cfg.send(rb).await?;
+  Ok(WpMedia {
+    id: v.get("id").and_then(|x| x.as_i64()).unwrap_or(0),
+    url: v.get("source_url").and_then(|x| x.as_str()).unwrap_or("").to_string(),
+  })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordPressPublishReq {
+  #[serde(flatten)]
+  pub doc: PublishDoc,
+  // 已发布文章 id：有则更新
+  #[serde(default)]
+  pub post_id: Option<String>,
+  // draft / publish / pending / private；缺省按 front matter 的 draft 决定
+  #[serde(default)]
+  pub status: Option<String>,
+  // 定时发布时间（RFC3339 或 YYYY-MM-DD HH:MM），设置后状态为 future
+  #[serde(default)]
+  pub schedule_at: Option<String>,
+  #[serde(default = "super::default_true")]
+  pub upload_images: bool,
+}
+
+// 转为 WordPress 的 date_gmt（YYYY-MM-DDTHH:MM:SS，UTC）；时区规则见 utc_time
+fn wp_date(s: &str) -> Option<String> {
+  utc_time(s).map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+pub async fn publish(cfg: &WordPressConfig, req: &WordPressPublishReq) -> Result<PublishResult, String> {
+  // 定时时间无法识别时报错，不能退回为立即发布
+  let schedule = match req.schedule_at.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+    Some(s) => Some(wp_date(s).ok_or_else(|| format!("invalid schedule_at: {}", s))?),
+    None => None,
+  };
+  let Prepared { meta, body } = prepare(&req.doc, Some(&cfg.target())).await?;
+  let doc_dir = req.doc.doc_dir();
+  let (body, images_uploaded) = if req.upload_images {
+    upload_local_images(&body, doc_dir.as_deref(), |img| async move {
+      upload_media(cfg, &img.name, &img.content_type, img.bytes).await.map(|m| m.url)
+    }).await?
+  } else {
+    (body, 0)
+  };
+
+  let categories = ensure_terms(cfg, TermKind::Categories, &meta.categories).await?;
+  let tags = ensure_terms(cfg, TermKind::Tags, &meta.tags).await?;
+  let mut post = json!({
+    "title": meta.title,
+    "content": markdown::render_html(&body),
+    "categories": categories,
+    "tags": tags,
+  });
+  let status = match (&schedule, req.status.as_deref()) {
+    (Some(_), _) => "future".to_string(),
+    (None, Some(s)) if !s.trim().is_empty() => s.trim().to_ascii_lowercase(),
+    _ => if meta.draft { "draft".into() } else { "publish".into() },
+  };
+  post["status"] = json!(status);
+  if let Some(d) = schedule.or_else(|| meta.date.as_deref().and_then(wp_date)) { post["date_gmt"] = json!(d); }
+  if let Some(slug) = &meta.slug { post["slug"] = json!(slug); }
+  if let Some(ex) = &meta.excerpt { post["excerpt"] = json!(ex); }
+
+  let client = cfg.client()?;
+  let existing = req.post_id.as_ref().map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+  let created = existing.is_none();
+  let url = match &existing {
+    Some(id) => cfg.api(&format!("posts/{}", id)),
+    None => cfg.api("posts"),
+  };
+  let v = cfg.send(client.post(url).json(&post)).await?;
+  let post_id = v.get("id").and_then(|x| x.as_i64()).map(|i| i.to_string()).or(existing).unwrap_or_default();
+  let link = v.get("link").and_then(|x| x.as_str()).unwrap_or("").to_string();
+  Ok(PublishResult { post_id, url: link, created, images_uploaded, document: None })
+}
+
+#[tauri::command]
+pub async fn wordpress_list_terms(cfg: WordPressConfig, kind: TermKind, search: Option<String>) -> Result<Vec<WpTerm>, String> {
+  list_terms(&cfg, kind, search.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn wordpress_create_term(cfg: WordPressConfig, kind: TermKind, name: String) -> Result<WpTerm, String> {
+  create_term(&cfg, kind, &name).await
+}
+
+#[tauri::command]
+pub async fn wordpress_upload_media(cfg: WordPressConfig, path: String) -> Result<WpMedia, String> {
+  let p = std::path::PathBuf::from(&path);
+  let bytes = super::read_file(p.clone()).await?;
+  let checked = crate::upload_policy::UploadPolicy::default().enforce(bytes, None)?;
+  let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "file".into());
+  upload_media(&cfg, &name, &checked.content_type, checked.bytes).await
+}
+
+#[tauri::command]
+pub async fn wordpress_publish(cfg: WordPressConfig, mut req: WordPressPublishReq) -> Result<PublishResult, String> {
+  let target = cfg.target();
+  req.post_id = super::state::resolve_post_id(&req.doc, &target, req.post_id.take()).await?;
+  let mut res = publish(&cfg, &req).await?;
+  super::state::record(&req.doc, &target, &mut res).await?;
+  Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_http::{serve, Response};
+
+  #[test]
+  fn dates() {
+    assert_eq!(wp_date("2025-03-01T08:30:00+08:00").as_deref(), Some("2025-03-01T00:30:00"));
+    assert_eq!(wp_date("2025-03-01T00:30:00Z").as_deref(), Some("2025-03-01T00:30:00"));
+    assert_eq!(wp_date("2025-01-01T02:00:00+05:00").as_deref(), Some("2024-12-31T21:00:00"));
+    // 不带时区的按本机时区换算
+    let local = utc_time("2025-03-01 08:30").unwrap().format("%Y-%m-%dT%H:%M:%S").to_string();
+    assert_eq!(wp_date("2025-03-01 08:30"), Some(local));
+    assert_eq!(wp_date("next tuesday"), None);
+  }
+
+  #[tokio::test]
+  async fn publish_create_update_and_schedule() {
+    let srv = serve(|req| {
+      let path = req.path.split('?').next().unwrap_or("");
+      match (req.method.as_str(), path) {
+        ("GET", "/wp-json/wp/v2/categories") => Response::new(200, "application/json", "[]"),
+        ("POST", "/wp-json/wp/v2/categories") => Response::new(201, "application/json", r#"{"id":3,"name":"News","slug":"news","count":0}"#),
+        ("GET", "/wp-json/wp/v2/tags") => Response::new(200, "application/json", r#"[{"id":7,"name":"Rust","slug":"rust","count":4}]"#),
+        ("POST", "/wp-json/wp/v2/posts") => Response::new(201, "application/json", r#"{"id":42,"link":"https://wp.example/?p=42"}"#),
+        ("POST", "/wp-json/wp/v2/posts/42") => Response::new(200, "application/json", r#"{"id":42,"link":"https://wp.example/hello/"}"#),
+        _ => Response::new(404, "application/json", r#"{"code":"rest_no_route","message":"No route"}"#),
+      }
+    });
+    let cfg = WordPressConfig { site_url: srv.url("/"), username: "me".into(), app_password: "abcd efgh".into(), http: HttpOptions::default() };
+    let content = "---
+title: Hello
+categories: [News]
+tags: [rust]
+date: 2025-03-01 08:30
+---
+# Hi
+";
+    let req = |content: &str, schedule_at: Option<&str>| WordPressPublishReq {
+      doc: PublishDoc { content: Some(content.into()), ..Default::default() },
+      post_id: None,
+      status: None,
+      schedule_at: schedule_at.map(str::to_string),
+      upload_images: false,
+    };
+
+    let res = wordpress_publish(cfg.clone(), req(content, None)).await.unwrap();
+    assert_eq!((res.post_id.as_str(), res.url.as_str(), res.created), ("42", "https://wp.example/?p=42", true));
+    let reqs = srv.requests();
+    let auth = format!("Basic {}", base64::engine::general_purpose::STANDARD.encode("me:abcd efgh"));
+    assert!(reqs.iter().all(|r| r.header("authorization") == Some(auth.as_str())));
+    let create = reqs.iter().find(|r| r.path == "/wp-json/wp/v2/posts").unwrap();
+    let post: Json = serde_json::from_slice(&create.body).unwrap();
+    assert_eq!((post["status"].as_str(), post["date_gmt"].as_str()), (Some("publish"), wp_date("2025-03-01 08:30").as_deref()));
+    assert_eq!((post["categories"].clone(), post["tags"].clone()), (json!([3]), json!([7])));
+    assert!(post["content"].as_str().unwrap().contains("<h1>Hi</h1>"));
+    let document = res.document.unwrap();
+    assert_eq!(super::super::state::find_entry(&document, &cfg.target()).unwrap().id, "42");
+
+    // 再次发布：使用记录的 id 更新，定时时间带时区时写入 date_gmt 并设为 future
+    let n = srv.requests().len();
+    let res = wordpress_publish(cfg.clone(), req(&document, Some("2099-01-01T08:00:00+08:00"))).await.unwrap();
+    assert_eq!((res.post_id.as_str(), res.created), ("42", false));
+    let reqs = srv.requests();
+    let update = reqs[n..].iter().find(|r| r.method == "POST" && r.path == "/wp-json/wp/v2/posts/42").unwrap();
+    let post: Json = serde_json::from_slice(&update.body).unwrap();
+    assert_eq!((post["status"].as_str(), post["date_gmt"].as_str()), (Some("future"), Some("2099-01-01T00:00:00")));
+
+    // 无法识别的定时时间：报错且不发出任何请求
+    let n = srv.requests().len();
+    let err = wordpress_publish(cfg.clone(), req(content, Some("next tuesday"))).await.unwrap_err();
+    assert_eq!(err, "invalid schedule_at: next tuesday");
+    assert_eq!(srv.requests().len(), n);
+  }
+}
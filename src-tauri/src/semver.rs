@@ -0,0 +1,334 @@
+// SemVer 2.0 版本比较（更新检查用）
+// 在标准规则之外保留 flymd 的 -fix 约定：`X.Y.Z-fix[.N]` 是 X.Y.Z 发布后的修复版，
+// 排在 X.Y.Z 之后、下一个版本（含其预发行）之前；多个 fix 之间按其后的标识符比较（fix < fix.1 < fix.2 < fix.10）。
+// 构建元数据（+ 之后）不参与比较；允许前缀 v 与省略的 minor/patch（1.2 视为 1.2.0）。
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Ident {
+  Num(String),
+  Alpha(String),
+}
+
+impl Ident {
+  fn parse(s: &str) -> Option<Ident> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') { return None; }
+    if s.chars().all(|c| c.is_ascii_digit()) {
+      // 数字标识符去掉前导零后按长度再按字面比较，避免溢出
+      let t = s.trim_start_matches('0');
+      Some(Ident::Num(if t.is_empty() { "0".into() } else { t.to_string() }))
+    } else {
+      Some(Ident::Alpha(s.to_string()))
+    }
+  }
+}
+
+impl Ord for Ident {
+  fn cmp(&self, other: &Self) -> Ordering {
+    match (self, other) {
+      (Ident::Num(a), Ident::Num(b)) => a.len().cmp(&b.len()).then_with(|| a.cmp(b)),
+      (Ident::Num(_), Ident::Alpha(_)) => Ordering::Less,
+      (Ident::Alpha(_), Ident::Num(_)) => Ordering::Greater,
+      (Ident::Alpha(a), Ident::Alpha(b)) => a.cmp(b),
+    }
+  }
+}
+
+impl PartialOrd for Ident {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+// 同一 major.minor.patch 下的先后：预发行 < 正式版 < fix 修复版
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Stage {
+  Pre(Vec<Ident>),
+  Release,
+  Fix(Vec<Ident>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+  major: u64,
+  minor: u64,
+  patch: u64,
+  stage: Stage,
+}
+
+fn parse_idents(s: &str) -> Option<Vec<Ident>> {
+  s.split('.').map(Ident::parse).collect()
+}
+
+impl Version {
+  pub fn parse(v: &str) -> Option<Version> {
+    let s = v.trim();
+    let s = s.strip_prefix(['v', 'V']).unwrap_or(s);
+    let s = s.split_once('+').map(|(a, _)| a).unwrap_or(s);
+    let (core, pre) = match s.split_once('-') {
+      Some((c, p)) => (c, Some(p)),
+      None => (s, None),
+    };
+    let nums: Vec<&str> = core.split('.').collect();
+    if nums.is_empty() || nums.len() > 3 { return None; }
+    let mut parsed = [0u64; 3];
+    for (i, n) in nums.iter().enumerate() {
+      if n.is_empty() || !n.chars().all(|c| c.is_ascii_digit()) { return None; }
+      parsed[i] = n.parse().ok()?;
+    }
+    let stage = match pre {
+      None => Stage::Release,
+      Some(p) => {
+        let lower = p.to_ascii_lowercase();
+        match lower.strip_prefix("fix") {
+          // fix / fix.2 / fix2
+          Some("") => Stage::Fix(Vec::new()),
+          Some(rest) if rest.starts_with('.') => Stage::Fix(parse_idents(&rest[1..])?),
+          Some(rest) if rest.chars().all(|c| c.is_ascii_digit()) => Stage::Fix(vec![Ident::parse(rest)?]),
+          _ => Stage::Pre(parse_idents(p)?),
+        }
+      }
+    };
+    Some(Version { major: parsed[0], minor: parsed[1], patch: parsed[2], stage })
+  }
+
+  pub fn is_prerelease(&self) -> bool {
+    matches!(self.stage, Stage::Pre(_))
+  }
+
+  // 同一 major.minor 下的版本（补丁或 fix 修复版）
+  fn same_minor(&self, other: &Version) -> bool {
+    self.major == other.major && self.minor == other.minor
+  }
+}
+
+// latest 是否比 current 新；任一方无法解析时不提示更新
+pub fn is_newer(latest: &str, current: &str) -> bool {
+  match (Version::parse(latest), Version::parse(current)) {
+    (Some(l), Some(c)) => l > c,
+    _ => false,
+  }
+}
+
+// 更新通道
+//   - stable：正式版与 -fix 修复版
+//   - security：仅当前 minor 内的补丁与 -fix 修复版（不跨 minor/major）
+//   - beta：额外包含预发行版
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+  #[default]
+  Stable,
+  Security,
+  Beta,
+}
+
+// 候选版本的筛选规则：通道、锁定的最高版本与跳过的版本
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+  pub channel: Channel,
+  // 锁定后不提示高于该版本的更新
+  pub pinned: Option<Version>,
+  // 用户选择跳过的版本（仅影响提示哪个版本，更新说明仍会包含）
+  pub skipped: Vec<Version>,
+}
+
+impl Policy {
+  fn accepts(&self, c: &Candidate, v: &Version, current: Option<&Version>) -> bool {
+    let channel_ok = match self.channel {
+      Channel::Beta => true,
+      Channel::Stable => !c.prerelease && !v.is_prerelease(),
+      Channel::Security => !c.prerelease && !v.is_prerelease() && current.map(|cur| v.same_minor(cur)).unwrap_or(false),
+    };
+    channel_ok && self.pinned.as_ref().map(|p| v <= p).unwrap_or(true)
+  }
+}
+
+// 候选发行版的概要（GitHub Release 的 tag 与状态）
+pub struct Candidate<'a> {
+  pub tag: &'a str,
+  pub draft: bool,
+  pub prerelease: bool,
+}
+
+// 在可用的发行版中选出版本号最高者（不依赖接口返回顺序）；草稿、无法解析的 tag 与跳过的版本不参与，
+// 其余按通道（stable 跳过 GitHub 标记的预发行与 SemVer 预发行版本）与锁定版本筛选
+pub fn pick_latest(candidates: &[Candidate], current: &str, policy: &Policy) -> Option<usize> {
+  let cur = Version::parse(current);
+  candidates.iter().enumerate()
+    .filter(|(_, c)| !c.draft)
+    .filter_map(|(i, c)| Version::parse(c.tag).map(|v| (i, c, v)))
+    .filter(|(_, c, v)| policy.accepts(c, v, cur.as_ref()) && !policy.skipped.contains(v))
+    .max_by(|a, b| a.2.cmp(&b.2))
+    .map(|(i, _, _)| i)
+}
+
+// 比 current 新的所有可用发行版（按通道与锁定版本筛选，跳过的版本仍保留），按版本从新到旧排列
+pub fn newer_than(candidates: &[Candidate], current: &str, policy: &Policy) -> Vec<usize> {
+  let Some(cur) = Version::parse(current) else { return Vec::new() };
+  let mut found: Vec<(usize, Version)> = candidates.iter().enumerate()
+    .filter(|(_, c)| !c.draft)
+    .filter_map(|(i, c)| Version::parse(c.tag).map(|v| (i, c, v)))
+    .filter(|(_, c, v)| policy.accepts(c, v, Some(&cur)))
+    .filter(|(_, _, v)| *v > cur)
+    .map(|(i, _, v)| (i, v))
+    .collect();
+  found.sort_by(|a, b| b.1.cmp(&a.1));
+  // 同一版本号出现多次（如 v1.0.0 与 1.0.0+build）时只保留第一个
+  found.dedup_by(|a, b| a.1 == b.1);
+  found.into_iter().map(|(i, _)| i).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn ordering() {
+    // (a, b, a 与 b 的关系)
+    let cases: &[(&str, &str, Ordering)] = &[
+      ("1.0.0", "1.0.0", Ordering::Equal),
+      ("v1.0.0", "1.0.0", Ordering::Equal),
+      ("1.0.0+build.5", "1.0.0+build.9", Ordering::Equal),
+      ("1.2", "1.2.0", Ordering::Equal),
+      ("1.0.1", "1.0.0", Ordering::Greater),
+      ("1.10.0", "1.9.0", Ordering::Greater),
+      ("2.0.0", "1.99.99", Ordering::Greater),
+      ("0.1.2-beta.10", "0.1.2-beta.2", Ordering::Greater),
+      ("0.1.2-rc.1", "0.1.2-alpha.1", Ordering::Greater),
+      ("1.0.0-alpha", "1.0.0-alpha.1", Ordering::Less),
+      ("1.0.0-alpha.1", "1.0.0-alpha.beta", Ordering::Less),
+      ("1.0.0-alpha.beta", "1.0.0-beta", Ordering::Less),
+      ("1.0.0-beta.11", "1.0.0-rc.1", Ordering::Less),
+      ("1.0.0-rc.1", "1.0.0", Ordering::Less),
+      ("1.0.0-rc.1+build.1", "1.0.0-rc.1", Ordering::Equal),
+      ("0.1.2-fix", "0.1.2", Ordering::Greater),
+      ("0.1.2-fix", "0.1.2-rc.9", Ordering::Greater),
+      ("0.1.2-fix.2", "0.1.2-fix", Ordering::Greater),
+      ("0.1.2-fix.10", "0.1.2-fix.2", Ordering::Greater),
+      ("0.1.2-fix2", "0.1.2-fix.2", Ordering::Equal),
+      ("0.1.3-alpha", "0.1.2-fix.5", Ordering::Greater),
+      ("0.1.3", "0.1.2-fix", Ordering::Greater),
+      ("1.0.0-99999999999999999999999", "1.0.0-99999999999999999999998", Ordering::Greater),
+      ("1.0.0-007", "1.0.0-7", Ordering::Equal),
+    ];
+    for (a, b, want) in cases {
+      let (va, vb) = (Version::parse(a).unwrap(), Version::parse(b).unwrap());
+      assert_eq!(va.cmp(&vb), *want, "{} vs {}", a, b);
+    }
+  }
+
+  #[test]
+  fn invalid() {
+    for v in ["", "v", "1.2.3.4", "1..2", "a.b.c", "1.2.3-", "1.2.3-beta..1", "1.2.3-beta_1"] {
+      assert!(Version::parse(v).is_none(), "{:?}", v);
+    }
+  }
+
+  fn chan(channel: Channel) -> Policy {
+    Policy { channel, ..Default::default() }
+  }
+
+  fn cand(tag: &str, draft: bool, prerelease: bool) -> Candidate<'_> {
+    Candidate { tag, draft, prerelease }
+  }
+
+  #[test]
+  fn update_decision() {
+    use Channel::{Beta, Stable};
+    // (当前版本, 发行列表 (tag, draft, prerelease), 通道, 期望提示的版本)
+    type Case<'a> = (&'a str, &'a [(&'a str, bool, bool)], Channel, Option<&'a str>);
+    let cases: &[Case] = &[
+      ("0.1.2", &[("v0.1.3", false, false)], Stable, Some("v0.1.3")),
+      ("0.1.2", &[("v0.1.2", false, false)], Stable, None),
+      ("0.1.2", &[("v0.1.1", false, false)], Stable, None),
+      ("0.1.2", &[("v0.1.2-fix", false, false)], Stable, Some("v0.1.2-fix")),
+      ("0.1.2-fix", &[("v0.1.2", false, false)], Stable, None),
+      ("0.1.2-fix", &[("v0.1.2-fix.1", false, false)], Stable, Some("v0.1.2-fix.1")),
+      // 草稿忽略
+      ("0.1.2", &[("v0.2.0", true, false), ("v0.1.3", false, false)], Stable, Some("v0.1.3")),
+      // 预发行默认忽略（GitHub 标记或版本号带预发行后缀）
+      ("0.1.2", &[("v0.2.0-beta.1", false, true), ("v0.1.3", false, false)], Stable, Some("v0.1.3")),
+      ("0.1.2", &[("v0.2.0-beta.1", false, false)], Stable, None),
+      ("0.1.2", &[("v0.2.0-beta.1", false, true), ("v0.1.3", false, false)], Beta, Some("v0.2.0-beta.1")),
+      // 当前为预发行：正式版更新
+      ("0.2.0-beta.2", &[("v0.2.0", false, false)], Stable, Some("v0.2.0")),
+      ("0.2.0-beta.2", &[("v0.2.0-beta.10", false, true)], Beta, Some("v0.2.0-beta.10")),
+      ("0.2.0-beta.10", &[("v0.2.0-beta.2", false, true)], Beta, None),
+      // 不依赖返回顺序：取最高版本
+      ("0.1.0", &[("v0.1.1", false, false), ("v0.1.3", false, false), ("v0.1.2", false, false)], Stable, Some("v0.1.3")),
+      // 构建元数据不影响判断
+      ("0.1.2", &[("v0.1.2+linux", false, false)], Stable, None),
+      // 无法解析的 tag 跳过
+      ("0.1.2", &[("nightly", false, false), ("v0.1.3", false, false)], Stable, Some("v0.1.3")),
+      ("0.1.2", &[("nightly", false, false)], Stable, None),
+      ("0.1.2", &[], Stable, None),
+    ];
+    for (current, releases, channel, want) in cases {
+      let cands: Vec<Candidate> = releases.iter().map(|(t, d, p)| cand(t, *d, *p)).collect();
+      let got = pick_latest(&cands, current, &chan(*channel))
+        .map(|i| cands[i].tag)
+        .filter(|tag| is_newer(tag, current));
+      assert_eq!(got, *want, "current {} releases {:?} channel {:?}", current, releases, channel);
+    }
+  }
+
+  #[test]
+  fn notes_range() {
+    use Channel::{Beta, Stable};
+    // (当前版本, 发行列表 (tag, draft, prerelease), 通道, 期望的 tag，从新到旧)
+    type Case<'a> = (&'a str, &'a [(&'a str, bool, bool)], Channel, &'a [&'a str]);
+    let cases: &[Case] = &[
+      ("0.1.0", &[("v0.1.1", false, false), ("v0.1.3", false, false), ("v0.1.2", false, false), ("v0.1.0", false, false)], Stable, &["v0.1.3", "v0.1.2", "v0.1.1"]),
+      ("0.1.2", &[("v0.1.2-fix", false, false), ("v0.1.1", false, false)], Stable, &["v0.1.2-fix"]),
+      ("0.1.0", &[("v0.2.0-beta.1", false, true), ("v0.1.1", true, false), ("v0.1.2", false, false)], Stable, &["v0.1.2"]),
+      ("0.1.0", &[("v0.2.0-beta.1", false, true), ("v0.1.2", false, false)], Beta, &["v0.2.0-beta.1", "v0.1.2"]),
+      ("0.1.0", &[("v0.1.1", false, false), ("0.1.1+linux", false, false), ("nightly", false, false)], Stable, &["v0.1.1"]),
+      ("0.2.0", &[("v0.1.9", false, false)], Stable, &[]),
+      ("dev", &[("v0.1.9", false, false)], Stable, &[]),
+    ];
+    for (current, releases, channel, want) in cases {
+      let cands: Vec<Candidate> = releases.iter().map(|(t, d, p)| cand(t, *d, *p)).collect();
+      let got: Vec<&str> = newer_than(&cands, current, &chan(*channel)).into_iter().map(|i| cands[i].tag).collect();
+      assert_eq!(got, *want, "current {} releases {:?} channel {:?}", current, releases, channel);
+    }
+  }
+
+  #[test]
+  fn channel_policy() {
+    let releases = [
+      ("v0.1.3", false, false),
+      ("v0.1.2-fix.1", false, false),
+      ("v0.2.0", false, false),
+      ("v0.2.1", false, false),
+      ("v1.0.0-rc.1", false, true),
+    ];
+    let cands: Vec<Candidate> = releases.iter().map(|(t, d, p)| cand(t, *d, *p)).collect();
+    let v = |s: &str| Version::parse(s).unwrap();
+    // (当前版本, 规则, 期望提示的版本)
+    let cases: Vec<(&str, Policy, Option<&str>)> = vec![
+      ("0.1.2", chan(Channel::Stable), Some("v0.2.1")),
+      ("0.1.2", chan(Channel::Beta), Some("v1.0.0-rc.1")),
+      // security：只在当前 minor 内更新
+      ("0.1.2", chan(Channel::Security), Some("v0.1.3")),
+      ("0.1.3", chan(Channel::Security), None),
+      ("0.2.0", chan(Channel::Security), Some("v0.2.1")),
+      // 锁定版本：不超过锁定的版本
+      ("0.1.2", Policy { pinned: Some(v("0.2.0")), ..Default::default() }, Some("v0.2.0")),
+      ("0.1.2", Policy { channel: Channel::Beta, pinned: Some(v("0.1")), ..Default::default() }, None),
+      // 跳过的版本不提示，但更高的版本照常提示
+      ("0.1.2", Policy { skipped: vec![v("0.2.1")], ..Default::default() }, Some("v0.2.0")),
+      ("0.1.2", Policy { skipped: vec![v("v0.2.1"), v("0.2.0"), v("0.1.3")], ..Default::default() }, Some("v0.1.2-fix.1")),
+    ];
+    for (current, policy, want) in cases {
+      let got = pick_latest(&cands, current, &policy)
+        .map(|i| cands[i].tag)
+        .filter(|tag| is_newer(tag, current));
+      assert_eq!(got, want, "current {} policy {:?}", current, policy);
+    }
+    // 跳过的版本仍出现在更新说明中
+    let skip = Policy { skipped: vec![v("0.2.1")], ..Default::default() };
+    let notes: Vec<&str> = newer_than(&cands, "0.2.0", &skip).into_iter().map(|i| cands[i].tag).collect();
+    assert_eq!(notes, ["v0.2.1"]);
+  }
+}
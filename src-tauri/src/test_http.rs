@@ -0,0 +1,108 @@
+// 单元测试用的本地 HTTP 替身：在 127.0.0.1 随机端口上按 handler 应答，并记录收到的请求
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone)]
+pub struct Request {
+  pub method: String,
+  pub path: String,
+  pub headers: Vec<(String, String)>,
+  pub body: Vec<u8>,
+}
+
+impl Request {
+  pub fn header(&self, name: &str) -> Option<&str> {
+    self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+  }
+
+  pub fn text(&self) -> String {
+    String::from_utf8_lossy(&self.body).to_string()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+  pub status: u16,
+  pub headers: Vec<(String, String)>,
+  pub body: Vec<u8>,
+}
+
+impl Response {
+  pub fn new(status: u16, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+    Response { status, headers: vec![("Content-Type".into(), content_type.into())], body: body.into() }
+  }
+
+  pub fn header(mut self, name: &str, value: &str) -> Self {
+    self.headers.push((name.into(), value.into()));
+    self
+  }
+}
+
+pub struct Server {
+  pub base: String,
+  pub requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl Server {
+  pub fn url(&self, path: &str) -> String {
+    format!("{}{}", self.base, path)
+  }
+
+  pub fn requests(&self) -> Vec<Request> {
+    self.requests.lock().unwrap().clone()
+  }
+}
+
+// 每个连接只处理一个请求（应答带 Connection: close）；线程随测试进程结束
+pub fn serve<F>(handler: F) -> Server
+where
+  F: Fn(&Request) -> Response + Send + Sync + 'static,
+{
+  let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+  let base = format!("http://{}", listener.local_addr().unwrap());
+  let requests = Arc::new(Mutex::new(Vec::new()));
+  let log = requests.clone();
+  let handler = Arc::new(handler);
+  std::thread::spawn(move || {
+    for stream in listener.incoming() {
+      let Ok(stream) = stream else { continue };
+      let (log, handler) = (log.clone(), handler.clone());
+      std::thread::spawn(move || {
+        let Some(req) = read_request(&stream) else { return };
+        log.lock().unwrap().push(req.clone());
+        let resp = handler(&req);
+        let mut out = format!("HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n", resp.status, resp.body.len());
+        for (k, v) in &resp.headers { out.push_str(&format!("{}: {}\r\n", k, v)); }
+        out.push_str("\r\n");
+        let mut stream = stream;
+        let _ = stream.write_all(out.as_bytes());
+        if req.method != "HEAD" { let _ = stream.write_all(&resp.body); }
+        let _ = stream.flush();
+      });
+    }
+  });
+  Server { base, requests }
+}
+
+fn read_request(stream: &std::net::TcpStream) -> Option<Request> {
+  let mut reader = BufReader::new(stream);
+  let mut line = String::new();
+  reader.read_line(&mut line).ok()?;
+  let mut parts = line.split_whitespace();
+  let method = parts.next()?.to_string();
+  let path = parts.next()?.to_string();
+  let mut headers = Vec::new();
+  loop {
+    let mut h = String::new();
+    if reader.read_line(&mut h).ok()? == 0 { break; }
+    let h = h.trim_end();
+    if h.is_empty() { break; }
+    if let Some((k, v)) = h.split_once(':') { headers.push((k.trim().to_string(), v.trim().to_string())); }
+  }
+  let len = headers.iter().find(|(k, _)| k.eq_ignore_ascii_case("content-length")).and_then(|(_, v)| v.parse::<usize>().ok()).unwrap_or(0);
+  let mut body = vec![0u8; len];
+  reader.read_exact(&mut body).ok()?;
+  Some(Request { method, path, headers, body })
+}
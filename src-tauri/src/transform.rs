@@ -0,0 +1,466 @@
+// 发布/导出前的正文转换流水线：剔除私有段落与 %%注释%%、解析 [[wiki 链接]]、转换 callout、生成目录
+// 代码块（围栏与行内）中的内容保持原样
+
+use serde::Deserialize;
+
+use crate::export::escape_html;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformOptions {
+  // <!-- private --> ... <!-- /private --> 之间的内容
+  #[serde(default = "crate::publish::default_true")]
+  pub strip_private: bool,
+  // Obsidian 风格 %%注释%%（可跨行）
+  #[serde(default = "crate::publish::default_true")]
+  pub strip_comments: bool,
+  // [[笔记]] / [[笔记#标题|别名]] / ![[图片.png]]
+  #[serde(default = "crate::publish::default_true")]
+  pub wiki_links: bool,
+  // 未能解析到已发布链接时使用的地址模板，支持 {slug} 与 {name}；缺省时退化为纯文本
+  #[serde(default)]
+  pub wiki_url_template: Option<String>,
+  // > [!NOTE] 标题 形式的 callout 转为 HTML
+  #[serde(default)]
+  pub callouts: bool,
+  // 在 [TOC] / <!-- toc --> 处（无标记时在开头）插入目录
+  #[serde(default)]
+  pub toc: bool,
+  // 目录收录的最大标题级别（2..=6），缺省 3
+  #[serde(default)]
+  pub toc_max_level: Option<u8>,
+}
+
+impl Default for TransformOptions {
+  fn default() -> Self {
+    TransformOptions {
+      strip_private: true,
+      strip_comments: true,
+      wiki_links: true,
+      wiki_url_template: None,
+      callouts: false,
+      toc: false,
+      toc_max_level: None,
+    }
+  }
+}
+
+// 按顺序执行各步骤；resolve 将 wiki 链接的笔记名解析为地址
+pub fn apply(body: &str, opts: &TransformOptions, resolve: &dyn Fn(&str) -> Option<String>) -> String {
+  let mut s = body.to_string();
+  if opts.strip_private { s = strip_private(&s); }
+  if opts.strip_comments { s = strip_comments(&s); }
+  if opts.wiki_links {
+    s = wiki_links(&s, &|name: &str| resolve(name).or_else(|| url_from_template(opts.wiki_url_template.as_deref()?, name)));
+  }
+  if opts.callouts { s = callouts(&s); }
+  if opts.toc { s = toc(&s, opts.toc_max_level.unwrap_or(3).clamp(2, 6)); }
+  s
+}
+
+// RFC 3986 的非保留字符不编码，slug 中的 - 保持原样
+const URL_COMPONENT: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+fn url_from_template(tpl: &str, name: &str) -> Option<String> {
+  if tpl.trim().is_empty() { return None; }
+  let slug = match crate::publish::slugify(name) { s if s.is_empty() => name.to_string(), s => s };
+  let enc = |s: &str| percent_encoding::utf8_percent_encode(s, URL_COMPONENT).to_string();
+  Some(tpl.replace("{slug}", &enc(&slug)).replace("{name}", &enc(name)))
+}
+
+// 围栏代码块跟踪：返回每行是否属于代码（含围栏行本身）
+pub fn code_lines(text: &str) -> Vec<(&str, bool)> {
+  let mut out = Vec::new();
+  let mut fence: Option<(char, usize)> = None;
+  for line in text.split_inclusive('\n') {
+    let t = line.trim_start();
+    let indent = line.len() - t.len();
+    let run = |c: char| t.chars().take_while(|x| *x == c).count();
+    match fence {
+      Some((c, n)) => {
+        if indent < 4 && run(c) >= n && t.trim_start_matches(c).trim().is_empty() { fence = None; }
+        out.push((line, true));
+      }
+      None => {
+        let opener = ['`', '~'].into_iter().find(|c| indent < 4 && run(*c) >= 3);
+        if let Some(c) = opener { fence = Some((c, run(c))); }
+        out.push((line, opener.is_some()));
+      }
+    }
+  }
+  out
+}
+
+// 行内代码 `...` 的长度（从 s 开头的反引号串起算），未闭合返回 None
+fn inline_code_len(s: &str) -> Option<usize> {
+  let n = s.chars().take_while(|c| *c == '`').count();
+  let ticks = &s[..n];
+  let rest = &s[n..];
+  let mut from = 0;
+  while let Some(i) = rest[from..].find(ticks) {
+    let at = from + i;
+    let m = rest[at..].chars().take_while(|c| *c == '`').count();
+    if m == n { return Some(n + at + n); }
+    from = at + m;
+  }
+  None
+}
+
+// HTML 注释标记：<!-- private -->（start=true）或 <!-- /private -->、<!-- endprivate -->（start=false）
+fn find_private_marker(s: &str, start: bool) -> Option<(usize, usize)> {
+  let lower = s.to_ascii_lowercase();
+  let mut from = 0;
+  while let Some(i) = lower[from..].find("<!--") {
+    let at = from + i;
+    let end = lower[at..].find("-->").map(|e| at + e + 3)?;
+    // <!--> 与 <!---> 的结束符与开头重叠，内容为空
+    let inner: String = lower.get(at + 4..end - 3).unwrap_or("").chars().filter(|c| !c.is_whitespace()).collect();
+    let hit = if start { inner == "private" } else { matches!(inner.as_str(), "/private" | "endprivate") };
+    if hit { return Some((at, end)); }
+    from = end;
+  }
+  None
+}
+
+pub fn strip_private(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  let mut private = false;
+  for (line, code) in code_lines(text) {
+    if code {
+      if !private { out.push_str(line); }
+      continue;
+    }
+    let mut rest = line;
+    loop {
+      if !private {
+        match find_private_marker(rest, true) {
+          Some((a, b)) => { out.push_str(&rest[..a]); private = true; rest = &rest[b..]; }
+          None => { out.push_str(rest); break; }
+        }
+      } else {
+        match find_private_marker(rest, false) {
+          Some((_, b)) => {
+            private = false;
+            rest = &rest[b..];
+            // 避免两侧空白叠加
+            if out.ends_with([' ', '\t']) { rest = rest.trim_start_matches([' ', '\t']); }
+          }
+          None => break,
+        }
+      }
+    }
+  }
+  out
+}
+
+pub fn strip_comments(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  let mut in_comment = false;
+  for (line, code) in code_lines(text) {
+    if code {
+      if !in_comment { out.push_str(line); }
+      continue;
+    }
+    let mut i = 0;
+    let bytes = line.as_bytes();
+    // 注释占满整行时连同换行一起去掉
+    let whole_line = in_comment || line.trim_start().starts_with("%%");
+    let mut kept = String::new();
+    while i < line.len() {
+      if in_comment {
+        match line[i..].find("%%") {
+          Some(j) => { in_comment = false; i += j + 2; }
+          None => i = line.len(),
+        }
+      } else if bytes[i] == b'`' {
+        let n = inline_code_len(&line[i..]).unwrap_or(1);
+        kept.push_str(&line[i..i + n]);
+        i += n;
+      } else if line[i..].starts_with("%%") {
+        in_comment = true;
+        i += 2;
+      } else {
+        let c = line[i..].chars().next().unwrap_or(' ');
+        kept.push(c);
+        i += c.len_utf8();
+      }
+    }
+    if whole_line && kept.trim().is_empty() { continue; }
+    if in_comment && !kept.ends_with('\n') && line.ends_with('\n') { kept.push('\n'); }
+    out.push_str(&kept);
+  }
+  out
+}
+
+// 标题锚点：小写，保留字母数字（含中文）、- 与 _，空白转为 -
+pub fn heading_anchor(text: &str) -> String {
+  let mut s = String::new();
+  for c in text.trim().chars() {
+    if c.is_alphanumeric() || c == '-' || c == '_' { s.extend(c.to_lowercase()); }
+    else if c.is_whitespace() && !s.ends_with('-') { s.push('-'); }
+  }
+  s.trim_matches('-').to_string()
+}
+
+const IMAGE_EXTS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "avif"];
+
+fn wiki_replacement(inner: &str, embed: bool, resolve: &dyn Fn(&str) -> Option<String>) -> String {
+  let (target, alias) = match inner.split_once('|') {
+    Some((t, a)) => (t.trim(), Some(a.trim())),
+    None => (inner.trim(), None),
+  };
+  let (name, heading) = match target.split_once('#') {
+    Some((n, h)) => (n.trim(), Some(h.trim())),
+    None => (target, None),
+  };
+  let ext = name.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+  if embed && IMAGE_EXTS.contains(&ext.as_str()) {
+    return format!("![{}](<{}>)", alias.unwrap_or(""), name);
+  }
+  let label = alias.map(|a| a.to_string()).unwrap_or_else(|| match (name.is_empty(), heading) {
+    (false, Some(h)) => format!("{} > {}", name, h),
+    (true, Some(h)) => h.to_string(),
+    _ => name.to_string(),
+  });
+  let anchor = heading.map(|h| format!("#{}", heading_anchor(h))).unwrap_or_default();
+  // 同页标题链接
+  if name.is_empty() { return format!("[{}]({})", label, anchor); }
+  match resolve(name) {
+    Some(url) => format!("[{}](<{}{}>)", label, url, anchor),
+    None => label,
+  }
+}
+
+pub fn wiki_links(text: &str, resolve: &dyn Fn(&str) -> Option<String>) -> String {
+  let mut out = String::with_capacity(text.len());
+  for (line, code) in code_lines(text) {
+    if code { out.push_str(line); continue; }
+    let mut i = 0;
+    while i < line.len() {
+      let rest = &line[i..];
+      if rest.starts_with('`') {
+        let n = inline_code_len(rest).unwrap_or(1);
+        out.push_str(&rest[..n]);
+        i += n;
+        continue;
+      }
+      let embed = rest.starts_with("![[");
+      if embed || rest.starts_with("[[") {
+        let open = if embed { 3 } else { 2 };
+        if let Some(close) = rest[open..].find("]]").filter(|c| !rest[open..open + c].contains('\n')) {
+          out.push_str(&wiki_replacement(&rest[open..open + close], embed, resolve));
+          i += open + close + 2;
+          continue;
+        }
+      }
+      let c = rest.chars().next().unwrap_or(' ');
+      out.push(c);
+      i += c.len_utf8();
+    }
+  }
+  out
+}
+
+// > [!TYPE][+-] 标题 —— 其后连续的 > 行为内容
+fn callout_head(line: &str) -> Option<(String, String)> {
+  let t = line.trim_start();
+  if line.len() - t.len() > 3 { return None; }
+  let t = t.strip_prefix('>')?.trim_start().strip_prefix("[!")?;
+  let end = t.find(']')?;
+  let kind = t[..end].trim().to_ascii_lowercase();
+  if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') { return None; }
+  let title = t[end + 1..].trim_start_matches(['+', '-']).trim().to_string();
+  Some((kind, title))
+}
+
+pub fn callouts(text: &str) -> String {
+  let lines = code_lines(text);
+  let mut out = String::with_capacity(text.len());
+  let mut i = 0;
+  while i < lines.len() {
+    let (line, code) = lines[i];
+    // callout 标记只在引用块的第一行生效
+    let first = i == 0 || lines[i - 1].1 || !lines[i - 1].0.trim_start().starts_with('>');
+    let Some((kind, title)) = (!code && first).then(|| callout_head(line)).flatten() else {
+      out.push_str(line);
+      i += 1;
+      continue;
+    };
+    let title = if title.is_empty() {
+      let mut c = kind.chars();
+      c.next().map(|f| f.to_uppercase().collect::<String>() + c.as_str()).unwrap_or_default()
+    } else { title };
+    let mut content = String::new();
+    i += 1;
+    while i < lines.len() && lines[i].0.trim_start().starts_with('>') {
+      let l = lines[i].0.trim_start().strip_prefix('>').unwrap_or("");
+      content.push_str(l.strip_prefix(' ').unwrap_or(l));
+      i += 1;
+    }
+    if !content.ends_with('\n') { content.push('\n'); }
+    // 空行包围内容，使 div 内的 Markdown 仍被解析
+    out.push_str(&format!(
+      "<div class=\"callout callout-{}\">\n<p class=\"callout-title\">{}</p>\n\n{}\n</div>\n",
+      kind, escape_html(&title), content
+    ));
+  }
+  out
+}
+
+pub fn toc(text: &str, max_level: u8) -> String {
+  use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+  let mut heads: Vec<(usize, u8, String, Option<String>)> = Vec::new();
+  let mut cur: Option<(usize, u8, String, Option<String>)> = None;
+  for (ev, range) in Parser::new_ext(text, crate::markdown::parser_options()).into_offset_iter() {
+    match ev {
+      Event::Start(Tag::Heading { level, id, .. }) => {
+        let lv = match level { HeadingLevel::H1 => 1, HeadingLevel::H2 => 2, HeadingLevel::H3 => 3, HeadingLevel::H4 => 4, HeadingLevel::H5 => 5, HeadingLevel::H6 => 6 };
+        cur = Some((range.start, lv, String::new(), id.map(|s| s.to_string())));
+      }
+      Event::Text(t) | Event::Code(t) => if let Some(c) = cur.as_mut() { c.2.push_str(&t) },
+      Event::End(TagEnd::Heading(_)) => if let Some(c) = cur.take() { heads.push(c) },
+      _ => {}
+    }
+  }
+  // 一级标题通常是文章标题，目录从二级开始
+  let heads: Vec<_> = heads.into_iter().filter(|h| h.1 >= 2 && h.1 <= max_level && !h.2.trim().is_empty()).collect();
+  if heads.is_empty() { return text.to_string(); }
+
+  // 显式 {#id} 先占位，自动生成的锚点避开它们
+  let mut used: Vec<String> = heads.iter().filter_map(|h| h.3.clone()).collect();
+  let mut list = String::new();
+  let mut edits = Vec::new();
+  for (start, lv, title, id) in &heads {
+    let anchor = match id {
+      Some(id) => id.clone(),
+      None => {
+        let base = match heading_anchor(title) { a if a.is_empty() => "section".to_string(), a => a };
+        let mut a = base.clone();
+        let mut n = 1;
+        while used.contains(&a) { a = format!("{}-{}", base, n); n += 1; }
+        // ATX 标题在 # 之后插入锚点；Setext 标题在行首插入
+        let line = &text[*start..];
+        let hashes = line.chars().take_while(|c| *c == '#').count();
+        let at = if hashes > 0 { start + hashes + (line[hashes..].len() - line[hashes..].trim_start_matches([' ', '\t']).len()) } else { *start };
+        edits.push((at..at, format!("<a id=\"{}\"></a>", a)));
+        a
+      }
+    };
+    if id.is_none() { used.push(anchor.clone()); }
+    list.push_str(&format!("{}- [{}](#{})\n", "  ".repeat((*lv - 2) as usize), title.trim().replace(['[', ']'], ""), anchor));
+  }
+  let body = crate::markdown::replace_ranges(text, edits);
+
+  // 替换目录标记所在行；无标记则放在开头
+  let mut out = String::with_capacity(body.len() + list.len());
+  let mut placed = false;
+  for (line, code) in code_lines(&body) {
+    let t = line.trim();
+    if !placed && !code && (t.eq_ignore_ascii_case("[toc]") || t.eq_ignore_ascii_case("<!-- toc -->")) {
+      out.push_str(&list);
+      placed = true;
+    } else {
+      out.push_str(line);
+    }
+  }
+  if !placed { out = format!("{}\n{}", list, body); }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn private_markers() {
+    for s in ["<!-->", "<!--->", "<!---->", "a <!--> b <!-- private --> c"] {
+      let _ = find_private_marker(s, true);
+      let _ = find_private_marker(s, false);
+    }
+    assert_eq!(find_private_marker("<!-->", true), None);
+    assert_eq!(find_private_marker("<!--->", true), None);
+    assert_eq!(find_private_marker("<!---->", false), None);
+    assert_eq!(find_private_marker("x <!--> <!-- PRIVATE -->", true), Some((8, 24)));
+    assert_eq!(find_private_marker("<!----><!-- /private -->", false), Some((7, 24)));
+
+    let text = "keep <!--> one\n<!-- private -->\nsecret <!---->\n<!-- endprivate -->\nkeep two\n";
+    assert_eq!(strip_private(text), "keep <!--> one\n\nkeep two\n");
+    assert_eq!(strip_private("a <!-- private -->hidden<!-- /private --> b\n"), "a b\n");
+  }
+
+  #[test]
+  fn comments() {
+    assert_eq!(strip_comments("a %%hidden%% b\n"), "a  b\n");
+    // 整行注释连同换行去掉；跨行注释
+    assert_eq!(strip_comments("one\n%% note %%\ntwo\n"), "one\ntwo\n");
+    assert_eq!(strip_comments("one %%start\nmiddle\nend%% two\n"), "one \n two\n");
+    assert_eq!(strip_comments("x\n%%\nall\ngone\n%%\ny\n"), "x\ny\n");
+    // 行内代码与围栏中的 %% 保留
+    assert_eq!(strip_comments("`%%x%%` %%y%%\n"), "`%%x%%` \n");
+    assert_eq!(strip_comments("```\n%% kept %%\n```\n%%gone%%\n"), "```\n%% kept %%\n```\n");
+    // 未闭合注释吞掉其后内容，但围栏仍不输出
+    assert_eq!(strip_comments("a %%open\n```\ncode\n```\nb\n"), "a \n");
+  }
+
+  #[test]
+  fn wiki() {
+    let resolve = |n: &str| (n == "Note").then(|| "/posts/note/".to_string());
+    assert_eq!(wiki_links("see [[Note]]\n", &resolve), "see [Note](</posts/note/>)\n");
+    assert_eq!(wiki_links("[[Note#Two Words|alias]]\n", &resolve), "[alias](</posts/note/#two-words>)\n");
+    assert_eq!(wiki_links("[[Note#Part]]\n", &resolve), "[Note > Part](</posts/note/#part>)\n");
+    assert_eq!(wiki_links("[[#Local Head]]\n", &resolve), "[Local Head](#local-head)\n");
+    assert_eq!(wiki_links("![[pic.PNG|cap]]\n", &resolve), "![cap](<pic.PNG>)\n");
+    // 未解析：退化为文本
+    assert_eq!(wiki_links("[[Missing]] and [[Missing|shown]]\n", &resolve), "Missing and shown\n");
+    assert_eq!(wiki_links("![[Missing]]\n", &resolve), "Missing\n");
+    // 代码与未闭合的链接保持原样
+    assert_eq!(wiki_links("`[[Note]]` [[Note\n", &resolve), "`[[Note]]` [[Note\n");
+    assert_eq!(wiki_links("~~~\n[[Note]]\n~~~\n", &resolve), "~~~\n[[Note]]\n~~~\n");
+  }
+
+  #[test]
+  fn wiki_template() {
+    let opts = TransformOptions { wiki_url_template: Some("/p/{slug}/?n={name}".into()), ..Default::default() };
+    let none = |_: &str| None;
+    assert_eq!(apply("[[Hello World]]\n", &opts, &none), "[Hello World](</p/hello-world/?n=Hello%20World>)\n");
+    let found = |_: &str| Some("/x/".to_string());
+    assert_eq!(apply("[[Hello World]]\n", &opts, &found), "[Hello World](</x/>)\n");
+  }
+
+  #[test]
+  fn callout_blocks() {
+    let out = callouts("> [!warning]- Be <careful>\n> line one\n> **two**\nafter\n");
+    assert_eq!(out, "<div class=\"callout callout-warning\">\n<p class=\"callout-title\">Be &lt;careful&gt;</p>\n\nline one\n**two**\n\n</div>\nafter\n");
+    // 无标题时用类型名
+    assert!(callouts("> [!NOTE]\n> x\n").contains("<p class=\"callout-title\">Note</p>"));
+    // 普通引用、缩进过深与围栏中的标记不转换
+    for s in ["> quote\n> [!note]\n", "    > [!note]\n", "```\n> [!note]\n```\n", "> [!bad kind]\n"] {
+      assert_eq!(callouts(s), s);
+    }
+  }
+
+  #[test]
+  fn toc_anchors() {
+    let text = "# Title\n\n[TOC]\n\n## Intro\n\n### Deep\n\n#### Too deep\n\n## Intro\n\nSetext Head\n-----------\n\n## Custom {#own}\n\n```\n[TOC]\n## not a heading\n```\n";
+    let out = toc(text, 3);
+    let list = "- [Intro](#intro)\n  - [Deep](#deep)\n- [Intro](#intro-1)\n- [Setext Head](#setext-head)\n- [Custom](#own)\n";
+    assert!(out.starts_with(&format!("# Title\n\n{}\n", list)), "{out}");
+    assert!(out.contains("## <a id=\"intro\"></a>Intro\n"));
+    assert!(out.contains("## <a id=\"intro-1\"></a>Intro\n"));
+    assert!(out.contains("<a id=\"setext-head\"></a>Setext Head\n-----------\n"));
+    assert!(out.contains("## Custom {#own}\n"));
+    // 围栏中的标记与标题不受影响
+    assert!(out.contains("```\n[TOC]\n## not a heading\n```\n"));
+    assert!(!out.contains("too-deep"));
+  }
+
+  #[test]
+  fn toc_placement() {
+    // 无标记时放在开头；自动锚点避开后面的显式 id
+    let out = toc("## Own\n\n## Other {#own}\n", 3);
+    assert_eq!(out, "- [Own](#own-1)\n- [Other](#own)\n\n## <a id=\"own-1\"></a>Own\n\n## Other {#own}\n");
+    assert_eq!(toc("<!-- TOC -->\n## A\n", 2), "- [A](#a)\n## <a id=\"a\"></a>A\n");
+    // 没有可收录的标题时原样返回
+    assert_eq!(toc("# Only title\n[toc]\n", 3), "# Only title\n[toc]\n");
+  }
+}
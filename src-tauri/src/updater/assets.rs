@@ -0,0 +1,212 @@
+// 按运行平台挑选 Release 资产：识别文件名中的包类型与架构，过滤并排序
+// 排序：架构完全匹配优先于可模拟运行的架构（Windows/macOS 上 ARM64 可运行 x64 版本），
+// 同架构下按包类型偏好（Linux 上优先与当前安装方式一致的类型）
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+  X64,
+  Arm64,
+  Armv7,
+  X86,
+  // 文件名中未标明架构（历史版本只发布 x64）
+  Unknown,
+}
+
+impl Arch {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Arch::X64 => "x86_64",
+      Arch::Arm64 => "aarch64",
+      Arch::Armv7 => "armv7",
+      Arch::X86 => "x86",
+      Arch::Unknown => "unknown",
+    }
+  }
+
+  pub fn current() -> Arch {
+    #[cfg(target_arch = "x86_64")] { Arch::X64 }
+    #[cfg(target_arch = "aarch64")] { Arch::Arm64 }
+    #[cfg(target_arch = "arm")] { Arch::Armv7 }
+    #[cfg(target_arch = "x86")] { Arch::X86 }
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "arm", target_arch = "x86")))] { Arch::Unknown }
+  }
+
+  // 文件名按非字母数字切分后逐段识别，避免 "x86_64" 被误判为 x86
+  fn of(name: &str) -> Arch {
+    let lower = name.to_ascii_lowercase();
+    if lower.contains("x86_64") || lower.contains("x86-64") { return Arch::X64; }
+    for tok in lower.split(|c: char| !c.is_ascii_alphanumeric()) {
+      match tok {
+        "x64" | "amd64" => return Arch::X64,
+        "arm64" | "aarch64" => return Arch::Arm64,
+        "armv7" | "armv7l" | "armhf" => return Arch::Armv7,
+        "x86" | "i386" | "i686" => return Arch::X86,
+        _ => {}
+      }
+    }
+    Arch::Unknown
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+  Nsis,
+  Msi,
+  AppImage,
+  Deb,
+  Rpm,
+  TarGz,
+  Dmg,
+}
+
+impl Kind {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Kind::Nsis => "nsis",
+      Kind::Msi => "msi",
+      Kind::AppImage => "appimage",
+      Kind::Deb => "deb",
+      Kind::Rpm => "rpm",
+      Kind::TarGz => "tar.gz",
+      Kind::Dmg => "dmg",
+    }
+  }
+
+  fn os(self) -> &'static str {
+    match self {
+      Kind::Nsis | Kind::Msi => "windows",
+      Kind::AppImage | Kind::Deb | Kind::Rpm | Kind::TarGz => "linux",
+      Kind::Dmg => "macos",
+    }
+  }
+
+  fn of(name: &str) -> Option<Kind> {
+    let n = name.to_ascii_lowercase();
+    // Tauri 自带更新器的包（*.AppImage.tar.gz、*.app.tar.gz、*.nsis.zip）与签名/校验文件不作为安装包
+    if n.ends_with(".appimage.tar.gz") || n.ends_with(".app.tar.gz") { return None; }
+    if n.ends_with(".exe") { Some(Kind::Nsis) }
+    else if n.ends_with(".msi") { Some(Kind::Msi) }
+    else if n.ends_with(".appimage") { Some(Kind::AppImage) }
+    else if n.ends_with(".deb") { Some(Kind::Deb) }
+    else if n.ends_with(".rpm") { Some(Kind::Rpm) }
+    else if n.ends_with(".tar.gz") || n.ends_with(".tgz") { Some(Kind::TarGz) }
+    else if n.ends_with(".dmg") { Some(Kind::Dmg) }
+    else { None }
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Matched {
+  pub index: usize,
+  pub kind: Kind,
+  pub arch: Arch,
+}
+
+// 当前架构下资产架构的匹配程度：0 完全匹配，1 未标明架构（视为 x64），2 可模拟运行；None 不可用
+fn arch_rank(os: &str, want: Arch, got: Arch) -> Option<u8> {
+  if got == want { return Some(0); }
+  match (want, got) {
+    (Arch::X64, Arch::Unknown) => Some(1),
+    // Windows 11 ARM 与 macOS（Rosetta）可运行 x64 程序
+    (Arch::Arm64, Arch::X64 | Arch::Unknown) if os == "windows" || os == "macos" => Some(2),
+    (Arch::X64, Arch::X86) if os == "windows" => Some(2),
+    _ => None,
+  }
+}
+
+// 同架构下的包类型偏好；install_kind 为 Linux 上的实际安装方式（appimage / deb）
+fn kind_rank(kind: Kind, install_kind: &str) -> u8 {
+  if kind.as_str() == install_kind { return 0; }
+  match kind {
+    Kind::Nsis | Kind::AppImage | Kind::Dmg => 1,
+    Kind::Msi | Kind::Deb => 2,
+    Kind::Rpm => 3,
+    Kind::TarGz => 4,
+  }
+}
+
+// 返回适用于 os/arch 的资产（按推荐程度排序）
+pub fn rank(names: &[&str], os: &str, arch: Arch, install_kind: &str) -> Vec<Matched> {
+  let mut found: Vec<(u8, u8, Matched)> = names.iter().enumerate().filter_map(|(index, name)| {
+    let kind = Kind::of(name).filter(|k| k.os() == os)?;
+    let got = Arch::of(name);
+    let ar = arch_rank(os, arch, got)?;
+    Some((ar, kind_rank(kind, install_kind), Matched { index, kind, arch: got }))
+  }).collect();
+  found.sort_by_key(|(a, k, m)| (*a, *k, m.index));
+  found.into_iter().map(|(_, _, m)| m).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const NAMES: &[&str] = &[
+    "flymd_1.2.0_x64-setup.exe",
+    "flymd_1.2.0_x64_en-US.msi",
+    "flymd_1.2.0_arm64-setup.exe",
+    "flymd_1.2.0_x86-setup.exe",
+    "flymd_1.2.0_amd64.AppImage",
+    "flymd_1.2.0_amd64.AppImage.tar.gz",
+    "flymd_1.2.0_amd64.deb",
+    "flymd_1.2.0_aarch64.AppImage",
+    "flymd_1.2.0_arm64.deb",
+    "flymd-1.2.0-1.x86_64.rpm",
+    "flymd_1.2.0_linux_x86_64.tar.gz",
+    "flymd_1.2.0_armhf.deb",
+    "flymd_1.2.0_aarch64.dmg",
+    "flymd_1.2.0_x64.dmg",
+    "flymd.app.tar.gz",
+    "SHA256SUMS",
+  ];
+
+  fn ranked(os: &str, arch: Arch, install_kind: &str) -> Vec<&'static str> {
+    rank(NAMES, os, arch, install_kind).into_iter().map(|m| NAMES[m.index]).collect()
+  }
+
+  #[test]
+  fn archs_and_kinds() {
+    assert_eq!(Arch::of("flymd-1.2.0-1.x86_64.rpm"), Arch::X64);
+    assert_eq!(Arch::of("flymd_x86-64.AppImage"), Arch::X64);
+    assert_eq!(Arch::of("flymd_1.2.0_i686.exe"), Arch::X86);
+    assert_eq!(Arch::of("flymd_1.2.0_armv7l.deb"), Arch::Armv7);
+    assert_eq!(Arch::of("flymd_1.2.0-setup.exe"), Arch::Unknown);
+    assert_eq!(Kind::of("flymd.AppImage.tar.gz"), None);
+    assert_eq!(Kind::of("flymd.tgz"), Some(Kind::TarGz));
+    assert_eq!(Kind::of("flymd.exe.sig"), None);
+  }
+
+  #[test]
+  fn windows() {
+    assert_eq!(ranked("windows", Arch::X64, "windows"), vec![
+      "flymd_1.2.0_x64-setup.exe", "flymd_1.2.0_x64_en-US.msi", "flymd_1.2.0_x86-setup.exe",
+    ]);
+    // ARM64 优先原生包，其次可模拟运行的 x64
+    assert_eq!(ranked("windows", Arch::Arm64, "windows"), vec![
+      "flymd_1.2.0_arm64-setup.exe", "flymd_1.2.0_x64-setup.exe", "flymd_1.2.0_x64_en-US.msi",
+    ]);
+    // 未标明架构的旧包视为 x64，排在标明 x64 的包之后
+    let old = ["flymd_1.0.0-setup.exe", "flymd_1.0.0_x64.msi"];
+    let got: Vec<_> = rank(&old, "windows", Arch::X64, "windows").into_iter().map(|m| (m.index, m.arch)).collect();
+    assert_eq!(got, vec![(1, Arch::X64), (0, Arch::Unknown)]);
+    assert!(rank(&old, "windows", Arch::Arm64, "windows").iter().all(|m| m.arch != Arch::Arm64));
+  }
+
+  #[test]
+  fn linux_follows_install_kind() {
+    assert_eq!(ranked("linux", Arch::X64, "deb"), vec![
+      "flymd_1.2.0_amd64.deb", "flymd_1.2.0_amd64.AppImage", "flymd-1.2.0-1.x86_64.rpm", "flymd_1.2.0_linux_x86_64.tar.gz",
+    ]);
+    assert_eq!(ranked("linux", Arch::X64, "appimage")[..2], ["flymd_1.2.0_amd64.AppImage", "flymd_1.2.0_amd64.deb"]);
+    // Linux 上不跨架构
+    assert_eq!(ranked("linux", Arch::Arm64, "appimage"), vec!["flymd_1.2.0_aarch64.AppImage", "flymd_1.2.0_arm64.deb"]);
+    assert_eq!(ranked("linux", Arch::Armv7, "deb"), vec!["flymd_1.2.0_armhf.deb"]);
+    assert!(ranked("linux", Arch::X86, "deb").is_empty());
+  }
+
+  #[test]
+  fn macos() {
+    assert_eq!(ranked("macos", Arch::Arm64, ""), vec!["flymd_1.2.0_aarch64.dmg", "flymd_1.2.0_x64.dmg"]);
+    assert_eq!(ranked("macos", Arch::X64, ""), vec!["flymd_1.2.0_x64.dmg"]);
+  }
+}
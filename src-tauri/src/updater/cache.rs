@@ -0,0 +1,214 @@
+// 更新检查缓存：把 Release 列表连同 ETag 保存在应用缓存目录，避免频繁请求耗尽 GitHub 匿名额度（60 次/小时）
+//   - 非强制检查在 MIN_INTERVAL 内直接使用缓存
+//   - 其余情况带 If-None-Match / If-Modified-Since 请求，304 时沿用缓存（条件请求不计入额度）
+//   - 额度耗尽（X-RateLimit-Remaining: 0，或 403/429 带 Retry-After）时记录恢复时间，期间不再请求
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// 限流错误的前缀，前端据此区分限流与其他失败
+pub const RATE_LIMITED: &str = "rate limited";
+
+const CACHE_FILE: &str = "update-check.json";
+const MIN_INTERVAL: u64 = 60 * 60;
+// 未给出恢复时间时的等待
+const DEFAULT_BACKOFF: u64 = 60;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Cached {
+  feed_url: String,
+  // 额度按是否携带 Token 分别计算
+  #[serde(default)]
+  authed: bool,
+  etag: Option<String>,
+  last_modified: Option<String>,
+  fetched_at: u64,
+  body: String,
+  rate_limited_until: Option<u64>,
+}
+
+fn now() -> u64 {
+  SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn load(path: &Path) -> Option<Cached> {
+  let s = std::fs::read_to_string(path).ok()?;
+  serde_json::from_str(&s).ok()
+}
+
+fn save(path: &Path, c: &Cached) {
+  if let Some(dir) = path.parent() { let _ = std::fs::create_dir_all(dir); }
+  if let Ok(s) = serde_json::to_string(c) { let _ = std::fs::write(path, s); }
+}
+
+fn rate_limited(until: u64) -> String {
+  let at = chrono::DateTime::<chrono::Utc>::from(UNIX_EPOCH + Duration::from_secs(until));
+  format!("{}: update feed request limit reached, retry after {}", RATE_LIMITED, at.to_rfc3339())
+}
+
+fn header_u64(resp: &reqwest::Response, name: &str) -> Option<u64> {
+  resp.headers().get(name).and_then(|v| v.to_str().ok()).and_then(|s| s.trim().parse().ok())
+}
+
+fn header_string(resp: &reqwest::Response, name: reqwest::header::HeaderName) -> Option<String> {
+  resp.headers().get(name).and_then(|v| v.to_str().ok()).map(|s| s.to_string())
+}
+
+// 获取 Release 列表原文；cache_dir 不可用时退化为每次请求
+pub async fn fetch_feed(
+  client: &reqwest::Client,
+  url: &url::Url,
+  token: Option<&str>,
+  cache_dir: Option<PathBuf>,
+  force: bool,
+) -> Result<String, String> {
+  let now = now();
+  let authed = token.is_some();
+  let path = cache_dir.map(|d| d.join(CACHE_FILE));
+  let cached = path.as_deref().and_then(load).filter(|c| c.feed_url == url.as_str() && c.authed == authed);
+
+  if let Some(c) = &cached {
+    if !force && !c.body.is_empty() && now < c.fetched_at.saturating_add(MIN_INTERVAL) {
+      return Ok(c.body.clone());
+    }
+    // 限流期间即使强制检查也不再请求，服务器只会再次拒绝
+    if let Some(until) = c.rate_limited_until.filter(|u| *u > now) {
+      return Err(rate_limited(until));
+    }
+  }
+
+  let mut req = client
+    .get(url.clone())
+    .header("Accept", "application/vnd.github+json");
+  // Token 只发给更新源，不发给下载镜像
+  if let Some(token) = token { req = req.bearer_auth(token); }
+  if let Some(c) = cached.as_ref().filter(|c| !c.body.is_empty()) {
+    if let Some(etag) = &c.etag { req = req.header(reqwest::header::IF_NONE_MATCH, etag); }
+    if let Some(lm) = &c.last_modified { req = req.header(reqwest::header::IF_MODIFIED_SINCE, lm); }
+  }
+  let resp = req.send().await.map_err(|e| format!("request error: {e}"))?;
+  let status = resp.status();
+  let remaining = header_u64(&resp, "x-ratelimit-remaining");
+  let reset = header_u64(&resp, "x-ratelimit-reset");
+  let retry_after = header_u64(&resp, "retry-after");
+  let mut entry = cached.unwrap_or_else(|| Cached { feed_url: url.to_string(), authed, ..Default::default() });
+
+  // 主限流（剩余 0）与次级限流（Retry-After）
+  let limited = (status == reqwest::StatusCode::FORBIDDEN || status == reqwest::StatusCode::TOO_MANY_REQUESTS)
+    && (remaining == Some(0) || retry_after.is_some());
+  if limited {
+    let until = retry_after.map(|s| now + s).or(reset).unwrap_or(now + DEFAULT_BACKOFF);
+    entry.rate_limited_until = Some(until);
+    if let Some(p) = &path { save(p, &entry); }
+    return Err(rate_limited(until));
+  }
+
+  // 本次成功但额度已用完：记下恢复时间，之前的检查直接报限流
+  entry.rate_limited_until = if remaining == Some(0) { reset } else { None };
+  if status == reqwest::StatusCode::NOT_MODIFIED && !entry.body.is_empty() {
+    entry.fetched_at = now;
+    if let Some(p) = &path { save(p, &entry); }
+    return Ok(entry.body);
+  }
+  if !status.is_success() { return Err(format!("http status {}", status)); }
+  entry.etag = header_string(&resp, reqwest::header::ETAG);
+  entry.last_modified = header_string(&resp, reqwest::header::LAST_MODIFIED);
+  entry.body = resp.text().await.map_err(|e| format!("read feed error: {e}"))?;
+  entry.fetched_at = now;
+  if let Some(p) = &path { save(p, &entry); }
+  Ok(entry.body)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_http::{serve, Response};
+
+  fn cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("flymd-upd-cache-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+  }
+
+  #[tokio::test]
+  async fn conditional_requests() {
+    let server = serve(|req| {
+      if req.header("if-none-match") == Some("\"v1\"") { return Response::new(304, "application/json", ""); }
+      Response::new(200, "application/json", "[1]").header("ETag", "\"v1\"").header("Last-Modified", "Mon, 01 Jan 2024 00:00:00 GMT")
+    });
+    let client = reqwest::Client::new();
+    let url = url::Url::parse(&server.url("/releases")).unwrap();
+    let dir = cache_dir("etag");
+
+    assert_eq!(fetch_feed(&client, &url, None, Some(dir.clone()), false).await.unwrap(), "[1]");
+    // 间隔内的非强制检查直接用缓存
+    assert_eq!(fetch_feed(&client, &url, None, Some(dir.clone()), false).await.unwrap(), "[1]");
+    assert_eq!(server.requests().len(), 1);
+    // 强制检查带条件请求，304 时沿用缓存
+    assert_eq!(fetch_feed(&client, &url, None, Some(dir.clone()), true).await.unwrap(), "[1]");
+    let reqs = server.requests();
+    assert_eq!(reqs.len(), 2);
+    assert_eq!(reqs[0].header("if-none-match"), None);
+    assert_eq!(reqs[1].header("if-none-match"), Some("\"v1\""));
+    assert_eq!(reqs[1].header("if-modified-since"), Some("Mon, 01 Jan 2024 00:00:00 GMT"));
+
+    // 携带 Token 时另行缓存，且 Token 只作为 Bearer 发给更新源
+    assert_eq!(fetch_feed(&client, &url, Some("t0ken"), Some(dir.clone()), false).await.unwrap(), "[1]");
+    let reqs = server.requests();
+    assert_eq!(reqs.len(), 3);
+    assert_eq!(reqs[2].header("authorization"), Some("Bearer t0ken"));
+    assert_eq!(reqs[2].header("if-none-match"), None);
+
+    // 没有缓存目录时每次都请求
+    fetch_feed(&client, &url, None, None, false).await.unwrap();
+    assert_eq!(server.requests().len(), 4);
+  }
+
+  #[tokio::test]
+  async fn rate_limits() {
+    let reset = now() + 3600;
+    let server = serve(move |req| match req.path.as_str() {
+      "/primary" => Response::new(403, "application/json", "{}")
+        .header("X-RateLimit-Remaining", "0")
+        .header("X-RateLimit-Reset", &reset.to_string()),
+      "/secondary" => Response::new(429, "application/json", "{}").header("Retry-After", "120"),
+      "/last" => Response::new(200, "application/json", "[2]")
+        .header("X-RateLimit-Remaining", "0")
+        .header("X-RateLimit-Reset", &reset.to_string()),
+      _ => Response::new(403, "application/json", "{\"message\":\"forbidden\"}"),
+    });
+    let client = reqwest::Client::new();
+    let url = |p: &str| url::Url::parse(&server.url(p)).unwrap();
+
+    let dir = cache_dir("primary");
+    let err = fetch_feed(&client, &url("/primary"), None, Some(dir.clone()), true).await.unwrap_err();
+    assert!(err.starts_with(RATE_LIMITED), "{err}");
+    // 限流期间强制检查也不再请求；带 Token 的额度另算
+    let again = fetch_feed(&client, &url("/primary"), None, Some(dir.clone()), true).await.unwrap_err();
+    assert_eq!(again, err);
+    assert_eq!(server.requests().len(), 1);
+    assert!(fetch_feed(&client, &url("/primary"), Some("t"), Some(dir), true).await.unwrap_err().starts_with(RATE_LIMITED));
+    assert_eq!(server.requests().len(), 2);
+
+    let dir = cache_dir("secondary");
+    let before = now();
+    let err = fetch_feed(&client, &url("/secondary"), None, Some(dir.clone()), true).await.unwrap_err();
+    assert!(err.starts_with(RATE_LIMITED), "{err}");
+    let until = load(&dir.join(CACHE_FILE)).unwrap().rate_limited_until.unwrap();
+    assert!(until >= before + 120 && until <= now() + 120);
+
+    // 成功但额度已用完：本次返回结果，之后的强制检查报限流
+    let dir = cache_dir("last");
+    assert_eq!(fetch_feed(&client, &url("/last"), None, Some(dir.clone()), true).await.unwrap(), "[2]");
+    let n = server.requests().len();
+    assert!(fetch_feed(&client, &url("/last"), None, Some(dir), true).await.unwrap_err().starts_with(RATE_LIMITED));
+    assert_eq!(server.requests().len(), n);
+
+    // 不带限流头的 403 是普通错误，不记录限流
+    let dir = cache_dir("forbidden");
+    assert_eq!(fetch_feed(&client, &url("/other"), None, Some(dir.clone()), true).await.unwrap_err(), "http status 403 Forbidden");
+    assert_eq!(load(&dir.join(CACHE_FILE)).and_then(|c| c.rate_limited_until), None);
+  }
+}
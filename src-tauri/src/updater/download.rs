@@ -0,0 +1,369 @@
+// 可续传的下载：写入 <目标>.part，中断后用 HTTP Range 从已有长度继续；完成并校验后再原子改名
+// 直连与代理共用同一个 .part 文件，切换线路时接着下载而不是从头开始
+
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+pub const PROGRESS_EVENT: &str = "update-download-progress";
+pub const CANCELLED: &str = "download cancelled";
+// 磁盘空间不足的错误前缀；换线路也无济于事，调用方据此直接结束
+pub const NO_SPACE: &str = "not enough disk space";
+
+// 预留空间，避免把分区写满
+const SPACE_MARGIN: u64 = 16 * 1024 * 1024;
+
+// 进度事件的最小间隔，避免每个数据块都推送到前端
+const EMIT_INTERVAL: Duration = Duration::from_millis(250);
+
+// 下载用客户端的超时：连接不上或数据停滞超过该时间即视为该线路失败，换下一条线路（不限制整体下载时长）
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+pub const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+// 等待连接与数据时检查取消标记的间隔；数据停滞时取消也要及时生效
+const CANCEL_POLL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadProgress {
+  pub url: String,
+  pub downloaded: u64,
+  pub total: Option<u64>,
+  // 最近一个统计周期内的速度（字节/秒）
+  pub speed: u64,
+  pub done: bool,
+  // 后台预下载的进度（由调用方标记），前端只显示用户发起的下载
+  pub background: bool,
+}
+
+pub fn part_path(dest: &Path) -> PathBuf {
+  let mut s = dest.as_os_str().to_os_string();
+  s.push(".part");
+  PathBuf::from(s)
+}
+
+// 当前下载的取消标记；用户下载与后台预下载各用一个，互不影响。每次开始下载时换新，cancel 只影响正在进行的那一次
+fn active(background: bool) -> &'static Mutex<Option<Arc<AtomicBool>>> {
+  static USER: OnceLock<Mutex<Option<Arc<AtomicBool>>>> = OnceLock::new();
+  static BACKGROUND: OnceLock<Mutex<Option<Arc<AtomicBool>>>> = OnceLock::new();
+  (if background { &BACKGROUND } else { &USER }).get_or_init(|| Mutex::new(None))
+}
+
+pub fn begin(background: bool) -> Arc<AtomicBool> {
+  let flag = Arc::new(AtomicBool::new(false));
+  if let Ok(mut a) = active(background).lock() { *a = Some(flag.clone()); }
+  flag
+}
+
+pub fn cancel(background: bool) -> bool {
+  match active(background).lock().ok().and_then(|a| a.clone()) {
+    Some(flag) => { flag.store(true, Ordering::SeqCst); true }
+    None => false,
+  }
+}
+
+// Content-Range: bytes <start>-<end>/<total|*>，或 416 时的 bytes */<total>
+fn parse_content_range(v: &str) -> (Option<u64>, Option<u64>) {
+  let v = v.trim().strip_prefix("bytes").unwrap_or(v).trim();
+  let (range, total) = v.split_once('/').unwrap_or((v, "*"));
+  let start = range.split_once('-').and_then(|(a, _)| a.trim().parse().ok());
+  (start, total.trim().parse().ok())
+}
+
+fn header_str(resp: &reqwest::Response, name: reqwest::header::HeaderName) -> Option<String> {
+  resp.headers().get(name).and_then(|v| v.to_str().ok()).map(|s| s.to_string())
+}
+
+// 等待 fut 完成，期间按 CANCEL_POLL 检查取消标记；取消时丢弃 fut（连接随之关闭）
+async fn until_cancelled<F: std::future::Future>(fut: F, cancel: &AtomicBool) -> Result<F::Output, String> {
+  tokio::pin!(fut);
+  loop {
+    if cancel.load(Ordering::SeqCst) { return Err(CANCELLED.into()); }
+    if let Ok(out) = tokio::time::timeout(CANCEL_POLL, &mut fut).await { return Ok(out); }
+  }
+}
+
+// 把 url 的内容续写到 part；成功返回时 part 即为完整文件
+pub async fn fetch_resumable(
+  client: &reqwest::Client,
+  url: &url::Url,
+  part: &Path,
+  cancel: &AtomicBool,
+  on_progress: &(dyn Fn(DownloadProgress) + Send + Sync),
+) -> Result<(), String> {
+  use futures_util::StreamExt;
+  use reqwest::StatusCode;
+
+  let mut f = std::fs::OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(part)
+    .map_err(|e| format!("open {} error: {e}", part.display()))?;
+  // 服务端不接受续传位置时清空重下，最多一次
+  let mut restarted = false;
+  loop {
+    let have = f.metadata().map_err(|e| format!("stat {} error: {e}", part.display()))?.len();
+    let mut req = client.get(url.clone());
+    if have > 0 {
+      req = req.header(reqwest::header::RANGE, format!("bytes={}-", have));
+    }
+    let resp = until_cancelled(req.send(), cancel).await?.map_err(|e| format!("request error: {e}"))?;
+    let status = resp.status();
+    let range = header_str(&resp, reqwest::header::CONTENT_RANGE).map(|v| parse_content_range(&v));
+
+    let (mut downloaded, total) = if status == StatusCode::PARTIAL_CONTENT {
+      let (start, total) = range.unwrap_or((None, None));
+      if start != Some(have) {
+        if restarted { return Err(format!("unexpected content-range from {}", url)); }
+        f.set_len(0).map_err(|e| format!("truncate error: {e}"))?;
+        restarted = true;
+        continue;
+      }
+      (have, total)
+    } else if status == StatusCode::RANGE_NOT_SATISFIABLE && have > 0 {
+      // 已有部分与文件总长相同：此前已下载完整，只差改名
+      if range.and_then(|r| r.1) == Some(have) {
+        on_progress(DownloadProgress { url: url.to_string(), downloaded: have, total: Some(have), done: true, ..Default::default() });
+        return Ok(());
+      }
+      if restarted { return Err(format!("http status {}", status)); }
+      f.set_len(0).map_err(|e| format!("truncate error: {e}"))?;
+      restarted = true;
+      continue;
+    } else if status.is_success() {
+      // 不支持 Range（或首次下载）：从头写
+      f.set_len(0).map_err(|e| format!("truncate error: {e}"))?;
+      (0, resp.content_length())
+    } else {
+      return Err(format!("http status {}", status));
+    };
+
+    // 按 Content-Length（或 Content-Range 中的总长）检查剩余空间
+    if let (Some(t), Some(dir)) = (total, part.parent()) {
+      let need = t.saturating_sub(downloaded);
+      if let Some(free) = crate::fsutil::available_space(dir) {
+        if free < need.saturating_add(SPACE_MARGIN) {
+          return Err(format!("{} in {}: need {} MB, {} MB available", NO_SPACE, dir.display(), (need + (1 << 20) - 1) >> 20, free >> 20));
+        }
+      }
+    }
+
+    let mut stream = resp.bytes_stream();
+    let mut last_emit = Instant::now();
+    let mut last_bytes = downloaded;
+    let mut speed = 0u64;
+    loop {
+      let next = match until_cancelled(stream.next(), cancel).await {
+        Ok(next) => next,
+        Err(e) => {
+          let _ = f.flush();
+          return Err(e);
+        }
+      };
+      let Some(chunk) = next else { break };
+      let bytes = chunk.map_err(|e| format!("read chunk error: {e}"))?;
+      f.write_all(&bytes).map_err(|e| format!("write error: {e}"))?;
+      downloaded += bytes.len() as u64;
+      let elapsed = last_emit.elapsed();
+      if elapsed >= EMIT_INTERVAL {
+        speed = ((downloaded - last_bytes) as f64 / elapsed.as_secs_f64()) as u64;
+        last_emit = Instant::now();
+        last_bytes = downloaded;
+        on_progress(DownloadProgress { url: url.to_string(), downloaded, total, speed, ..Default::default() });
+      }
+    }
+    f.flush().map_err(|e| format!("write error: {e}"))?;
+    if let Some(t) = total {
+      if downloaded < t { return Err(format!("connection closed at {} of {} bytes", downloaded, t)); }
+    }
+    on_progress(DownloadProgress { url: url.to_string(), downloaded, total: total.or(Some(downloaded)), speed, done: true, ..Default::default() });
+    return Ok(());
+  }
+}
+
+// 完成后改名为目标文件（同目录改名，不会出现写了一半的目标文件）；不覆盖已有文件，按 name (1).ext 依次取新名，
+// 途中遇到内容相同的文件（此前下载过同一版本）则直接沿用
+pub fn finish(part: &Path, dir: &Path, name: &str, sha256: &str) -> Result<PathBuf, String> {
+  for cand in crate::fsutil::numbered_paths(dir, name).take_while(|p| p.exists()) {
+    if cand.is_file() && super::verify::sha256_file(&cand).ok().as_deref() == Some(sha256) {
+      let _ = std::fs::remove_file(part);
+      return Ok(cand);
+    }
+  }
+  // 先以 create_new 占住新名，再改名覆盖这个空文件
+  let (dest, f) = crate::fsutil::create_non_clobbering(dir, name).map_err(|e| format!("create {} error: {e}", name))?;
+  drop(f);
+  if let Err(e) = std::fs::rename(part, &dest) {
+    let _ = std::fs::remove_file(&dest);
+    return Err(format!("rename {} error: {e}", part.display()));
+  }
+  Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_http::{serve, Response};
+
+  #[test]
+  fn content_range() {
+    assert_eq!(parse_content_range("bytes 100-199/1000"), (Some(100), Some(1000)));
+    assert_eq!(parse_content_range(" bytes 0-9/* "), (Some(0), None));
+    assert_eq!(parse_content_range("bytes */1000"), (None, Some(1000)));
+    assert_eq!(parse_content_range("100-199/1000"), (Some(100), Some(1000)));
+    assert_eq!(parse_content_range("bytes 5-"), (Some(5), None));
+    assert_eq!(parse_content_range("garbage"), (None, None));
+    assert_eq!(parse_content_range(""), (None, None));
+  }
+
+  #[test]
+  fn separate_cancel_tokens() {
+    let user = begin(false);
+    let bg = begin(true);
+    assert!(cancel(false));
+    assert!(user.load(Ordering::SeqCst));
+    assert!(!bg.load(Ordering::SeqCst));
+    // 新的下载换用新的标记，不受之前取消的影响
+    let user2 = begin(false);
+    assert!(!user2.load(Ordering::SeqCst));
+    assert!(cancel(true));
+    assert!(bg.load(Ordering::SeqCst) && !user2.load(Ordering::SeqCst));
+  }
+
+  // 按 Range 请求头应答的文件服务；ranges 为 false 时忽略 Range 返回整个文件
+  fn file_server(data: Vec<u8>, ranges: bool) -> crate::test_http::Server {
+    serve(move |req| {
+      let from = req.header("range").and_then(|r| r.strip_prefix("bytes=")).and_then(|r| r.trim_end_matches('-').parse::<usize>().ok());
+      match from {
+        Some(n) if ranges && n >= data.len() => Response::new(416, "text/plain", "").header("Content-Range", &format!("bytes */{}", data.len())),
+        Some(n) if ranges => Response::new(206, "application/octet-stream", data[n..].to_vec())
+          .header("Content-Range", &format!("bytes {}-{}/{}", n, data.len() - 1, data.len())),
+        _ => Response::new(200, "application/octet-stream", data.clone()),
+      }
+    })
+  }
+
+  fn tmp_part(name: &str, have: &[u8]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("flymd-dl-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let part = dir.join("a.bin.part");
+    std::fs::write(&part, have).unwrap();
+    part
+  }
+
+  async fn fetch(server: &crate::test_http::Server, part: &Path) -> Result<Vec<DownloadProgress>, String> {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+    let url = url::Url::parse(&server.url("/a.bin")).unwrap();
+    let on_progress = move |p: DownloadProgress| log.lock().unwrap().push(p);
+    fetch_resumable(&reqwest::Client::new(), &url, part, &AtomicBool::new(false), &on_progress).await?;
+    let out = seen.lock().unwrap().clone();
+    Ok(out)
+  }
+
+  #[tokio::test]
+  async fn resumes_from_part() {
+    let data: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+    let server = file_server(data.clone(), true);
+    let part = tmp_part("resume", &data[..300]);
+    let progress = fetch(&server, &part).await.unwrap();
+    assert_eq!(std::fs::read(&part).unwrap(), data);
+    assert_eq!(server.requests()[0].header("range"), Some("bytes=300-"));
+    let last = progress.last().unwrap();
+    assert!(last.done);
+    assert_eq!((last.downloaded, last.total), (1000, Some(1000)));
+
+    // 已完整下载只差改名：416 且总长与已有长度相同
+    let progress = fetch(&server, &part).await.unwrap();
+    assert_eq!(std::fs::read(&part).unwrap(), data);
+    assert_eq!(server.requests()[1].header("range"), Some("bytes=1000-"));
+    assert!(progress.last().unwrap().done);
+  }
+
+  #[tokio::test]
+  async fn restarts_without_range_support() {
+    let data = b"0123456789".repeat(50);
+    let server = file_server(data.clone(), false);
+    let part = tmp_part("norange", b"stale bytes");
+    fetch(&server, &part).await.unwrap();
+    assert_eq!(std::fs::read(&part).unwrap(), data);
+    assert_eq!(server.requests().len(), 1);
+  }
+
+  #[test]
+  fn finish_keeps_existing_files() {
+    let part = tmp_part("finish", b"new");
+    let dir = part.parent().unwrap().to_path_buf();
+    std::fs::write(dir.join("a.bin"), b"old").unwrap();
+    let sha = super::super::verify::sha256_file(&part).unwrap();
+    let dest = finish(&part, &dir, "a.bin", &sha).unwrap();
+    assert_eq!(dest, dir.join("a (1).bin"));
+    assert_eq!(std::fs::read(dir.join("a.bin")).unwrap(), b"old");
+    assert_eq!(std::fs::read(&dest).unwrap(), b"new");
+    assert!(!part.exists());
+
+    // 再次下载同一文件：沿用内容相同的 a (1).bin
+    std::fs::write(&part, b"new").unwrap();
+    assert_eq!(finish(&part, &dir, "a.bin", &sha).unwrap(), dest);
+    assert!(!part.exists());
+    assert!(!dir.join("a (2).bin").exists());
+  }
+
+  #[tokio::test]
+  async fn cancel_and_errors() {
+    let server = serve(|req| {
+      if req.path == "/missing" { Response::new(404, "text/plain", "no") } else { Response::new(200, "text/plain", "x") }
+    });
+    let part = tmp_part("errors", b"");
+    let url = url::Url::parse(&server.url("/a.bin")).unwrap();
+    let cancelled = AtomicBool::new(true);
+    let err = fetch_resumable(&reqwest::Client::new(), &url, &part, &cancelled, &|_| {}).await.unwrap_err();
+    assert_eq!(err, CANCELLED);
+    assert!(server.requests().is_empty());
+
+    let url = url::Url::parse(&server.url("/missing")).unwrap();
+    let err = fetch_resumable(&reqwest::Client::new(), &url, &part, &AtomicBool::new(false), &|_| {}).await.unwrap_err();
+    assert!(err.starts_with("http status 404"), "{err}");
+  }
+
+  // 发送部分数据后不再发送也不关闭连接
+  fn stalled_server() -> url::Url {
+    use std::io::Read;
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = url::Url::parse(&format!("http://{}/a.bin", listener.local_addr().unwrap())).unwrap();
+    std::thread::spawn(move || {
+      let (mut conn, _) = listener.accept().unwrap();
+      let _ = conn.read(&mut [0u8; 4096]);
+      let _ = conn.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1000\r\n\r\n0123456789");
+      std::thread::sleep(Duration::from_secs(30));
+    });
+    url
+  }
+
+  #[tokio::test]
+  async fn cancel_while_stalled() {
+    let part = tmp_part("stalled", b"");
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = cancel.clone();
+    tokio::spawn(async move {
+      tokio::time::sleep(Duration::from_millis(300)).await;
+      flag.store(true, Ordering::SeqCst);
+    });
+    let t0 = Instant::now();
+    let err = fetch_resumable(&reqwest::Client::new(), &stalled_server(), &part, &cancel, &|_| {}).await.unwrap_err();
+    assert_eq!(err, CANCELLED);
+    assert!(t0.elapsed() < Duration::from_secs(3), "{:?}", t0.elapsed());
+    assert_eq!(std::fs::read(&part).unwrap(), b"0123456789");
+
+    // 读超时：停滞的线路报错（调用方据此换线路），已收到的部分保留
+    let client = reqwest::Client::builder().read_timeout(Duration::from_millis(300)).build().unwrap();
+    let part = tmp_part("timeout", b"");
+    let err = fetch_resumable(&client, &stalled_server(), &part, &AtomicBool::new(false), &|_| {}).await.unwrap_err();
+    assert!(err.starts_with("read chunk error"), "{err}");
+    assert_eq!(std::fs::read(&part).unwrap(), b"0123456789");
+  }
+}
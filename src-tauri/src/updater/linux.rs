@@ -0,0 +1,266 @@
+// Linux 自更新：按实际安装方式处理
+//   - AppImage：运行时由 AppRun 设置 $APPIMAGE，直接替换该文件（同目录临时文件 + 改名，保留权限）
+//   - .deb：可执行文件归属于某个 dpkg 包时，通过 pkexec 调用 apt 安装
+// 其余情况（源码构建、解压运行等）不自动安装，由前端引导手动下载。
+
+use std::ffi::{OsStr, OsString};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallKind {
+  AppImage(PathBuf),
+  Deb,
+  Unknown,
+}
+
+impl InstallKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      InstallKind::AppImage(_) => "appimage",
+      InstallKind::Deb => "deb",
+      InstallKind::Unknown => "unknown",
+    }
+  }
+}
+
+// 正在运行的可执行文件；被 dpkg 替换后 /proc/self/exe 会带 " (deleted)" 后缀
+fn running_exe() -> Option<PathBuf> {
+  let p = std::env::current_exe().ok()?;
+  let s = p.to_string_lossy();
+  Some(match s.strip_suffix(" (deleted)") {
+    Some(t) => PathBuf::from(t),
+    None => p,
+  })
+}
+
+fn find_in_path(prog: &str) -> Option<PathBuf> {
+  let paths = std::env::var_os("PATH")?;
+  std::env::split_paths(&paths)
+    .map(|d| d.join(prog))
+    .find(|p| p.metadata().map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0).unwrap_or(false))
+}
+
+fn dpkg_owns(exe: &Path) -> bool {
+  if find_in_path("dpkg-query").is_none() { return false; }
+  // usr-merge 系统上包内记录的可能是 /bin/... 或 /usr/bin/...，两个路径都查
+  let mut candidates = vec![exe.to_path_buf()];
+  if let Ok(c) = std::fs::canonicalize(exe) { if c != exe { candidates.push(c); } }
+  candidates.iter().any(|p| {
+    Command::new("dpkg-query")
+      .arg("-S")
+      .arg(p)
+      .stdout(Stdio::null())
+      .stderr(Stdio::null())
+      .status()
+      .map(|s| s.success())
+      .unwrap_or(false)
+  })
+}
+
+pub fn detect() -> InstallKind {
+  if let Some(p) = std::env::var_os("APPIMAGE").map(PathBuf::from) {
+    if p.is_file() { return InstallKind::AppImage(p); }
+  }
+  match running_exe() {
+    Some(exe) if dpkg_owns(&exe) => InstallKind::Deb,
+    _ => InstallKind::Unknown,
+  }
+}
+
+// 用新文件替换正在运行的 AppImage：先写到同目录临时文件并落盘，再原子改名覆盖
+fn replace_appimage(target: &Path, new_file: &Path) -> Result<(), String> {
+  // $APPIMAGE 可能是符号链接，替换其指向的实际文件
+  let target = std::fs::canonicalize(target).map_err(|e| format!("resolve {} error: {e}", target.display()))?;
+  let dir = target.parent().ok_or_else(|| format!("invalid AppImage path {}", target.display()))?;
+  let name = target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "flymd.AppImage".into());
+  let mode = std::fs::metadata(&target).map_err(|e| format!("stat {} error: {e}", target.display()))?.permissions().mode();
+  let tmp = dir.join(format!(".{}.update-{}", name, std::process::id()));
+  let write = || -> Result<(), String> {
+    std::fs::copy(new_file, &tmp).map_err(|e| match e.kind() {
+      std::io::ErrorKind::PermissionDenied => format!("no write permission in {}; move the AppImage to a writable folder or update it manually", dir.display()),
+      _ => format!("copy update error: {e}"),
+    })?;
+    // 保留原文件权限，并确保可执行
+    std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode((mode & 0o7777) | 0o100))
+      .map_err(|e| format!("set permissions error: {e}"))?;
+    std::fs::File::open(&tmp).and_then(|f| f.sync_all()).map_err(|e| format!("sync error: {e}"))?;
+    std::fs::rename(&tmp, &target).map_err(|e| format!("replace {} error: {e}", target.display()))?;
+    if let Ok(d) = std::fs::File::open(dir) { let _ = d.sync_all(); }
+    Ok(())
+  };
+  let res = write();
+  if res.is_err() { let _ = std::fs::remove_file(&tmp); }
+  res
+}
+
+fn install_deb(file: &Path, downgrade: bool) -> Result<(), String> {
+  let manual = format!("sudo apt install {}", file.display());
+  let pkexec = find_in_path("pkexec").ok_or_else(|| format!("pkexec not found; install the update manually: {}", manual))?;
+  let apt = find_in_path("apt").ok_or_else(|| format!("apt not found; install the update manually: sudo dpkg -i {}", file.display()))?;
+  // apt 只把以 / 或 ./ 开头的参数当作本地文件
+  let abs = std::fs::canonicalize(file).map_err(|e| format!("installer {} error: {e}", file.display()))?;
+  let out = Command::new(pkexec)
+    .arg(apt)
+    .args(["install", "-y"])
+    .args(if downgrade { &["--allow-downgrades"][..] } else { &[] })
+    .arg(&abs)
+    .stdin(Stdio::null())
+    .output()
+    .map_err(|e| format!("spawn pkexec error: {e}"))?;
+  if out.status.success() { return Ok(()); }
+  match out.status.code() {
+    // pkexec：126 为用户取消授权对话框，127 为未获授权或认证失败
+    Some(126) => Err("authorization was cancelled".into()),
+    Some(127) => Err(format!("not authorized to install packages; install the update manually: {}", manual)),
+    code => {
+      let stderr = String::from_utf8_lossy(&out.stderr);
+      let detail: Vec<&str> = stderr.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with("WARNING:")).collect();
+      let tail = detail[detail.len().saturating_sub(3)..].join(" | ");
+      Err(format!("apt install failed (exit {}): {}", code.map(|c| c.to_string()).unwrap_or_else(|| "signal".into()), tail))
+    }
+  }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Step<'a> {
+  // 替换该路径上的 AppImage
+  ReplaceAppImage(&'a Path),
+  InstallDeb,
+}
+
+// 安装包类型必须与当前安装方式一致
+fn install_step<'a>(file: &Path, kind: &'a InstallKind) -> Result<Step<'a>, String> {
+  let ext = file.extension().map(|e| e.to_string_lossy().to_ascii_lowercase()).unwrap_or_default();
+  match (kind, ext.as_str()) {
+    (InstallKind::AppImage(target), "appimage") => Ok(Step::ReplaceAppImage(target)),
+    (InstallKind::Deb, "deb") => Ok(Step::InstallDeb),
+    (InstallKind::AppImage(_), _) => Err("flymd is running as an AppImage; download the .AppImage update instead".into()),
+    (InstallKind::Deb, _) => Err("flymd was installed from a .deb package; download the .deb update instead".into()),
+    (InstallKind::Unknown, _) => Err("cannot determine how flymd was installed; please install the update manually".into()),
+  }
+}
+
+// 安装已校验的更新包（kind 为 detect() 的结果）；downgrade 用于回滚到旧版本
+pub fn install(file: &Path, kind: &InstallKind, downgrade: bool) -> Result<(), String> {
+  match install_step(file, kind)? {
+    Step::ReplaceAppImage(target) => replace_appimage(target, file),
+    Step::InstallDeb => install_deb(file, downgrade),
+  }
+}
+
+// 重启时要从环境中去掉的变量：值中引用旧 AppImage 挂载点的，以及 AppImage 运行时自身设置的
+fn stale_env(appdir: Option<&OsStr>, vars: impl Iterator<Item = (OsString, OsString)>) -> Vec<OsString> {
+  let Some(appdir) = appdir.map(|d| d.to_string_lossy().to_string()).filter(|d| !d.is_empty()) else { return Vec::new() };
+  let mut keys: Vec<OsString> = vars.filter(|(_, v)| v.to_string_lossy().contains(&appdir)).map(|(k, _)| k).collect();
+  for k in ["APPIMAGE", "APPDIR", "ARGV0", "OWD"] {
+    if !keys.iter().any(|x| x == k) { keys.push(k.into()); }
+  }
+  keys
+}
+
+// 启动更新后的程序（调用方随后退出当前进程）
+pub fn relaunch() -> Result<(), String> {
+  let (exe, appdir) = match detect() {
+    InstallKind::AppImage(p) => (p, std::env::var_os("APPDIR")),
+    _ => (running_exe().ok_or_else(|| "cannot locate flymd executable".to_string())?, None),
+  };
+  let mut cmd = Command::new(&exe);
+  cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+  // 旧 AppImage 的挂载点随本进程退出而卸载，不能把指向它的环境变量（LD_LIBRARY_PATH 等）传给新进程
+  for k in stale_env(appdir.as_deref(), std::env::vars_os()) { cmd.env_remove(k); }
+  cmd.spawn().map(|_| ()).map_err(|e| format!("start {} error: {e}", exe.display()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::os::unix::fs::MetadataExt;
+
+  #[test]
+  fn install_matrix() {
+    let app = InstallKind::AppImage(PathBuf::from("/opt/flymd.AppImage"));
+    assert_eq!(install_step(Path::new("/tmp/flymd_1.1.0_amd64.AppImage"), &app), Ok(Step::ReplaceAppImage(Path::new("/opt/flymd.AppImage"))));
+    assert_eq!(install_step(Path::new("/tmp/x.appimage"), &app), Ok(Step::ReplaceAppImage(Path::new("/opt/flymd.AppImage"))));
+    assert_eq!(install_step(Path::new("/tmp/flymd_1.1.0_amd64.deb"), &InstallKind::Deb), Ok(Step::InstallDeb));
+    assert_eq!(install_step(Path::new("/tmp/X.DEB"), &InstallKind::Deb), Ok(Step::InstallDeb));
+
+    for (file, kind, msg) in [
+      ("/tmp/a.deb", &app, "download the .AppImage update"),
+      ("/tmp/a.AppImage.part", &app, "download the .AppImage update"),
+      ("/tmp/a", &app, "download the .AppImage update"),
+      ("/tmp/a.AppImage", &InstallKind::Deb, "download the .deb update"),
+      ("/tmp/a.rpm", &InstallKind::Deb, "download the .deb update"),
+      ("/tmp/a.deb", &InstallKind::Unknown, "install the update manually"),
+      ("/tmp/a.AppImage", &InstallKind::Unknown, "install the update manually"),
+    ] {
+      let err = install_step(Path::new(file), kind).unwrap_err();
+      assert!(err.contains(msg), "{file}: {err}");
+    }
+  }
+
+  #[test]
+  fn relaunch_env() {
+    let vars = || vec![
+      ("LD_LIBRARY_PATH", "/tmp/.mount_flymdX/usr/lib:/usr/lib"),
+      ("XDG_DATA_DIRS", "/usr/share:/tmp/.mount_flymdX/usr/share"),
+      ("PATH", "/usr/bin"),
+      ("HOME", "/home/u"),
+      ("APPIMAGE", "/opt/flymd.AppImage"),
+      ("APPDIR", "/tmp/.mount_flymdX"),
+    ].into_iter().map(|(k, v)| (OsString::from(k), OsString::from(v)));
+    let keys = stale_env(Some(OsStr::new("/tmp/.mount_flymdX")), vars());
+    assert_eq!(keys, ["LD_LIBRARY_PATH", "XDG_DATA_DIRS", "APPDIR", "APPIMAGE", "ARGV0", "OWD"]);
+    // 不是 AppImage 时环境原样保留
+    assert!(stale_env(None, vars()).is_empty());
+    assert!(stale_env(Some(OsStr::new("")), vars()).is_empty());
+  }
+
+  fn tmp(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("flymd-linux-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  #[test]
+  fn replaces_appimage() {
+    let dir = tmp("replace");
+    let target = dir.join("flymd.AppImage");
+    let update = dir.join("download.AppImage");
+    std::fs::write(&target, b"old").unwrap();
+    std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o750)).unwrap();
+    std::fs::write(&update, b"new").unwrap();
+    std::fs::set_permissions(&update, std::fs::Permissions::from_mode(0o600)).unwrap();
+    // 运行中的旧文件保持打开：改名替换后它仍读到旧内容
+    let running = std::fs::File::open(&target).unwrap();
+    let old_ino = running.metadata().unwrap().ino();
+
+    // 经符号链接替换实际文件，链接本身保留
+    let link = dir.join("flymd-link");
+    std::os::unix::fs::symlink(&target, &link).unwrap();
+    replace_appimage(&link, &update).unwrap();
+
+    assert_eq!(std::fs::read(&target).unwrap(), b"new");
+    assert!(std::fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+    let md = std::fs::metadata(&target).unwrap();
+    assert_eq!(md.permissions().mode() & 0o7777, 0o750);
+    assert_ne!(md.ino(), old_ino);
+    assert_eq!(std::fs::read_to_string(format!("/proc/self/fd/{}", std::os::unix::io::AsRawFd::as_raw_fd(&running))).unwrap(), "old");
+    assert_eq!(std::fs::read(&update).unwrap(), b"new");
+    let names: Vec<String> = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().to_string()).collect();
+    assert_eq!(names.len(), 3, "{names:?}");
+
+    // 原文件不可执行时补上属主执行位
+    std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o644)).unwrap();
+    replace_appimage(&target, &update).unwrap();
+    assert_eq!(std::fs::metadata(&target).unwrap().permissions().mode() & 0o7777, 0o744);
+
+    // 复制失败：目标不变，不留临时文件
+    assert!(replace_appimage(&target, &dir.join("missing")).unwrap_err().starts_with("copy update error"));
+    assert_eq!(std::fs::read(&target).unwrap(), b"new");
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 3);
+    let _ = std::fs::remove_dir_all(&dir);
+  }
+}
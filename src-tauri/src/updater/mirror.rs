@@ -0,0 +1,172 @@
+// 更新源配置：Release 列表地址、下载镜像与 GitHub Token（由前端从设置中读取后随命令传入）
+// 镜像写法：
+//   - 前缀："https://gh-proxy.com/" -> 前缀 + 原始地址
+//   - 模板：含 {url}（原始地址）、{path}（原始地址路径，如 /owner/repo/releases/download/v1/x.exe）或 {name}（文件名）
+//   - "direct"：直连原始地址；未列出时排在最后
+
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_FEED: &str = "https://api.github.com/repos/flyhunterl/flymd/releases";
+const DEFAULT_MIRRORS: &[&str] = &["https://gh-proxy.com/", "https://cdn.gh-proxy.com/", "https://edgeone.gh-proxy.com/"];
+const DIRECT: &str = "direct";
+
+// 测速超时：超过即视为不可用，排到最后但仍会尝试
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateConfig {
+  pub feed_url: Option<String>,
+  pub mirrors: Option<Vec<String>>,
+  pub github_token: Option<String>,
+  // 关闭后按配置顺序尝试镜像
+  pub probe_mirrors: Option<bool>,
+}
+
+impl UpdateConfig {
+  pub fn feed_url(&self) -> Result<url::Url, String> {
+    let raw = self.feed_url.as_deref().map(str::trim).filter(|s| !s.is_empty()).unwrap_or(DEFAULT_FEED);
+    let u = url::Url::parse(raw).map_err(|e| format!("invalid feed url: {e}"))?;
+    if u.scheme() != "https" && u.scheme() != "http" { return Err(format!("invalid feed url: {}", raw)); }
+    Ok(u)
+  }
+
+  pub fn token(&self) -> Option<&str> {
+    self.github_token.as_deref().map(str::trim).filter(|s| !s.is_empty())
+  }
+
+  // 配置的镜像（含 direct），保持用户给出的顺序
+  pub fn mirrors(&self) -> Vec<Mirror> {
+    let mut list: Vec<Mirror> = match &self.mirrors {
+      Some(v) => v.iter().map(|s| s.trim()).filter(|s| !s.is_empty()).map(|s| Mirror(s.to_string())).collect(),
+      None => DEFAULT_MIRRORS.iter().map(|s| Mirror(s.to_string())).collect(),
+    };
+    list.dedup_by(|a, b| a.0 == b.0);
+    if !list.iter().any(Mirror::is_direct) { list.push(Mirror(DIRECT.to_string())); }
+    list
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mirror(pub String);
+
+impl Mirror {
+  pub fn is_direct(&self) -> bool {
+    self.0.eq_ignore_ascii_case(DIRECT)
+  }
+
+  pub fn apply(&self, origin: &url::Url) -> Option<url::Url> {
+    if self.is_direct() { return Some(origin.clone()); }
+    let t = &self.0;
+    let raw = if t.contains("{url}") || t.contains("{path}") || t.contains("{name}") {
+      let name = origin.path_segments().and_then(|mut s| s.next_back()).unwrap_or("");
+      t.replace("{url}", origin.as_str())
+        .replace("{path}", origin.path().trim_start_matches('/'))
+        .replace("{name}", name)
+    } else if origin.as_str().starts_with(t.as_str()) {
+      origin.to_string()
+    } else if t.ends_with('/') {
+      format!("{}{}", t, origin)
+    } else {
+      format!("{}/{}", t, origin)
+    };
+    url::Url::parse(&raw).ok()
+  }
+}
+
+// 前端可能直接传入镜像地址，还原出原始地址（找不到嵌套地址时原样返回）
+pub fn origin_of(u: &url::Url) -> url::Url {
+  // mailto:a 这类没有 "//" 的地址可能比 scheme:// 还短
+  let start = u.scheme().len() + 3;
+  let Some(rest) = u.as_str().get(start..) else { return u.clone() };
+  rest.find("/https://").or_else(|| rest.find("/http://"))
+    .and_then(|i| url::Url::parse(&rest[i + 1..]).ok())
+    .unwrap_or_else(|| u.clone())
+}
+
+// 对各镜像发起 1 字节的 Range 请求测速，按延迟排序；失败的排在后面，同等情况下保持配置顺序
+pub async fn rank(client: &reqwest::Client, mirrors: Vec<Mirror>, origin: &url::Url) -> Vec<Mirror> {
+  if mirrors.len() < 2 { return mirrors; }
+  let probes = mirrors.iter().map(|m| {
+    let target = m.apply(origin);
+    async move {
+      let u = target?;
+      let t0 = Instant::now();
+      let r = client.get(u)
+        .header(reqwest::header::RANGE, "bytes=0-0")
+        .timeout(PROBE_TIMEOUT)
+        .send().await.ok()?;
+      if !r.status().is_success() { return None; }
+      Some(t0.elapsed())
+    }
+  });
+  let latencies = futures_util::future::join_all(probes).await;
+  let mut ranked: Vec<(Option<Duration>, Mirror)> = latencies.into_iter().zip(mirrors).collect();
+  ranked.sort_by_key(|(d, _)| d.unwrap_or(Duration::MAX));
+  ranked.into_iter().map(|(_, m)| m).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_http::{serve, Response};
+
+  const ORIGIN: &str = "https://github.com/flyhunterl/flymd/releases/download/v1.2.0/flymd_1.2.0_x64-setup.exe";
+
+  fn apply(m: &str) -> Option<String> {
+    Mirror(m.into()).apply(&url::Url::parse(ORIGIN).unwrap()).map(|u| u.to_string())
+  }
+
+  #[test]
+  fn applies_mirrors() {
+    assert_eq!(apply("direct").as_deref(), Some(ORIGIN));
+    assert_eq!(apply("DIRECT").as_deref(), Some(ORIGIN));
+    assert_eq!(apply("https://gh-proxy.com/"), Some(format!("https://gh-proxy.com/{}", ORIGIN)));
+    assert_eq!(apply("https://gh-proxy.com"), Some(format!("https://gh-proxy.com/{}", ORIGIN)));
+    assert_eq!(apply("https://m.example/?u={url}"), Some(format!("https://m.example/?u={}", ORIGIN)));
+    assert_eq!(
+      apply("https://m.example/gh/{path}").as_deref(),
+      Some("https://m.example/gh/flyhunterl/flymd/releases/download/v1.2.0/flymd_1.2.0_x64-setup.exe"),
+    );
+    assert_eq!(apply("https://m.example/files/{name}").as_deref(), Some("https://m.example/files/flymd_1.2.0_x64-setup.exe"));
+    // 前缀与原始地址相同时不重复拼接
+    assert_eq!(apply("https://github.com/").as_deref(), Some(ORIGIN));
+    assert_eq!(apply("not a url {name}"), None);
+  }
+
+  #[test]
+  fn origins() {
+    let origin = |s: &str| origin_of(&url::Url::parse(s).unwrap()).to_string();
+    assert_eq!(origin(&format!("https://gh-proxy.com/{}", ORIGIN)), ORIGIN);
+    assert_eq!(origin("https://m.example/x/http://host.example/a.exe"), "http://host.example/a.exe");
+    assert_eq!(origin(ORIGIN), ORIGIN);
+    assert_eq!(origin("mailto:a"), "mailto:a");
+    assert_eq!(origin("a:b"), "a:b");
+    assert_eq!(origin("file:///tmp/x"), "file:///tmp/x");
+  }
+
+  #[test]
+  fn configured_mirrors() {
+    let names = |c: UpdateConfig| c.mirrors().into_iter().map(|m| m.0).collect::<Vec<_>>();
+    let mut all: Vec<String> = DEFAULT_MIRRORS.iter().map(|s| s.to_string()).collect();
+    all.push(DIRECT.into());
+    assert_eq!(names(UpdateConfig::default()), all);
+    let c = UpdateConfig { mirrors: Some(vec![" direct ".into(), "https://a/".into(), "https://a/".into(), "".into()]), ..Default::default() };
+    assert_eq!(names(c), vec!["direct", "https://a/"]);
+  }
+
+  #[tokio::test]
+  async fn ranks_by_latency() {
+    let server = serve(|req| {
+      if req.path.starts_with("/slow/") { std::thread::sleep(Duration::from_millis(300)); }
+      if req.path.starts_with("/down/") { return Response::new(503, "text/plain", "busy"); }
+      Response::new(206, "application/octet-stream", "M").header("Content-Range", "bytes 0-0/100")
+    });
+    let mirror = |name: &str| Mirror(format!("{}/{}/{{path}}", server.base, name));
+    let ranked = rank(&reqwest::Client::new(), vec![mirror("down"), mirror("slow"), mirror("fast"), Mirror("bad {name}".into())], &url::Url::parse(ORIGIN).unwrap()).await;
+    assert_eq!(ranked, vec![mirror("fast"), mirror("slow"), mirror("down"), Mirror("bad {name}".into())]);
+    assert!(server.requests().iter().all(|r| r.header("range") == Some("bytes=0-0")));
+    assert_eq!(server.requests().len(), 3);
+  }
+}
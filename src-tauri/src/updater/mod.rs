@@ -0,0 +1,501 @@
+// 应用自更新：查询 GitHub Release、按平台挑选安装包、下载并校验、启动安装程序
+
+use serde::{Deserialize, Serialize};
+
+use crate::semver;
+
+mod assets;
+mod cache;
+mod download;
+#[cfg(target_os = "linux")]
+mod linux;
+mod mirror;
+mod notes;
+mod rollback;
+pub mod scheduler;
+pub mod settings;
+mod verify;
+
+pub use mirror::UpdateConfig;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAssetInfo {
+  name: String,
+  size: u64,
+  // 包类型：nsis / msi / appimage / deb / rpm / tar.gz / dmg
+  kind: String,
+  // 文件名标明的架构（未标明时为 unknown）
+  arch: String,
+  direct_url: String,
+  proxy_url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckUpdateResp {
+  has_update: bool,
+  current: String,
+  latest: String,
+  release_name: String,
+  notes: String,
+  html_url: String,
+  // 当前版本之后的每个发行版的说明（从新到旧），跨多个版本更新时展示完整变更
+  release_notes: Vec<notes::ReleaseNote>,
+  // 其中有版本含不兼容变更或迁移说明
+  has_breaking: bool,
+  // 适用于当前系统与架构的安装包，按推荐程度排序
+  assets: Vec<UpdateAssetInfo>,
+  // 当前运行的架构
+  arch: String,
+  // 当前的安装方式：windows / appimage / deb / unknown，前端据此选择安装包
+  install_kind: String,
+  // 生效的更新通道
+  channel: semver::Channel,
+  // 该版本处于“稍后提醒”期间（此时 has_update 为 false，仅手动检查会提示）
+  snoozed: bool,
+  // 可回滚到的旧版本（从新到旧），为空表示没有可回滚的版本
+  rollback_versions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallResult {
+  // installer（已启动安装程序）/ appimage / deb
+  method: String,
+  // 文件已就地更新，需要重启应用才能生效
+  restart_required: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GhAsset {
+  name: String,
+  browser_download_url: String,
+  size: Option<u64>,
+  #[allow(dead_code)]
+  content_type: Option<String>,
+}
+#[derive(Debug, Deserialize)]
+struct GhRelease {
+  tag_name: String,
+  name: Option<String>,
+  body: Option<String>,
+  draft: bool,
+  prerelease: bool,
+  html_url: String,
+  published_at: Option<String>,
+  assets: Vec<GhAsset>,
+}
+
+// Release 列表：GitHub API 的数组格式，也接受 /releases/latest 的单个对象（便于用静态文件充当更新源）
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum GhFeed {
+  List(Vec<GhRelease>),
+  One(Box<GhRelease>),
+}
+
+// 资产的代理地址：取配置中第一个非直连镜像
+fn proxy_url(mirrors: &[mirror::Mirror], raw: &str) -> String {
+  url::Url::parse(raw).ok()
+    .and_then(|u| mirrors.iter().filter(|m| !m.is_direct()).find_map(|m| m.apply(&u)))
+    .map(|u| u.to_string())
+    .unwrap_or_else(|| raw.to_string())
+}
+
+fn asset_info(mirrors: &[mirror::Mirror], a: &GhAsset, m: &assets::Matched) -> UpdateAssetInfo {
+  UpdateAssetInfo{
+    name: a.name.clone(),
+    size: a.size.unwrap_or(0),
+    kind: m.kind.as_str().to_string(),
+    arch: m.arch.as_str().to_string(),
+    direct_url: a.browser_download_url.clone(),
+    proxy_url: proxy_url(mirrors, &a.browser_download_url),
+  }
+}
+
+fn os_tag() -> &'static str {
+  #[cfg(target_os = "windows")] { "windows" }
+  #[cfg(target_os = "linux")] { "linux" }
+  #[cfg(target_os = "macos")] { "macos" }
+  #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))] { "other" }
+}
+
+#[tauri::command]
+pub async fn check_update(app: tauri::AppHandle, force: Option<bool>, include_prerelease: Option<bool>, config: Option<UpdateConfig>) -> Result<CheckUpdateResp, String> {
+  use tauri::Manager;
+  // 当前版本：与 tauri.conf.json 一致（构建时可由环境注入，这里直接读取 Cargo.toml 同步版本）
+  let current = env!("CARGO_PKG_VERSION").to_string();
+  let os = os_tag();
+  let arch = assets::Arch::current();
+
+  let force = force.unwrap_or(false);
+  let config = config.unwrap_or_default();
+  let url = config.feed_url()?;
+  let mirrors = config.mirrors();
+  let client = reqwest::Client::builder()
+    .user_agent("flymd-updater")
+    .build()
+    .map_err(|e| format!("build client error: {e}"))?;
+  // 非强制检查优先使用缓存；ETag 未变时服务器返回 304，不消耗额度
+  let body = cache::fetch_feed(&client, &url, config.token(), app.path().app_cache_dir().ok(), force).await?;
+  let releases = match serde_json::from_str::<GhFeed>(&body).map_err(|e| format!("json error: {e}"))? {
+    GhFeed::List(v) => v,
+    GhFeed::One(r) => vec![*r],
+  };
+  // 通道、锁定与跳过的版本来自保存的设置；兼容旧参数 include_prerelease = true（视为 beta 通道）
+  let settings = settings::UpdateSettings::load(&app);
+  let mut policy = settings.policy();
+  if include_prerelease == Some(true) { policy.channel = semver::Channel::Beta; }
+  let candidates: Vec<semver::Candidate> = releases.iter()
+    .map(|r| semver::Candidate { tag: r.tag_name.trim(), draft: r.draft, prerelease: r.prerelease })
+    .collect();
+  let install_kind = install_kind().await;
+  // 当前通道没有可用版本时视为已是最新
+  let Some(idx) = semver::pick_latest(&candidates, &current, &policy) else {
+    let rollback_versions = rollback_versions(&app, &current, &install_kind).await;
+    return Ok(CheckUpdateResp{
+      has_update: false,
+      latest: current.clone(),
+      current,
+      release_name: String::new(),
+      notes: String::new(),
+      html_url: String::new(),
+      release_notes: Vec::new(),
+      has_breaking: false,
+      assets: Vec::new(),
+      arch: arch.as_str().to_string(),
+      install_kind,
+      channel: policy.channel,
+      snoozed: false,
+      rollback_versions,
+    });
+  };
+  // 更新说明只到将要提示的版本为止（更高的版本可能已被跳过）
+  let offered = semver::Version::parse(candidates[idx].tag);
+  let release_notes: Vec<notes::ReleaseNote> = semver::newer_than(&candidates, &current, &policy).into_iter()
+    .filter(|i| semver::Version::parse(candidates[*i].tag) <= offered)
+    .map(|i| &releases[i])
+    .map(|r| notes::note(r.tag_name.trim(), r.name.as_deref(), r.body.as_deref(), &r.html_url, r.published_at.as_deref(), r.prerelease))
+    .collect();
+  let has_breaking = release_notes.iter().any(notes::ReleaseNote::breaking);
+  let latest = releases.into_iter().nth(idx).ok_or_else(|| "no release found".to_string())?;
+
+  let latest_tag = latest.tag_name.trim().to_string();
+  let snoozed = !force && settings.snoozed(&latest_tag);
+  let has_update = semver::is_newer(&latest_tag, &current) && !snoozed;
+
+  // 组装资产信息：按当前系统、架构与安装方式排序
+  let names: Vec<&str> = latest.assets.iter().map(|a| a.name.as_str()).collect();
+  let assets: Vec<UpdateAssetInfo> = assets::rank(&names, os, arch, &install_kind).iter()
+    .map(|m| asset_info(&mirrors, &latest.assets[m.index], m))
+    .collect();
+
+  let notes = latest.body.unwrap_or_default();
+  let name = latest.name.unwrap_or_else(|| latest_tag.clone());
+
+  let rollback_versions = rollback_versions(&app, &current, &install_kind).await;
+  Ok(CheckUpdateResp{
+    has_update,
+    current,
+    latest: latest_tag,
+    release_name: name,
+    notes,
+    html_url: latest.html_url,
+    release_notes,
+    has_breaking,
+    assets,
+    arch: arch.as_str().to_string(),
+    install_kind,
+    channel: policy.channel,
+    snoozed,
+    rollback_versions,
+  })
+}
+
+// 下载目录：调用方指定的目录（须为绝对路径），否则为系统下载目录（Linux 上遵循 XDG_DOWNLOAD_DIR），
+// 都不可用时回退到临时目录
+fn download_dir(app: &tauri::AppHandle, target_dir: Option<&str>) -> Result<std::path::PathBuf, String> {
+  use tauri::Manager;
+  if let Some(t) = target_dir.map(str::trim).filter(|t| !t.is_empty()) {
+    let dir = std::path::PathBuf::from(t);
+    if !dir.is_absolute() { return Err(format!("target dir must be absolute: {}", t)); }
+    std::fs::create_dir_all(&dir).map_err(|e| format!("create dir {} error: {e}", dir.display()))?;
+    if !dir.is_dir() { return Err(format!("not a directory: {}", dir.display())); }
+    return Ok(dir);
+  }
+  let dir = app.path().download_dir().ok()
+    .filter(|d| d.is_absolute() && std::fs::create_dir_all(d).is_ok())
+    .unwrap_or_else(std::env::temp_dir);
+  Ok(dir)
+}
+
+#[tauri::command]
+pub async fn download_file(
+  app: tauri::AppHandle,
+  url: String,
+  use_proxy: Option<bool>,
+  config: Option<UpdateConfig>,
+  target_dir: Option<String>,
+) -> Result<String, String> {
+  fetch_update(&app, &url, use_proxy, config, target_dir, false).await
+}
+
+// 下载并校验安装包；background 为后台预下载：使用单独的取消标记，进度事件带 background 标记
+async fn fetch_update(
+  app: &tauri::AppHandle,
+  url: &str,
+  use_proxy: Option<bool>,
+  config: Option<UpdateConfig>,
+  target_dir: Option<String>,
+  background: bool,
+) -> Result<String, String> {
+  use tauri::Emitter;
+  // 没有可用的签名公钥时无法校验，不必开始下载
+  let pk = verify::public_key()?;
+  let client = reqwest::Client::builder()
+    .user_agent("flymd-updater")
+    .connect_timeout(download::CONNECT_TIMEOUT)
+    .read_timeout(download::READ_TIMEOUT)
+    .build()
+    .map_err(|e| format!("build client error: {e}"))?;
+
+  // 前端可能直接传入镜像地址（https://gh-proxy.com/https://github.com/...），还原出 GitHub 原始地址用于校验与改走其他线路
+  let config = config.unwrap_or_default();
+  let given = url::Url::parse(url).map_err(|e| format!("invalid url: {e}"))?;
+  let origin = mirror::origin_of(&given);
+  // 线路顺序：默认按测速结果；use_proxy = false 时直连优先
+  let mut mirrors = config.mirrors();
+  if config.probe_mirrors.unwrap_or(true) {
+    mirrors = mirror::rank(&client, mirrors, &origin).await;
+  }
+  if use_proxy == Some(false) {
+    mirrors.sort_by_key(|m| !m.is_direct());
+  }
+  let mut routes = Vec::new();
+  if given != origin { routes.push(given.clone()); }
+  for u in mirrors.iter().filter_map(|m| m.apply(&origin)) {
+    if !routes.contains(&u) { routes.push(u); }
+  }
+
+  // 文件名取自 URL 最后一段，清理掉路径分隔符与非法字符
+  let fname = crate::fsutil::sanitize_file_name(
+    origin.path_segments().and_then(|mut s| s.next_back()).unwrap_or_default(),
+    "flymd-update.bin",
+  );
+  let dir = download_dir(app, target_dir.as_deref())?;
+  // .part 名固定，便于续传；完成后再按不覆盖的规则确定最终文件名
+  let part = download::part_path(&dir.join(&fname));
+
+  let cancel = download::begin(background);
+  let on_progress = |p: download::DownloadProgress| {
+    let _ = app.emit(download::PROGRESS_EVENT, download::DownloadProgress { background, ..p });
+  };
+  let mut errors: Vec<String> = Vec::new();
+  for route in &routes {
+    // 同一线路在有进展时重试（断点续传），无进展则换下一条线路，.part 保留给下一条线路继续
+    let mut attempts = 0;
+    let fetched = loop {
+      attempts += 1;
+      let before = std::fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+      match download::fetch_resumable(&client, route, &part, &cancel, &on_progress).await {
+        Ok(()) => break true,
+        Err(e) if e == download::CANCELLED || e.starts_with(download::NO_SPACE) => return Err(e),
+        Err(e) => {
+          let after = std::fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+          errors.push(format!("{}: {}", route, e));
+          if after <= before || attempts >= 3 { break false; }
+        }
+      }
+    };
+    if !fetched { continue; }
+    // 下载后校验签名与校验和；未通过时删除文件，视同下载失败（下一条线路从头下载）
+    match verify::verify_download(&client, &pk, &origin, &part, &mirrors).await {
+      Ok(sha) => {
+        let dest = download::finish(&part, &dir, &fname, &sha)?;
+        verify::remember(&dest, sha);
+        return Ok(dest.to_string_lossy().to_string());
+      }
+      Err(e) => {
+        let _ = std::fs::remove_file(&part);
+        errors.push(format!("verify {} failed: {}", route, e));
+      }
+    }
+  }
+  // 各线路的错误都带上，校验失败不会被后一次的网络错误掩盖
+  Err(if errors.is_empty() { "download failed".into() } else { errors.join("; ") })
+}
+
+// 取消用户发起的更新下载（不影响后台预下载）；已下载的部分保留在 .part 中，下次下载同一文件时续传
+#[tauri::command]
+pub async fn cancel_download() -> Result<bool, String> {
+  Ok(download::cancel(false))
+}
+
+async fn install_kind() -> String {
+  #[cfg(target_os = "linux")]
+  {
+    tauri::async_runtime::spawn_blocking(|| linux::detect().as_str())
+      .await
+      .unwrap_or("unknown")
+      .to_string()
+  }
+  #[cfg(target_os = "windows")]
+  { "windows".to_string() }
+  #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+  { "unknown".to_string() }
+}
+
+async fn rollback_versions(app: &tauri::AppHandle, current: &str, install_kind: &str) -> Vec<String> {
+  let Ok(root) = rollback::root(app) else { return Vec::new() };
+  let (current, install_kind) = (current.to_string(), install_kind.to_string());
+  tauri::async_runtime::spawn_blocking(move || rollback::available(&root, &current, &install_kind))
+    .await
+    .unwrap_or_default()
+    .into_iter()
+    .map(|e| e.version)
+    .collect()
+}
+
+// PowerShell 单引号字符串：内容按字面处理，其中的单引号（含 PowerShell 视同单引号的弯引号）写两次
+#[cfg(any(target_os = "windows", test))]
+fn ps_literal(s: &str) -> String {
+  let mut out = String::with_capacity(s.len() + 2);
+  out.push('\'');
+  for c in s.chars() {
+    if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') { out.push(c); }
+    out.push(c);
+  }
+  out.push('\'');
+  out
+}
+
+// 使用 PowerShell 以管理员权限启动安装程序；-Command 的参数由 PowerShell 重新解析，
+// 路径需写成字面字符串，否则含空格、括号（如 "setup (1).exe"）时会被拆开
+#[cfg(target_os = "windows")]
+fn start_installer(path: &str) -> Result<(), String> {
+  use std::process::Command;
+  let status = Command::new("powershell")
+    .args(["-NoProfile", "-Command"])
+    .arg(format!("Start-Process -FilePath {} -Verb runas", ps_literal(path)))
+    .status()
+    .map_err(|e| format!("spawn error: {e}"))?;
+  let _ = status; // 忽略返回码，由安装器自行处理
+  Ok(())
+}
+
+// version 为安装包对应的版本，用于保留回滚文件；未提供时不保留
+#[tauri::command]
+pub async fn run_installer(app: tauri::AppHandle, path: String, version: Option<String>) -> Result<InstallResult, String> {
+  // 只启动本次运行中由 download_file 下载并校验通过、且之后未被改动的文件
+  let p = std::path::PathBuf::from(&path);
+  tauri::async_runtime::spawn_blocking(move || verify::ensure_verified(&p))
+    .await
+    .map_err(|e| format!("join error: {e}"))??;
+  // 回滚文件只是尽力保留，保存失败不影响更新
+  let root = rollback::root(&app).ok();
+  let keep = settings::UpdateSettings::load(&app).rollback_keep();
+  let version = version.filter(|v| semver::Version::parse(v).is_some());
+  #[cfg(target_os = "windows")]
+  {
+    // 安装程序启动后应用随即退出，先保留本次的安装包（下次更新后即为可回滚的上一版本）
+    if let (Some(root), Some(v)) = (root, version) {
+      let p = std::path::PathBuf::from(&path);
+      let _ = tauri::async_runtime::spawn_blocking(move || rollback::keep(&root, &v, "windows", &p, Some(&v), keep)).await;
+    }
+    start_installer(&path)?;
+    Ok(InstallResult { method: "installer".into(), restart_required: false })
+  }
+  #[cfg(target_os = "linux")]
+  {
+    // pkexec 会等待授权对话框，放到阻塞线程执行
+    let p = std::path::PathBuf::from(&path);
+    let kind = tauri::async_runtime::spawn_blocking(move || {
+      let kind = linux::detect();
+      let current = env!("CARGO_PKG_VERSION");
+      // AppImage 替换前保留正在运行的版本
+      if let (linux::InstallKind::AppImage(target), Some(root)) = (&kind, &root) {
+        let _ = rollback::keep(root, current, kind.as_str(), target, version.as_deref(), keep);
+      }
+      linux::install(&p, &kind, false)?;
+      // deb 安装成功后保留本次的安装包
+      if let (linux::InstallKind::Deb, Some(root), Some(v)) = (&kind, &root, &version) {
+        let _ = rollback::keep(root, v, kind.as_str(), &p, Some(v), keep);
+      }
+      Ok::<_, String>(kind)
+    })
+      .await
+      .map_err(|e| format!("join error: {e}"))??;
+    Ok(InstallResult { method: kind.as_str().into(), restart_required: true })
+  }
+  #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+  {
+    let _ = (root, keep, version);
+    Err("run_installer only supports Windows and Linux".into())
+  }
+}
+
+// 回滚到保留的旧版本（未指定时为最近的一个）；与 run_installer 一样返回安装方式与是否需要重启
+#[tauri::command]
+pub async fn rollback_update(app: tauri::AppHandle, version: Option<String>) -> Result<InstallResult, String> {
+  let root = rollback::root(&app)?;
+  let current = env!("CARGO_PKG_VERSION");
+  #[cfg(target_os = "windows")]
+  {
+    let (_, path) = tauri::async_runtime::spawn_blocking(move || rollback::take(&root, version.as_deref(), current, "windows"))
+      .await
+      .map_err(|e| format!("join error: {e}"))??;
+    start_installer(&path.to_string_lossy())?;
+    Ok(InstallResult { method: "installer".into(), restart_required: false })
+  }
+  #[cfg(target_os = "linux")]
+  {
+    let keep = settings::UpdateSettings::load(&app).rollback_keep();
+    let kind = tauri::async_runtime::spawn_blocking(move || {
+      let kind = linux::detect();
+      let (entry, path) = rollback::take(&root, version.as_deref(), current, kind.as_str())?;
+      // AppImage 先保留当前版本，回滚后仍可恢复
+      if let linux::InstallKind::AppImage(target) = &kind {
+        let _ = rollback::keep(&root, current, kind.as_str(), target, Some(&entry.version), keep);
+      }
+      linux::install(&path, &kind, true)?;
+      Ok::<_, String>(kind)
+    })
+      .await
+      .map_err(|e| format!("join error: {e}"))??;
+    Ok(InstallResult { method: kind.as_str().into(), restart_required: true })
+  }
+  #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+  {
+    let _ = (root, current, version);
+    Err("rollback_update only supports Windows and Linux".into())
+  }
+}
+
+// 就地更新后重启：Linux 启动更新后的 AppImage / 可执行文件再退出当前进程
+#[tauri::command]
+pub async fn restart_after_update(app: tauri::AppHandle) -> Result<(), String> {
+  #[cfg(target_os = "linux")]
+  {
+    linux::relaunch()?;
+    app.exit(0);
+    Ok(())
+  }
+  #[cfg(not(target_os = "linux"))]
+  {
+    app.restart()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn powershell_literals() {
+    assert_eq!(ps_literal(r"C:\Users\me\Downloads\flymd_1.2.0_x64-setup (1).exe"), r"'C:\Users\me\Downloads\flymd_1.2.0_x64-setup (1).exe'");
+    assert_eq!(ps_literal(r"C:\Users\O'Brien\$env;a.exe"), r"'C:\Users\O''Brien\$env;a.exe'");
+    assert_eq!(ps_literal("C:\\x\u{2019}y.exe"), "'C:\\x\u{2019}\u{2019}y.exe'");
+    assert_eq!(ps_literal(""), "''");
+  }
+}
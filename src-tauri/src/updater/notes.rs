@@ -0,0 +1,94 @@
+// 跨版本更新说明：汇总当前版本之后的每个发行版，并标出含不兼容变更/迁移说明的版本
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseNote {
+  tag: String,
+  name: String,
+  notes: String,
+  html_url: String,
+  published_at: Option<String>,
+  prerelease: bool,
+  // 说明中含不兼容变更或迁移步骤
+  breaking: bool,
+}
+
+// Conventional Commits 的 "feat!: ..." / "fix(scope)!: ..."
+fn conventional_breaking(line: &str) -> bool {
+  let Some((head, _)) = line.split_once(':') else { return false };
+  let Some(kind) = head.trim().strip_suffix('!') else { return false };
+  let ty = kind.split_once('(').map(|(t, _)| t).unwrap_or(kind);
+  !ty.is_empty() && ty.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+// 标记写法：以 BREAKING / Breaking change / Migration / 迁移 / 不兼容 / 破坏性变更 开头的行（含标题、列表项、引用），
+// 以及 Conventional Commits 的 "feat!:"、"BREAKING CHANGE:"。只看行首，"No breaking changes"、"无不兼容变更" 不算
+pub fn is_breaking(body: &str) -> bool {
+  body.lines().any(|line| {
+    let l = line.trim().trim_start_matches(['#', '-', '*', '+', '>', ' ']).trim_start().trim_start_matches("**");
+    let lower = l.to_ascii_lowercase();
+    lower.starts_with("breaking")
+      || lower.starts_with("migration")
+      || ["迁移", "不兼容", "破坏性变更"].iter().any(|m| l.starts_with(m))
+      || conventional_breaking(l)
+  })
+}
+
+pub fn note(tag: &str, name: Option<&str>, body: Option<&str>, html_url: &str, published_at: Option<&str>, prerelease: bool) -> ReleaseNote {
+  let notes = body.unwrap_or_default().to_string();
+  ReleaseNote {
+    tag: tag.to_string(),
+    name: name.filter(|n| !n.trim().is_empty()).unwrap_or(tag).to_string(),
+    breaking: is_breaking(&notes),
+    notes,
+    html_url: html_url.to_string(),
+    published_at: published_at.map(|s| s.to_string()),
+    prerelease,
+  }
+}
+
+impl ReleaseNote {
+  pub fn breaking(&self) -> bool {
+    self.breaking
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn breaking_lines() {
+    for line in [
+      "## Breaking changes",
+      "### BREAKING",
+      "- Breaking: settings moved",
+      "* **Breaking change:** config renamed",
+      "> Migration: run the import again",
+      "BREAKING CHANGE: drop old format",
+      "- feat!: new storage",
+      "* fix(sync)!: change protocol",
+      "## 迁移说明",
+      "- 不兼容：旧插件需要更新",
+      "破坏性变更：移除旧接口",
+    ] {
+      assert!(is_breaking(&format!("intro\n{}\nmore", line)), "{line}");
+    }
+    for line in [
+      "No breaking changes in this release",
+      "- No breaking changes",
+      "- This is not a breaking change",
+      "Fixes a migration bug",
+      "- 无不兼容变更",
+      "本版本没有破坏性变更",
+      "- feat: new storage",
+      "- fix(sync): protocol!",
+      "- see: https://example.com",
+    ] {
+      assert!(!is_breaking(line), "{line}");
+    }
+    assert!(!is_breaking(""));
+  }
+}
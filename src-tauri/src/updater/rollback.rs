@@ -0,0 +1,263 @@
+// 回滚：更新时保留旧版本，新版本出问题时可以恢复
+//   - AppImage：替换前把正在运行的文件复制一份（即当前版本）
+//   - 安装包（Windows / deb）：已安装的程序无法还原成安装包，改为保留每次安装所用的安装包，
+//     下一次更新后它就是可回滚的上一个版本
+// 文件保存在应用数据目录的 rollback/<版本>/ 下，index.json 记录版本、安装方式与 sha256；
+// 比已安装版本旧的只保留最近 keep 个
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+
+use crate::semver::Version;
+
+const ROLLBACK_DIR: &str = "rollback";
+const INDEX_FILE: &str = "index.json";
+// 默认保留的旧版本个数
+pub const DEFAULT_KEEP: usize = 2;
+
+// 索引的读改写之间加锁
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+  pub version: String,
+  // 保存时的安装方式：windows / appimage / deb，只能回滚到相同安装方式的版本
+  pub install_kind: String,
+  // rollback/<版本>/ 下的文件名
+  pub file: String,
+  pub sha256: String,
+  pub saved_at: u64,
+}
+
+impl Entry {
+  fn dir(&self, root: &Path) -> PathBuf {
+    root.join(crate::fsutil::sanitize_file_name(&self.version, "unknown"))
+  }
+
+  pub fn path(&self, root: &Path) -> PathBuf {
+    self.dir(root).join(&self.file)
+  }
+}
+
+pub fn root(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+  app.path().app_data_dir()
+    .map(|d| d.join(ROLLBACK_DIR))
+    .map_err(|e| format!("data dir error: {e}"))
+}
+
+fn now() -> u64 {
+  SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn load(root: &Path) -> Vec<Entry> {
+  std::fs::read_to_string(root.join(INDEX_FILE)).ok()
+    .and_then(|s| serde_json::from_str(&s).ok())
+    .unwrap_or_default()
+}
+
+fn save(root: &Path, list: &[Entry]) -> Result<(), String> {
+  std::fs::create_dir_all(root).map_err(|e| format!("create dir error: {e}"))?;
+  let s = serde_json::to_string_pretty(list).map_err(|e| format!("serialize error: {e}"))?;
+  std::fs::write(root.join(INDEX_FILE), s).map_err(|e| format!("write rollback index error: {e}"))
+}
+
+fn parse_version(v: &str) -> Result<Version, String> {
+  Version::parse(v).ok_or_else(|| format!("invalid version: {}", v))
+}
+
+// 回滚文件名：安装时按扩展名识别安装包类型，而 $APPIMAGE 可能已被用户改名（如 ~/bin/flymd），
+// AppImage 统一保存为 .AppImage 结尾
+fn rollback_name(src: &Path, install_kind: &str) -> String {
+  let name = src.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+  let name = crate::fsutil::sanitize_file_name(&name, "flymd-rollback.bin");
+  if install_kind == "appimage" && !name.to_ascii_lowercase().ends_with(".appimage") {
+    return format!("{}.AppImage", name);
+  }
+  name
+}
+
+// 把 src 保存为 version 的回滚文件，然后清理：不早于 installed（本次安装后的版本）的都保留，
+// 更早的按版本从新到旧只留 keep 个；keep 为 0 时不再保存
+pub fn keep(root: &Path, version: &str, install_kind: &str, src: &Path, installed: Option<&str>, keep: usize) -> Result<(), String> {
+  let v = parse_version(version)?;
+  let installed = installed.and_then(Version::parse);
+  let _guard = LOCK.lock().map_err(|_| "rollback lock poisoned".to_string())?;
+  let mut list = load(root);
+  let mut removed = Vec::new();
+  // 同一版本的旧记录由本次替换；文件已被删除的记录一并清掉
+  list.retain(|e| {
+    if Version::parse(&e.version).as_ref() == Some(&v) { removed.push(e.dir(root)); return false; }
+    e.path(root).is_file()
+  });
+  for d in removed.drain(..) { let _ = std::fs::remove_dir_all(d); }
+
+  if keep > 0 {
+    let sha256 = super::verify::sha256_file(src)?;
+    let entry = Entry {
+      version: version.trim().to_string(),
+      install_kind: install_kind.to_string(),
+      file: rollback_name(src, install_kind),
+      sha256,
+      saved_at: now(),
+    };
+    let dir = entry.dir(root);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("create dir {} error: {e}", dir.display()))?;
+    // 先复制到临时文件再改名，中途失败不会留下不完整的回滚文件
+    let tmp = dir.join(format!(".{}.tmp", entry.file));
+    let res = std::fs::copy(src, &tmp)
+      .map_err(|e| format!("copy {} error: {e}", src.display()))
+      .and_then(|_| std::fs::rename(&tmp, entry.path(root)).map_err(|e| format!("rename error: {e}")));
+    if res.is_err() { let _ = std::fs::remove_dir_all(&dir); }
+    res?;
+    list.push(entry);
+  }
+
+  list.sort_by(|a, b| Version::parse(&b.version).cmp(&Version::parse(&a.version)));
+  let mut older = 0;
+  list.retain(|e| {
+    let ev = Version::parse(&e.version);
+    if installed.is_some() && ev >= installed { return true; }
+    older += 1;
+    if older <= keep { return true; }
+    removed.push(e.dir(root));
+    false
+  });
+  for d in removed { let _ = std::fs::remove_dir_all(d); }
+  save(root, &list)
+}
+
+// 可回滚的版本：早于当前版本、安装方式相同且文件仍在（从新到旧）
+pub fn available(root: &Path, current: &str, install_kind: &str) -> Vec<Entry> {
+  let Some(current) = Version::parse(current) else { return Vec::new() };
+  let mut list: Vec<Entry> = load(root).into_iter()
+    .filter(|e| e.install_kind == install_kind)
+    .filter(|e| Version::parse(&e.version).map(|v| v < current).unwrap_or(false))
+    .filter(|e| e.path(root).is_file())
+    .collect();
+  list.sort_by(|a, b| Version::parse(&b.version).cmp(&Version::parse(&a.version)));
+  list
+}
+
+// 选出要回滚的文件（未指定版本时取最新的一个），并确认保存后未被改动
+pub fn take(root: &Path, version: Option<&str>, current: &str, install_kind: &str) -> Result<(Entry, PathBuf), String> {
+  let list = available(root, current, install_kind);
+  let entry = match version.map(str::trim).filter(|v| !v.is_empty()) {
+    Some(v) => {
+      let want = parse_version(v)?;
+      list.into_iter().find(|e| Version::parse(&e.version).as_ref() == Some(&want))
+        .ok_or_else(|| format!("no saved version {} to roll back to", v))?
+    }
+    None => list.into_iter().next().ok_or_else(|| "no previous version to roll back to".to_string())?,
+  };
+  let path = entry.path(root);
+  if super::verify::sha256_file(&path)? != entry.sha256 {
+    return Err(format!("{} changed after it was saved", path.display()));
+  }
+  Ok((entry, path))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn setup(name: &str) -> (PathBuf, PathBuf) {
+    let base = std::env::temp_dir().join(format!("flymd-rollback-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&base);
+    std::fs::create_dir_all(base.join("src")).unwrap();
+    (base.join("rollback"), base.join("src"))
+  }
+
+  fn installer(src: &Path, version: &str) -> PathBuf {
+    let p = src.join(format!("flymd_{}_x64-setup.exe", version));
+    std::fs::write(&p, format!("installer {}", version)).unwrap();
+    p
+  }
+
+  fn versions(root: &Path) -> Vec<String> {
+    load(root).into_iter().map(|e| e.version).collect()
+  }
+
+  #[test]
+  fn prunes_older_versions() {
+    let (root, src) = setup("prune");
+    // 每次安装后保留本次安装包：不早于已安装版本的都保留，更早的只留 keep 个
+    for v in ["1.0.0", "1.1.0", "1.2.0", "1.3.0"] {
+      keep(&root, v, "windows", &installer(&src, v), Some(v), 2).unwrap();
+    }
+    assert_eq!(versions(&root), vec!["1.3.0", "1.2.0", "1.1.0"]);
+    assert!(!root.join("1.0.0").exists());
+    assert!(root.join("1.1.0").join("flymd_1.1.0_x64-setup.exe").is_file());
+
+    // 同一版本再次保存时替换旧记录
+    std::fs::write(src.join("flymd_1.2.0_x64-setup.exe"), "rebuilt").unwrap();
+    keep(&root, "1.2.0", "windows", &src.join("flymd_1.2.0_x64-setup.exe"), Some("1.3.0"), 2).unwrap();
+    assert_eq!(versions(&root), vec!["1.3.0", "1.2.0", "1.1.0"]);
+    assert_eq!(std::fs::read_to_string(root.join("1.2.0").join("flymd_1.2.0_x64-setup.exe")).unwrap(), "rebuilt");
+
+    // 文件已被删除的记录一并清掉
+    std::fs::remove_dir_all(root.join("1.1.0")).unwrap();
+    keep(&root, "1.3.0", "windows", &installer(&src, "1.3.0"), Some("1.3.0"), 2).unwrap();
+    assert_eq!(versions(&root), vec!["1.3.0", "1.2.0"]);
+  }
+
+  #[test]
+  fn keep_zero_and_unknown_installed() {
+    let (root, src) = setup("zero");
+    for v in ["1.0.0", "1.1.0"] {
+      keep(&root, v, "appimage", &installer(&src, v), Some("1.2.0"), 2).unwrap();
+    }
+    assert_eq!(versions(&root), vec!["1.1.0", "1.0.0"]);
+    // 未知已安装版本时所有记录都按旧版本计数
+    keep(&root, "0.9.0", "appimage", &installer(&src, "0.9.0"), None, 2).unwrap();
+    assert_eq!(versions(&root), vec!["1.1.0", "1.0.0"]);
+    assert!(!root.join("0.9.0").exists());
+    // keep 为 0：不再保存，并清理所有早于已安装版本的文件
+    keep(&root, "1.2.0", "appimage", &installer(&src, "1.2.0"), Some("1.2.0"), 0).unwrap();
+    assert!(versions(&root).is_empty());
+    assert!(!root.join("1.0.0").exists() && !root.join("1.1.0").exists() && !root.join("1.2.0").exists());
+    assert!(keep(&root, "not-a-version", "appimage", &installer(&src, "x"), None, 2).is_err());
+  }
+
+  #[test]
+  fn available_and_take() {
+    let (root, src) = setup("take");
+    for v in ["1.0.0", "1.1.0", "1.2.0"] {
+      keep(&root, v, "deb", &installer(&src, v), Some(v), 3).unwrap();
+    }
+    keep(&root, "1.1.5", "appimage", &installer(&src, "1.1.5"), Some("1.2.0"), 3).unwrap();
+    let avail: Vec<_> = available(&root, "1.2.0", "deb").into_iter().map(|e| e.version).collect();
+    assert_eq!(avail, vec!["1.1.0", "1.0.0"]);
+    assert!(available(&root, "garbage", "deb").is_empty());
+
+    let (entry, path) = take(&root, None, "1.2.0", "deb").unwrap();
+    assert_eq!(entry.version, "1.1.0");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "installer 1.1.0");
+    assert_eq!(take(&root, Some("1.0.0"), "1.2.0", "deb").unwrap().0.version, "1.0.0");
+    assert!(take(&root, Some("1.1.5"), "1.2.0", "deb").is_err());
+    // 保存后被改动的文件不能用于回滚
+    std::fs::write(&path, "tampered").unwrap();
+    assert!(take(&root, None, "1.2.0", "deb").unwrap_err().contains("changed after it was saved"));
+  }
+
+  #[test]
+  fn appimage_names() {
+    let (root, src) = setup("names");
+    // 改过名的 AppImage 也以 .AppImage 保存，回滚时才能被识别为 AppImage
+    let renamed = src.join("flymd");
+    std::fs::write(&renamed, "appimage 1.0.0").unwrap();
+    keep(&root, "1.0.0", "appimage", &renamed, Some("1.1.0"), 2).unwrap();
+    let (entry, path) = take(&root, None, "1.1.0", "appimage").unwrap();
+    assert_eq!(entry.file, "flymd.AppImage");
+    assert_eq!(path.extension().unwrap(), "AppImage");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "appimage 1.0.0");
+
+    assert_eq!(rollback_name(Path::new("/opt/Flymd.appimage"), "appimage"), "Flymd.appimage");
+    assert_eq!(rollback_name(Path::new("/opt/flymd-1.0.AppImage.bak"), "appimage"), "flymd-1.0.AppImage.bak.AppImage");
+    assert_eq!(rollback_name(Path::new("/tmp/flymd_1.0.0_amd64.deb"), "deb"), "flymd_1.0.0_amd64.deb");
+  }
+}
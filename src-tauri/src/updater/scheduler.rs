@@ -0,0 +1,253 @@
+// 后台更新检查：在 Rust 侧按设置的间隔（加随机抖动）定时检查，失败时指数退避；可选在后台预先下载匹配的安装包。
+// 结果以 update-available / update-downloaded 事件广播给所有窗口，同时保存在内存中，
+// 之后才加载前端代码的窗口可通过 get_update_status 补取
+
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{Emitter, Manager};
+
+use super::settings::UpdateSettings;
+use super::{CheckUpdateResp, UpdateConfig};
+
+pub const AVAILABLE_EVENT: &str = "update-available";
+pub const DOWNLOADED_EVENT: &str = "update-downloaded";
+
+// 前端设置所在的 store 文件（tauri-plugin-store，位于应用数据目录）及其中的更新源配置
+const STORE_FILE: &str = "flymd-settings.json";
+const STORE_KEY: &str = "updater";
+// 启动后首次检查的延迟，避开启动时的繁忙阶段
+const STARTUP_DELAY: Duration = Duration::from_secs(10);
+// 关闭自动检查时，隔多久重新读取一次设置
+const SETTINGS_POLL: Duration = Duration::from_secs(60 * 60);
+// 失败后首次重试的间隔，之后逐次翻倍，不超过检查间隔
+const BACKOFF_BASE: Duration = Duration::from_secs(5 * 60);
+// 间隔上下浮动的比例（%），避免大量客户端同时请求
+const JITTER_PERCENT: u64 = 10;
+// 预下载目录（应用缓存目录下），与用户手动下载的目录分开
+const PREDOWNLOAD_DIR: &str = "updates";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Downloaded {
+  version: String,
+  // 资产文件名与下载并校验后的本地路径
+  asset: String,
+  path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateStatus {
+  // 最近一次检查成功的时间与下一次检查的时间（Unix 秒）
+  last_check: Option<u64>,
+  next_check: Option<u64>,
+  last_error: Option<String>,
+  // 最近一次检查发现的更新
+  available: Option<CheckUpdateResp>,
+  // 已在后台下载并校验通过的安装包
+  downloaded: Option<Downloaded>,
+}
+
+static STATUS: Mutex<UpdateStatus> = Mutex::new(UpdateStatus {
+  last_check: None,
+  next_check: None,
+  last_error: None,
+  available: None,
+  downloaded: None,
+});
+
+fn now() -> u64 {
+  SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn with_status<R>(f: impl FnOnce(&mut UpdateStatus) -> R) -> Option<R> {
+  STATUS.lock().ok().map(|mut s| f(&mut s))
+}
+
+// 在 d 上下浮动 JITTER_PERCENT%
+fn jitter(d: Duration) -> Duration {
+  let span = d.as_secs() * JITTER_PERCENT / 100;
+  if span == 0 { return d; }
+  // 只用于错开请求时间，不需要密码学随机数：RandomState 的密钥来自系统随机源，每次 new() 都不同，
+  // 对空输入求哈希即得到一个足够分散的随机值，不必为此引入 rand
+  let r = RandomState::new().build_hasher().finish() % (2 * span + 1);
+  Duration::from_secs(d.as_secs() - span + r)
+}
+
+fn backoff(failures: u32, interval: Duration) -> Duration {
+  let d = BACKOFF_BASE.saturating_mul(1u32 << failures.saturating_sub(1).min(16));
+  jitter(d.min(interval))
+}
+
+// 更新源、镜像与 Token 由前端保存在 store 中；读取失败时使用默认配置
+fn stored_config(app: &tauri::AppHandle) -> UpdateConfig {
+  app.path().app_data_dir().ok()
+    .and_then(|d| std::fs::read_to_string(d.join(STORE_FILE)).ok())
+    .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+    .and_then(|mut v| v.get_mut(STORE_KEY).map(serde_json::Value::take))
+    .and_then(|v| serde_json::from_value(v).ok())
+    .unwrap_or_default()
+}
+
+// 可在后台直接下载的安装包：与当前安装方式一致的第一个（资产已按推荐程度排序）
+fn preferred_asset(resp: &CheckUpdateResp) -> Option<&super::UpdateAssetInfo> {
+  resp.assets.iter().find(|a| match resp.install_kind.as_str() {
+    "windows" => a.kind == "nsis" || a.kind == "msi",
+    "appimage" | "deb" => a.kind == resp.install_kind,
+    _ => false,
+  })
+}
+
+async fn predownload(app: &tauri::AppHandle, resp: &CheckUpdateResp, config: UpdateConfig) -> Result<(), String> {
+  let Some(asset) = preferred_asset(resp) else { return Ok(()) };
+  let done = with_status(|s| s.downloaded.clone()).flatten();
+  if done.map(|d| d.version == resp.latest && std::path::Path::new(&d.path).is_file()).unwrap_or(false) {
+    return Ok(());
+  }
+  let dir = app.path().app_cache_dir()
+    .map(|d| d.join(PREDOWNLOAD_DIR))
+    .map_err(|e| format!("cache dir error: {e}"))?;
+  let path = match super::fetch_update(app, &asset.direct_url, None, Some(config), Some(dir.to_string_lossy().to_string()), true).await {
+    Ok(p) => p,
+    // 关闭预下载时取消的，不算作检查失败
+    Err(e) if e == super::download::CANCELLED => return Ok(()),
+    Err(e) => return Err(e),
+  };
+  // 只保留本次的安装包，旧版本与残留的 .part 一并清理
+  if let Ok(rd) = std::fs::read_dir(&dir) {
+    for e in rd.flatten() {
+      if e.path().to_string_lossy() != path { let _ = std::fs::remove_file(e.path()); }
+    }
+  }
+  let d = Downloaded { version: resp.latest.clone(), asset: asset.name.clone(), path };
+  with_status(|s| s.downloaded = Some(d.clone()));
+  let _ = app.emit(DOWNLOADED_EVENT, d);
+  Ok(())
+}
+
+async fn tick(app: &tauri::AppHandle, settings: &UpdateSettings) -> Result<(), String> {
+  let config = stored_config(app);
+  // 非强制检查：使用缓存与条件请求，并遵守“稍后提醒”
+  let resp = super::check_update(app.clone(), Some(false), None, Some(config.clone())).await?;
+  with_status(|s| {
+    s.last_check = Some(now());
+    s.last_error = None;
+    s.available = if resp.has_update { Some(resp.clone()) } else { None };
+  });
+  if !resp.has_update { return Ok(()); }
+  let _ = app.emit(AVAILABLE_EVENT, resp.clone());
+  if settings.auto_download() {
+    predownload(app, &resp, config).await?;
+  }
+  Ok(())
+}
+
+async fn run(app: tauri::AppHandle) {
+  tokio::time::sleep(jitter(STARTUP_DELAY)).await;
+  let mut failures = 0u32;
+  loop {
+    // 每轮重新读取设置，修改间隔或开关后无需重启
+    let settings = UpdateSettings::load(&app);
+    let interval = Duration::from_secs(settings.check_interval_hours() * 3600);
+    let delay = if !settings.auto_check() {
+      SETTINGS_POLL.min(interval)
+    } else {
+      match tick(&app, &settings).await {
+        Ok(()) => { failures = 0; jitter(interval) }
+        Err(e) => {
+          with_status(|s| s.last_error = Some(e));
+          failures = failures.saturating_add(1);
+          backoff(failures, interval)
+        }
+      }
+    };
+    with_status(|s| s.next_check = Some(now() + delay.as_secs()));
+    tokio::time::sleep(delay).await;
+  }
+}
+
+// 在 setup 中调用，启动后台检查
+pub fn start(app: tauri::AppHandle) {
+  tauri::async_runtime::spawn(run(app));
+}
+
+// 最近一次后台检查的结果，供之后加载的窗口补取
+#[tauri::command]
+pub async fn get_update_status() -> Result<UpdateStatus, String> {
+  with_status(|s| s.clone()).ok_or_else(|| "update status lock poisoned".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::UpdateAssetInfo;
+
+  fn within(d: Duration, base: Duration) -> bool {
+    let span = base.as_secs() * JITTER_PERCENT / 100;
+    d.as_secs() >= base.as_secs() - span && d.as_secs() <= base.as_secs() + span
+  }
+
+  #[test]
+  fn jitter_bounds() {
+    let day = Duration::from_secs(24 * 3600);
+    let samples: Vec<Duration> = (0..200).map(|_| jitter(day)).collect();
+    assert!(samples.iter().all(|d| within(*d, day)), "{samples:?}");
+    // 不是固定值
+    assert!(samples.iter().any(|d| *d != samples[0]));
+    // 浮动不足一秒时原样返回
+    assert_eq!(jitter(Duration::from_secs(9)), Duration::from_secs(9));
+    assert_eq!(jitter(Duration::ZERO), Duration::ZERO);
+  }
+
+  #[test]
+  fn backoff_grows_and_caps() {
+    let interval = Duration::from_secs(24 * 3600);
+    for (failures, minutes) in [(0, 5), (1, 5), (2, 10), (3, 20), (5, 80)] {
+      let d = backoff(failures, interval);
+      assert!(within(d, Duration::from_secs(minutes * 60)), "{failures}: {d:?}");
+    }
+    // 不超过检查间隔（加抖动）；失败次数很大时不溢出
+    for failures in [10, 17, 40, u32::MAX] {
+      assert!(within(backoff(failures, interval), interval));
+    }
+    let short = Duration::from_secs(3600);
+    assert!(within(backoff(6, short), short));
+  }
+
+  fn asset(name: &str, kind: &str) -> UpdateAssetInfo {
+    UpdateAssetInfo {
+      name: name.into(), size: 1, kind: kind.into(), arch: "x64".into(),
+      direct_url: format!("https://example.com/{}", name), proxy_url: String::new(),
+    }
+  }
+
+  fn resp(install_kind: &str, assets: Vec<UpdateAssetInfo>) -> CheckUpdateResp {
+    CheckUpdateResp {
+      has_update: true, current: "1.0.0".into(), latest: "1.1.0".into(), release_name: String::new(), notes: String::new(),
+      html_url: String::new(), release_notes: Vec::new(), has_breaking: false, assets, arch: "x64".into(),
+      install_kind: install_kind.into(), channel: Default::default(), snoozed: false, rollback_versions: Vec::new(),
+    }
+  }
+
+  #[test]
+  fn preferred_assets() {
+    let all = || vec![
+      asset("a.rpm", "rpm"), asset("a.msi", "msi"), asset("a-setup.exe", "nsis"),
+      asset("a.deb", "deb"), asset("a.AppImage", "appimage"), asset("a.dmg", "dmg"),
+    ];
+    let pick = |kind: &str, assets: Vec<UpdateAssetInfo>| {
+      let r = resp(kind, assets);
+      preferred_asset(&r).map(|a| a.name.clone())
+    };
+    // 按资产顺序取第一个匹配的
+    assert_eq!(pick("windows", all()).as_deref(), Some("a.msi"));
+    assert_eq!(pick("appimage", all()).as_deref(), Some("a.AppImage"));
+    assert_eq!(pick("deb", all()).as_deref(), Some("a.deb"));
+    // 无法自动安装的方式（源码构建、解压运行等）不预下载
+    assert_eq!(pick("unknown", all()), None);
+    assert_eq!(pick("deb", vec![asset("a.AppImage", "appimage"), asset("a.rpm", "rpm")]), None);
+  }
+}
@@ -0,0 +1,233 @@
+// 更新偏好：通道、锁定/跳过的版本与“稍后提醒”，保存在应用配置目录的 updater.json
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+
+use crate::semver::{Channel, Policy, Version};
+
+const SETTINGS_FILE: &str = "updater.json";
+// 稍后提醒的默认间隔（小时）
+const DEFAULT_REMIND_HOURS: u64 = 24;
+// 后台检查的默认间隔与上限（小时）
+const DEFAULT_CHECK_HOURS: u64 = 24;
+const MAX_CHECK_HOURS: u64 = 24 * 30;
+
+// 读改写之间加锁，避免并发命令互相覆盖
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UpdateSettings {
+  pub channel: Channel,
+  // 锁定的最高版本：不提示高于该版本的更新
+  pub pinned: Option<String>,
+  // 跳过的版本
+  pub skipped: Vec<String>,
+  // 稍后提醒：在该时间（Unix 秒）之前，自动检查不提示 remind_version
+  pub remind_after: Option<u64>,
+  pub remind_version: Option<String>,
+  // 保留用于回滚的旧版本个数（默认 2，0 表示不保留）
+  pub rollback_keep: Option<usize>,
+  // 后台定时检查（默认开启）与检查间隔（小时，默认 24）
+  pub auto_check: Option<bool>,
+  pub check_interval_hours: Option<u64>,
+  // 发现更新后在后台预先下载安装包（默认关闭）
+  pub auto_download: Option<bool>,
+}
+
+fn now() -> u64 {
+  SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+  app.path().app_config_dir()
+    .map(|d| d.join(SETTINGS_FILE))
+    .map_err(|e| format!("config dir error: {e}"))
+}
+
+fn parse_version(v: &str) -> Result<Version, String> {
+  Version::parse(v).ok_or_else(|| format!("invalid version: {}", v))
+}
+
+impl UpdateSettings {
+  // 文件不存在或损坏时使用默认值（stable，无锁定/跳过）
+  pub fn load(app: &tauri::AppHandle) -> UpdateSettings {
+    settings_path(app).ok()
+      .and_then(|p| std::fs::read_to_string(p).ok())
+      .and_then(|s| serde_json::from_str(&s).ok())
+      .unwrap_or_default()
+  }
+
+  fn save(&self, app: &tauri::AppHandle) -> Result<(), String> {
+    let path = settings_path(app)?;
+    if let Some(dir) = path.parent() {
+      std::fs::create_dir_all(dir).map_err(|e| format!("create dir error: {e}"))?;
+    }
+    let s = serde_json::to_string_pretty(self).map_err(|e| format!("serialize error: {e}"))?;
+    std::fs::write(&path, s).map_err(|e| format!("write settings error: {e}"))
+  }
+
+  fn validate(&self) -> Result<(), String> {
+    if let Some(p) = &self.pinned { parse_version(p)?; }
+    for v in &self.skipped { parse_version(v)?; }
+    Ok(())
+  }
+
+  pub fn policy(&self) -> Policy {
+    Policy {
+      channel: self.channel,
+      pinned: self.pinned.as_deref().and_then(Version::parse),
+      skipped: self.skipped.iter().filter_map(|v| Version::parse(v)).collect(),
+    }
+  }
+
+  pub fn auto_check(&self) -> bool {
+    self.auto_check.unwrap_or(true)
+  }
+
+  pub fn check_interval_hours(&self) -> u64 {
+    self.check_interval_hours.unwrap_or(DEFAULT_CHECK_HOURS).clamp(1, MAX_CHECK_HOURS)
+  }
+
+  pub fn auto_download(&self) -> bool {
+    self.auto_download.unwrap_or(false)
+  }
+
+  pub fn rollback_keep(&self) -> usize {
+    self.rollback_keep.unwrap_or(super::rollback::DEFAULT_KEEP)
+  }
+
+  // 该版本是否处于“稍后提醒”期间
+  pub fn snoozed(&self, version: &str) -> bool {
+    self.snoozed_at(version, now())
+  }
+
+  fn snoozed_at(&self, version: &str, now: u64) -> bool {
+    let same = match (self.remind_version.as_deref().and_then(Version::parse), Version::parse(version)) {
+      (Some(a), Some(b)) => a == b,
+      _ => false,
+    };
+    same && self.remind_after.map(|t| now < t).unwrap_or(false)
+  }
+
+  // 同一版本的不同写法（v1.2.0 / 1.2.0）只记一次
+  fn skip(&mut self, version: &str) -> Result<(), String> {
+    let v = parse_version(version)?;
+    if !self.skipped.iter().any(|x| Version::parse(x).as_ref() == Some(&v)) {
+      self.skipped.push(version.trim().to_string());
+    }
+    Ok(())
+  }
+
+  fn remind_later(&mut self, version: &str, hours: Option<u64>, now: u64) -> Result<(), String> {
+    parse_version(version)?;
+    self.remind_version = Some(version.trim().to_string());
+    self.remind_after = Some(now + hours.unwrap_or(DEFAULT_REMIND_HOURS).max(1) * 3600);
+    Ok(())
+  }
+}
+
+fn update<F: FnOnce(&mut UpdateSettings) -> Result<(), String>>(app: &tauri::AppHandle, f: F) -> Result<UpdateSettings, String> {
+  let _guard = LOCK.lock().map_err(|_| "settings lock poisoned".to_string())?;
+  let mut s = UpdateSettings::load(app);
+  f(&mut s)?;
+  s.validate()?;
+  s.save(app)?;
+  Ok(s)
+}
+
+#[tauri::command]
+pub async fn get_update_settings(app: tauri::AppHandle) -> Result<UpdateSettings, String> {
+  Ok(UpdateSettings::load(&app))
+}
+
+// 整体保存（通道、锁定版本等）；关闭预下载时取消正在进行的后台下载
+#[tauri::command]
+pub async fn set_update_settings(app: tauri::AppHandle, settings: UpdateSettings) -> Result<UpdateSettings, String> {
+  let saved = tauri::async_runtime::spawn_blocking(move || update(&app, |s| { *s = settings; Ok(()) }))
+    .await
+    .map_err(|e| format!("join error: {e}"))??;
+  if !saved.auto_download() { super::download::cancel(true); }
+  Ok(saved)
+}
+
+// 跳过某个版本：之后只提示比它更高的版本
+#[tauri::command]
+pub async fn skip_update_version(app: tauri::AppHandle, version: String) -> Result<UpdateSettings, String> {
+  tauri::async_runtime::spawn_blocking(move || update(&app, |s| s.skip(&version)))
+    .await
+    .map_err(|e| format!("join error: {e}"))?
+}
+
+// 稍后提醒：hours 小时内自动检查不再提示该版本，手动检查不受影响
+#[tauri::command]
+pub async fn remind_update_later(app: tauri::AppHandle, version: String, hours: Option<u64>) -> Result<UpdateSettings, String> {
+  tauri::async_runtime::spawn_blocking(move || update(&app, |s| s.remind_later(&version, hours, now())))
+    .await
+    .map_err(|e| format!("join error: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn snooze() {
+    let mut s = UpdateSettings::default();
+    assert!(!s.snoozed_at("1.2.0", 0));
+    s.remind_later(" v1.2.0 ", None, 1000).unwrap();
+    assert_eq!(s.remind_version.as_deref(), Some("v1.2.0"));
+    assert_eq!(s.remind_after, Some(1000 + 24 * 3600));
+    assert!(s.snoozed_at("1.2.0", 1000));
+    assert!(s.snoozed_at("V1.2", 1000 + 24 * 3600 - 1));
+    // 到期或换了版本后照常提示
+    assert!(!s.snoozed_at("1.2.0", 1000 + 24 * 3600));
+    assert!(!s.snoozed_at("1.2.1", 1000));
+    assert!(!s.snoozed_at("1.2.0-beta.1", 1000));
+    assert!(!s.snoozed_at("garbage", 1000));
+
+    // 至少一小时；无效版本不改动原设置
+    s.remind_later("1.3.0", Some(0), 50).unwrap();
+    assert_eq!(s.remind_after, Some(50 + 3600));
+    assert!(s.remind_later("next", Some(5), 0).is_err());
+    assert_eq!(s.remind_version.as_deref(), Some("1.3.0"));
+    s.remind_after = None;
+    assert!(!s.snoozed_at("1.3.0", 0));
+  }
+
+  #[test]
+  fn skip_dedup() {
+    let mut s = UpdateSettings::default();
+    s.skip("1.2.0").unwrap();
+    s.skip(" v1.2.0").unwrap();
+    s.skip("1.2").unwrap();
+    s.skip("1.3.0-beta.1").unwrap();
+    assert_eq!(s.skipped, ["1.2.0", "1.3.0-beta.1"]);
+    assert!(s.skip("latest").is_err());
+    assert_eq!(s.skipped.len(), 2);
+    assert_eq!(s.policy().skipped.len(), 2);
+  }
+
+  #[test]
+  fn validation() {
+    let mut s = UpdateSettings { pinned: Some("v1.4".into()), skipped: vec!["1.2.0".into()], ..Default::default() };
+    assert!(s.validate().is_ok());
+    assert_eq!(s.policy().pinned, Version::parse("1.4.0"));
+    s.pinned = Some("1.x".into());
+    assert!(s.validate().unwrap_err().contains("invalid version: 1.x"));
+    s.pinned = None;
+    s.skipped.push("".into());
+    assert!(s.validate().is_err());
+
+    // 检查间隔限制在 1 小时到 30 天
+    let mut s = UpdateSettings::default();
+    assert_eq!((s.check_interval_hours(), s.auto_check(), s.auto_download()), (24, true, false));
+    s.check_interval_hours = Some(0);
+    assert_eq!(s.check_interval_hours(), 1);
+    s.check_interval_hours = Some(100_000);
+    assert_eq!(s.check_interval_hours(), MAX_CHECK_HOURS);
+  }
+}
@@ -0,0 +1,268 @@
+// 更新包校验：下载可能经过第三方镜像，必须以发布者签名为准
+// 发布时随安装包上传：
+//   - SHA256SUMS + SHA256SUMS.minisig（对清单签名，清单中列出各安装包的 SHA-256），或
+//   - <安装包>.minisig（直接对安装包签名，需 prehashed 格式，即 minisign 默认格式），可附 <安装包>.sha256
+// 公钥在构建时通过环境变量 FLYMD_UPDATE_PUBKEY 写入（minisign.pub 的第二行）；未配置公钥的构建拒绝所有更新包。
+
+use minisign_verify::{PublicKey, Signature};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use super::mirror::Mirror;
+
+const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("FLYMD_UPDATE_PUBKEY");
+
+const SUMS_FILE: &str = "SHA256SUMS";
+
+// 开始下载前调用：未配置或配置错误的公钥应当在下载前就报错，而不是下载完才失败
+pub fn public_key() -> Result<PublicKey, String> {
+  parse_public_key(UPDATE_PUBLIC_KEY)
+}
+
+fn parse_public_key(raw: Option<&str>) -> Result<PublicKey, String> {
+  let raw = raw.map(str::trim).filter(|s| !s.is_empty())
+    .ok_or_else(|| "this build has no update signing key; refusing to install unverified updates".to_string())?;
+  // 兼容直接粘贴整个 minisign.pub（含 untrusted comment 行）
+  let b64 = raw.lines().map(str::trim).rfind(|l| !l.is_empty() && !l.starts_with("untrusted comment:")).unwrap_or(raw);
+  PublicKey::from_base64(b64).map_err(|e| format!("invalid update public key: {e}"))
+}
+
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+  let mut f = std::fs::File::open(path).map_err(|e| format!("open {} error: {e}", path.display()))?;
+  let mut h = Sha256::new();
+  let mut buf = vec![0u8; 64 * 1024];
+  loop {
+    let n = f.read(&mut buf).map_err(|e| format!("read {} error: {e}", path.display()))?;
+    if n == 0 { break; }
+    h.update(&buf[..n]);
+  }
+  Ok(hex::encode(h.finalize()))
+}
+
+fn verify_file_signature(pk: &PublicKey, sig: &Signature, path: &Path) -> Result<(), String> {
+  let mut verifier = pk.verify_stream(sig).map_err(|e| format!("signature error: {e}"))?;
+  let mut f = std::fs::File::open(path).map_err(|e| format!("open {} error: {e}", path.display()))?;
+  let mut buf = vec![0u8; 64 * 1024];
+  loop {
+    let n = f.read(&mut buf).map_err(|e| format!("read {} error: {e}", path.display()))?;
+    if n == 0 { break; }
+    verifier.update(&buf[..n]);
+  }
+  verifier.finalize().map_err(|e| format!("signature mismatch: {e}"))
+}
+
+// SHA256SUMS 行格式："<hex>  <name>" 或 "<hex> *<name>"
+fn lookup_sum<'a>(sums: &'a str, name: &str) -> Option<&'a str> {
+  sums.lines().find_map(|line| {
+    let (hash, file) = line.trim().split_once(char::is_whitespace)?;
+    let file = file.trim_start().trim_start_matches('*');
+    let file = file.rsplit('/').next().unwrap_or(file);
+    (file == name).then_some(hash)
+  })
+}
+
+// 同一 Release 下的其他资产地址：替换最后一段路径
+fn sibling_url(asset: &url::Url, name: &str) -> Option<url::Url> {
+  let mut u = asset.clone();
+  u.path_segments_mut().ok()?.pop().push(name);
+  Some(u)
+}
+
+// 获取清单/签名等小文件：依次尝试各地址，均返回 404 时视为未发布
+async fn fetch_small(client: &reqwest::Client, urls: &[url::Url]) -> Result<Option<Vec<u8>>, String> {
+  let mut last_err = None;
+  let mut missing = false;
+  for u in urls {
+    match client.get(u.clone()).send().await {
+      Ok(r) if r.status() == reqwest::StatusCode::NOT_FOUND => missing = true,
+      Ok(r) if r.status().is_success() => match r.bytes().await {
+        Ok(b) => return Ok(Some(b.to_vec())),
+        Err(e) => last_err = Some(format!("read {} error: {e}", u)),
+      },
+      Ok(r) => last_err = Some(format!("fetch {}: http status {}", u, r.status())),
+      Err(e) => last_err = Some(format!("fetch {} error: {e}", u)),
+    }
+  }
+  match last_err {
+    Some(e) if !missing => Err(e),
+    _ => Ok(None),
+  }
+}
+
+// 校验已下载的安装包，返回其 SHA-256；asset_url 为 GitHub 上的原始地址，附属文件按 mirrors 的顺序获取
+pub async fn verify_download(client: &reqwest::Client, pk: &PublicKey, asset_url: &url::Url, file: &Path, mirrors: &[Mirror]) -> Result<String, String> {
+  let name = asset_url.path_segments().and_then(|mut s| s.next_back()).map(|s| {
+    percent_encoding::percent_decode_str(s).decode_utf8_lossy().to_string()
+  }).ok_or_else(|| "invalid asset url".to_string())?;
+  let routes = |sibling: &str| -> Vec<url::Url> {
+    let Some(direct) = sibling_url(asset_url, sibling) else { return Vec::new() };
+    mirrors.iter().filter_map(|m| m.apply(&direct)).collect()
+  };
+  let path = file.to_path_buf();
+  let actual = tauri::async_runtime::spawn_blocking(move || sha256_file(&path))
+    .await
+    .map_err(|e| format!("join error: {e}"))??;
+
+  // 1. 签名的 SHA256SUMS 清单
+  if let Some(sums) = fetch_small(client, &routes(SUMS_FILE)).await? {
+    let sig = fetch_small(client, &routes(&format!("{}.minisig", SUMS_FILE))).await?
+      .ok_or_else(|| format!("{} is not signed", SUMS_FILE))?;
+    let sig = Signature::decode(&String::from_utf8_lossy(&sig)).map_err(|e| format!("invalid signature file: {e}"))?;
+    pk.verify(&sums, &sig, false).map_err(|e| format!("{} signature mismatch: {e}", SUMS_FILE))?;
+    let sums = String::from_utf8_lossy(&sums);
+    let expected = lookup_sum(&sums, &name).ok_or_else(|| format!("{} not listed in {}", name, SUMS_FILE))?;
+    if !expected.eq_ignore_ascii_case(&actual) {
+      return Err(format!("checksum mismatch for {}: expected {}, got {}", name, expected, actual));
+    }
+    return Ok(actual);
+  }
+
+  // 2. 安装包自身的签名（及可选的 .sha256）
+  let sig = fetch_small(client, &routes(&format!("{}.minisig", name))).await?
+    .ok_or_else(|| format!("no signature published for {}", name))?;
+  let sig = Signature::decode(&String::from_utf8_lossy(&sig)).map_err(|e| format!("invalid signature file: {e}"))?;
+  let path = file.to_path_buf();
+  let pk = pk.clone();
+  tauri::async_runtime::spawn_blocking(move || verify_file_signature(&pk, &sig, &path))
+    .await
+    .map_err(|e| format!("join error: {e}"))??;
+  if let Some(h) = fetch_small(client, &routes(&format!("{}.sha256", name))).await? {
+    let text = String::from_utf8_lossy(&h);
+    let expected = text.split_whitespace().next().unwrap_or("");
+    if !expected.eq_ignore_ascii_case(&actual) {
+      return Err(format!("checksum mismatch for {}: expected {}, got {}", name, expected, actual));
+    }
+  }
+  Ok(actual)
+}
+
+// 已通过校验的安装包（路径 -> SHA-256），启动安装前再次比对，防止文件被替换
+fn verified() -> &'static Mutex<HashMap<PathBuf, String>> {
+  static V: OnceLock<Mutex<HashMap<PathBuf, String>>> = OnceLock::new();
+  V.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub fn remember(path: &Path, sha256: String) {
+  let key = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+  if let Ok(mut m) = verified().lock() { m.insert(key, sha256); }
+}
+
+pub fn ensure_verified(path: &Path) -> Result<(), String> {
+  let key = std::fs::canonicalize(path).map_err(|e| format!("installer {} error: {e}", path.display()))?;
+  let expected = verified().lock().ok().and_then(|m| m.get(&key).cloned())
+    .ok_or_else(|| format!("{} was not downloaded and verified by the updater", path.display()))?;
+  if sha256_file(&key)? != expected {
+    return Err(format!("{} changed after verification", path.display()));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_http::{serve, Response};
+  use std::sync::Arc;
+
+  // 测试用密钥对生成的签名（私钥不在仓库中）
+  const PUBKEY: &str = "RWQBAgMEBQYHCJCE3JPjQ59i0jBnQzwlFJPi5qSYBWEOIO6tPGhZfL81";
+  const INSTALLER: &[u8] = b"flymd installer\n";
+  const NAME: &str = "flymd_1.0.0_amd64.AppImage";
+  const SUMS: &str = "0000000000000000000000000000000000000000000000000000000000000000  other.exe\n87e361f4be62b2e293c2285cf450c5145af717d9bd4910f37ab523d773557c79 *dist/flymd_1.0.0_amd64.AppImage\n";
+  const SUMS_SIG: &str = "untrusted comment: signature from minisign secret key\nRUQBAgMEBQYHCAkBvt4F10T7TgJA4WPK2uc1hY1mglt0FTiSNx+hq64P4m0s+UJW+yBDZlPzsbsajgWLYR+Hp3GpSKcvuvoLsQg=\ntrusted comment: timestamp:1700000000\tfile:SHA256SUMS\nbWlu7F7DjaygVYD7KeaGWe44QgvbdPo89tZGohr1morBw4l1CW+0aX+5v0uL5ny68CgNvPL/1luqjqnH0P5uCw==\n";
+  const FILE_SIG: &str = "untrusted comment: signature from minisign secret key\nRUQBAgMEBQYHCMZSiUqNQPRyHtBqRdDQVdz0sllR5Cl9pQY21IjBzU2B4hUr6kLT36gn4k6nck/jGiD2ayTeDEwMvxIxBWlRHAY=\ntrusted comment: timestamp:1700000000\tfile:flymd_1.0.0_amd64.AppImage\nDGpTUesSwITAgC340/vIyPdQUxElMhYEo3ERdhXzmzC5hlyxhFJLAngibINqU0hVw1I0PG/0cOwkNPY7KbcpAw==\n";
+  // 只列出 other.exe 的清单
+  const OTHER_SUMS: &str = "0000000000000000000000000000000000000000000000000000000000000000  other.exe\n";
+  const OTHER_SUMS_SIG: &str = "untrusted comment: signature from minisign secret key\nRUQBAgMEBQYHCLmiphMxhLmdQpdmpSoBxhVzr8lWcodbYf8hIvE8M2jOK3kV4spC0VtxbJQH7sHZ0gOXWePYJnEXBhHqcLREfAk=\ntrusted comment: timestamp:1700000000\tfile:SHA256SUMS\nsPwF4DAjteSNzM69xRullAbP3Rindy7H7JLF3jcoUiXvU2KAyDJ0KDsizj+oEHK1lzcF+jmZT60YyaRB4CGjDw==\n";
+  const SHA: &str = "87e361f4be62b2e293c2285cf450c5145af717d9bd4910f37ab523d773557c79";
+
+  #[test]
+  fn sums_lookup() {
+    let sums = "abc  a.exe\r\nDEF *b.AppImage\n123  dist/sub/c.dmg\n\nbroken\n";
+    assert_eq!(lookup_sum(sums, "a.exe"), Some("abc"));
+    assert_eq!(lookup_sum(sums, "b.AppImage"), Some("DEF"));
+    assert_eq!(lookup_sum(sums, "c.dmg"), Some("123"));
+    assert_eq!(lookup_sum(sums, "d.exe"), None);
+    assert_eq!(lookup_sum(sums, "A.exe"), None);
+    assert_eq!(lookup_sum(sums, "broken"), None);
+  }
+
+  #[test]
+  fn public_keys() {
+    let bare = parse_public_key(Some(PUBKEY)).unwrap();
+    let full = format!("untrusted comment: minisign public key 0807060504030201\n{}\n", PUBKEY);
+    assert_eq!(parse_public_key(Some(&full)).unwrap(), bare);
+    assert!(parse_public_key(Some(&format!("  {}  ", PUBKEY))).is_ok());
+    assert!(parse_public_key(None).unwrap_err().contains("no update signing key"));
+    assert!(parse_public_key(Some(" \n")).is_err());
+    assert!(parse_public_key(Some("untrusted comment: x\nnot-base64")).unwrap_err().contains("invalid update public key"));
+  }
+
+  // 按文件名应答发布资产；缺失的返回 404
+  async fn verify(assets: Vec<(String, String)>, installer: &[u8]) -> Result<String, String> {
+    let assets = Arc::new(assets);
+    let server = serve(move |req| {
+      let name = req.path.rsplit('/').next().unwrap_or("");
+      match assets.iter().find(|(n, _)| n == name) {
+        Some((_, body)) => Response::new(200, "application/octet-stream", body.clone()),
+        None => Response::new(404, "text/plain", "not found"),
+      }
+    });
+    let dir = std::env::temp_dir().join(format!("flymd-verify-{}-{:?}", std::process::id(), std::thread::current().id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join(format!("{}.part", NAME));
+    std::fs::write(&file, installer).unwrap();
+    let origin = url::Url::parse(&format!("https://github.com/o/r/releases/download/v1.0.0/{}", NAME)).unwrap();
+    let mirrors = [Mirror(server.url("/files/{name}"))];
+    let pk = parse_public_key(Some(PUBKEY)).unwrap();
+    let r = verify_download(&reqwest::Client::new(), &pk, &origin, &file, &mirrors).await;
+    let _ = std::fs::remove_dir_all(&dir);
+    r
+  }
+
+  fn asset(name: &str, body: &str) -> (String, String) {
+    (name.to_string(), body.to_string())
+  }
+
+  #[tokio::test]
+  async fn signed_sums() {
+    let sums = || asset("SHA256SUMS", SUMS);
+    let sig = || asset("SHA256SUMS.minisig", SUMS_SIG);
+    assert_eq!(verify(vec![sums(), sig()], INSTALLER).await.unwrap(), SHA);
+
+    let err = verify(vec![asset("SHA256SUMS", &SUMS.replace("0000", "1111")), sig()], INSTALLER).await.unwrap_err();
+    assert!(err.contains("SHA256SUMS signature mismatch"), "{err}");
+
+    let err = verify(vec![sums()], INSTALLER).await.unwrap_err();
+    assert!(err.contains("is not signed"), "{err}");
+
+    let err = verify(vec![sums(), sig()], b"flymd installer, modified\n").await.unwrap_err();
+    assert!(err.contains("checksum mismatch"), "{err}");
+
+    let err = verify(vec![asset("SHA256SUMS", OTHER_SUMS), asset("SHA256SUMS.minisig", OTHER_SUMS_SIG)], INSTALLER).await.unwrap_err();
+    assert!(err.contains("not listed"), "{err}");
+
+    // 清单存在时以清单为准，不退回到安装包自身的签名
+    let err = verify(vec![sums(), asset("SHA256SUMS.minisig", FILE_SIG), asset(&format!("{}.minisig", NAME), FILE_SIG)], INSTALLER).await.unwrap_err();
+    assert!(err.contains("signature mismatch"), "{err}");
+  }
+
+  #[tokio::test]
+  async fn signed_installer() {
+    let sig = || asset(&format!("{}.minisig", NAME), FILE_SIG);
+    assert_eq!(verify(vec![sig()], INSTALLER).await.unwrap(), SHA);
+
+    let err = verify(vec![sig()], b"flymd installer, modified\n").await.unwrap_err();
+    assert!(err.contains("signature mismatch"), "{err}");
+
+    let err = verify(Vec::new(), INSTALLER).await.unwrap_err();
+    assert!(err.contains("no signature published"), "{err}");
+
+    let err = verify(vec![sig(), asset(&format!("{}.sha256", NAME), "abababababababababababababababababababababababababababababababab  x\n")], INSTALLER).await.unwrap_err();
+    assert!(err.contains("checksum mismatch"), "{err}");
+    let sha = format!("{}  {}\n", SHA, NAME);
+    assert_eq!(verify(vec![sig(), asset(&format!("{}.sha256", NAME), &sha)], INSTALLER).await.unwrap(), SHA);
+  }
+}
@@ -0,0 +1,462 @@
+// 上传策略：按魔数嗅探真实类型、类型白名单、大小上限、SVG 清洗
+// 前端传入的 content_type 仅作参考（剪贴板 Blob 常常为空），最终以文件头为准，
+// 避免伪装成图片的 HTML/SVG 以 image/png 发布到图床。
+
+pub const DEFAULT_MAX_BYTES: u64 = 20 * 1024 * 1024;
+
+pub const DEFAULT_ALLOWED_TYPES: &[&str] = &[
+  "image/png",
+  "image/jpeg",
+  "image/gif",
+  "image/webp",
+  "image/avif",
+  "image/bmp",
+  "image/x-icon",
+  "image/svg+xml",
+  "application/pdf",
+];
+
+// 根据文件头判断真实 MIME；无法识别返回 None
+pub fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+  if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) { return Some("image/png"); }
+  if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) { return Some("image/jpeg"); }
+  if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") { return Some("image/gif"); }
+  if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" { return Some("image/webp"); }
+  if is_avif(bytes) { return Some("image/avif"); }
+  // BMP：BM + 文件大小 + 4 字节保留位（为 0）；ICO：保留位 0、类型 1、图像数不为 0
+  if bytes.len() >= 14 && bytes.starts_with(b"BM") && bytes[6..10] == [0, 0, 0, 0] { return Some("image/bmp"); }
+  if bytes.len() >= 6 && bytes.starts_with(&[0, 0, 1, 0]) && bytes[4..6] != [0, 0] { return Some("image/x-icon"); }
+  if bytes.starts_with(b"%PDF-") { return Some("application/pdf"); }
+  if let Some(m) = sniff_media(bytes) { return Some(m); }
+  if let Some(m) = sniff_archive(bytes) { return Some(m); }
+  if looks_like_svg(bytes) { return Some("image/svg+xml"); }
+  None
+}
+
+// 音视频：ID3/MPEG 帧、ISO-BMFF（mp4/m4a/mov）、RIFF WAVE、Ogg、FLAC、Matroska/WebM
+fn sniff_media(bytes: &[u8]) -> Option<&'static str> {
+  if bytes.starts_with(b"ID3") { return Some("audio/mpeg"); }
+  // MPEG Layer III 帧同步：11 位 1 + layer 位为 01
+  if bytes.len() >= 2 && bytes[0] == 0xFF && (bytes[1] & 0xE0) == 0xE0 && (bytes[1] >> 1) & 0x03 == 0x01 { return Some("audio/mpeg"); }
+  if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+    return match &bytes[8..12] {
+      b"M4A " | b"M4B " => Some("audio/mp4"),
+      b"qt  " => Some("video/quicktime"),
+      b"avif" | b"avis" | b"heic" | b"heix" | b"mif1" | b"msf1" => None,
+      _ => Some("video/mp4"),
+    };
+  }
+  if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WAVE" { return Some("audio/wav"); }
+  if bytes.starts_with(b"OggS") { return Some("audio/ogg"); }
+  if bytes.starts_with(b"fLaC") { return Some("audio/flac"); }
+  if bytes.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+    // EBML 头中的 DocType 区分 webm 与 mkv
+    let head = &bytes[..bytes.len().min(64)];
+    return if head.windows(4).any(|w| w == b"webm") { Some("video/webm") } else { Some("video/x-matroska") };
+  }
+  None
+}
+
+// 压缩包与 Office 文档（OOXML 本质是 zip，按内部目录区分）
+fn sniff_archive(bytes: &[u8]) -> Option<&'static str> {
+  if bytes.starts_with(b"PK\x03\x04") {
+    let head = &bytes[..bytes.len().min(64 * 1024)];
+    let has = |needle: &[u8]| head.windows(needle.len()).any(|w| w == needle);
+    if has(b"word/") { return Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document"); }
+    if has(b"xl/") { return Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"); }
+    if has(b"ppt/") { return Some("application/vnd.openxmlformats-officedocument.presentationml.presentation"); }
+    return Some("application/zip");
+  }
+  if bytes.starts_with(&[0x37, 0x7A, 0xBC, 0xAF, 0x27, 0x1C]) { return Some("application/x-7z-compressed"); }
+  if bytes.starts_with(b"Rar!\x1A\x07") { return Some("application/vnd.rar"); }
+  if bytes.starts_with(&[0x1F, 0x8B]) { return Some("application/gzip"); }
+  // 旧版 Office（OLE 复合文档），具体类型需结合扩展名
+  if bytes.starts_with(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1]) { return Some("application/x-ole-storage"); }
+  None
+}
+
+// ISO-BMFF：ftyp 盒的主品牌或兼容品牌中包含 avif/avis
+fn is_avif(bytes: &[u8]) -> bool {
+  if bytes.len() < 16 || &bytes[4..8] != b"ftyp" { return false; }
+  let box_len = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+  let end = box_len.clamp(16, bytes.len());
+  let is_brand = |b: &[u8]| b == b"avif" || b == b"avis";
+  if is_brand(&bytes[8..12]) { return true; }
+  // 跳过 minor_version（12..16），其后每 4 字节一个兼容品牌
+  bytes[16..end].chunks_exact(4).any(is_brand)
+}
+
+// 文本头部（跳过 BOM、XML 声明、注释、空白）以 <svg 开头，且不含 HTML 根元素
+fn looks_like_svg(bytes: &[u8]) -> bool {
+  let head = &bytes[..bytes.len().min(4096)];
+  let text = match std::str::from_utf8(head) {
+    Ok(s) => s.to_string(),
+    // 截断可能切断多字节字符，退化为有损解码
+    Err(e) if e.error_len().is_none() => String::from_utf8_lossy(head).to_string(),
+    Err(_) => return false,
+  };
+  let lower = text.trim_start_matches('\u{feff}').to_ascii_lowercase();
+  if lower.contains("<html") || lower.contains("<body") { return false; }
+  let mut rest = lower.trim_start();
+  loop {
+    if rest.starts_with("<?xml") {
+      match rest.find("?>") { Some(i) => rest = rest[i + 2..].trim_start(), None => return false }
+    } else if rest.starts_with("<!--") {
+      match rest.find("-->") { Some(i) => rest = rest[i + 3..].trim_start(), None => return false }
+    } else if rest.starts_with("<!doctype") {
+      match rest.find('>') { Some(i) => rest = rest[i + 1..].trim_start(), None => return false }
+    } else {
+      break;
+    }
+  }
+  rest.starts_with("<svg")
+}
+
+#[derive(Debug, Clone)]
+pub struct UploadPolicy {
+  pub allowed_types: Vec<String>,
+  pub max_bytes: u64,
+}
+
+impl Default for UploadPolicy {
+  fn default() -> Self {
+    Self {
+      allowed_types: DEFAULT_ALLOWED_TYPES.iter().map(|s| s.to_string()).collect(),
+      max_bytes: DEFAULT_MAX_BYTES,
+    }
+  }
+}
+
+// 通过策略校验后的上传内容
+#[derive(Debug)]
+pub struct CheckedUpload {
+  pub content_type: String,
+  pub bytes: Vec<u8>,
+}
+
+impl UploadPolicy {
+  // 由图床配置（profile）构建；缺省项回退到默认值
+  pub fn from_profile(allowed_types: Option<&[String]>, max_bytes: Option<u64>) -> Self {
+    let mut p = Self::default();
+    if let Some(list) = allowed_types {
+      let list: Vec<String> = list.iter().map(|s| s.trim().to_ascii_lowercase()).filter(|s| !s.is_empty()).collect();
+      if !list.is_empty() { p.allowed_types = list; }
+    }
+    if let Some(m) = max_bytes { if m > 0 { p.max_bytes = m; } }
+    p
+  }
+
+  pub fn allows(&self, mime: &str) -> bool {
+    self.allowed_types.iter().any(|t| t == mime)
+  }
+
+  // 嗅探 -> 白名单 -> 大小 -> SVG 清洗；错误信息统一以 "upload policy:" 开头，前端据此不再走兜底上传
+  pub fn enforce(&self, bytes: Vec<u8>, declared: Option<&str>) -> Result<CheckedUpload, String> {
+    let sniffed = sniff_mime(&bytes);
+    self.check(bytes, sniffed, declared)
+  }
+
+  // 使用调用方已嗅探出的类型进行校验（附件可结合文件名细化类型）
+  pub fn check(&self, bytes: Vec<u8>, sniffed: Option<&str>, declared: Option<&str>) -> Result<CheckedUpload, String> {
+    if bytes.is_empty() { return Err("upload policy: empty file".into()); }
+    if bytes.len() as u64 > self.max_bytes {
+      return Err(format!("upload policy: file too large ({} bytes, limit {} bytes)", bytes.len(), self.max_bytes));
+    }
+    let declared = declared.map(|s| s.trim().to_ascii_lowercase()).filter(|s| !s.is_empty());
+    let sniffed = sniffed.ok_or_else(|| {
+      format!("upload policy: unrecognized content (declared {})", declared.as_deref().unwrap_or("none"))
+    })?;
+    if !self.allows(sniffed) {
+      return Err(format!("upload policy: type {} not allowed", sniffed));
+    }
+    let bytes = if sniffed == "image/svg+xml" {
+      let text = String::from_utf8(bytes).map_err(|_| "upload policy: svg is not valid utf-8".to_string())?;
+      sanitize_svg(&text)?.into_bytes()
+    } else {
+      bytes
+    };
+    Ok(CheckedUpload { content_type: sniffed.to_string(), bytes })
+  }
+}
+
+// 会执行脚本或嵌入外部文档的元素，连同内容整体移除
+// （style 中的 @import、url() 可加载外部资源）；按本地名比较，带前缀的 svg:script 同样移除
+const SVG_DROP_ELEMENTS: &[&str] = &["script", "style", "foreignobject", "iframe", "embed", "object", "handler", "listener"];
+// 可携带 URL 的属性（本地名，xlink:href 及任意前缀的 href 都按 href 处理）
+const SVG_URL_ATTRS: &[&str] = &["href", "src", "action", "formaction", "from", "to", "values"];
+
+// 去掉命名空间前缀：svg:script -> script；前缀可以绑定到任意名字，不能按前缀判断
+fn local_name(name: &str) -> &str {
+  name.rsplit(':').next().unwrap_or(name)
+}
+
+// SVG 清洗：去除脚本元素、事件属性、javascript:/vbscript:/data:text/html 链接、DOCTYPE（防实体注入）
+pub fn sanitize_svg(src: &str) -> Result<String, String> {
+  let mut out = String::with_capacity(src.len());
+  let mut rest = src;
+  // 正在跳过的危险元素：(元素名, 嵌套深度)
+  let mut skipping: Option<(String, usize)> = None;
+  while let Some(lt) = rest.find('<') {
+    if skipping.is_none() { out.push_str(&rest[..lt]); }
+    rest = &rest[lt..];
+    if rest.starts_with("<!--") {
+      let end = rest.find("-->").map(|i| i + 3).unwrap_or(rest.len());
+      rest = &rest[end..];
+      continue;
+    }
+    if rest.starts_with("<![CDATA[") {
+      let end = rest.find("]]>").map(|i| i + 3).unwrap_or(rest.len());
+      if skipping.is_none() { out.push_str(&rest[..end]); }
+      rest = &rest[end..];
+      continue;
+    }
+    if rest.starts_with("<?") {
+      let end = rest.find("?>").map(|i| i + 2).unwrap_or(rest.len());
+      if skipping.is_none() { out.push_str(&rest[..end]); }
+      rest = &rest[end..];
+      continue;
+    }
+    if rest.starts_with("<!") {
+      // DOCTYPE 及内部子集直接丢弃
+      let end = doctype_end(rest);
+      rest = &rest[end..];
+      continue;
+    }
+    let end = tag_end(rest).ok_or_else(|| "upload policy: malformed svg tag".to_string())?;
+    let tag = &rest[..end];
+    rest = &rest[end..];
+    let closing = tag.starts_with("</");
+    let name = tag_name(tag);
+    let self_closing = tag.ends_with("/>");
+    if let Some((skip_name, depth)) = skipping.as_mut() {
+      if name == *skip_name {
+        if closing { *depth -= 1; } else if !self_closing { *depth += 1; }
+        if *depth == 0 { skipping = None; }
+      }
+      continue;
+    }
+    if SVG_DROP_ELEMENTS.contains(&local_name(&name)) {
+      if !closing && !self_closing { skipping = Some((name, 1)); }
+      continue;
+    }
+    if closing { out.push_str(tag); } else { out.push_str(&clean_tag(tag)); }
+  }
+  if skipping.is_none() { out.push_str(rest); }
+  if !out.to_ascii_lowercase().contains("<svg") {
+    return Err("upload policy: svg root element missing".into());
+  }
+  Ok(out)
+}
+
+// DOCTYPE 可能带 [ ... ] 内部子集
+fn doctype_end(s: &str) -> usize {
+  let mut in_subset = false;
+  for (i, c) in s.char_indices() {
+    match c {
+      '[' => in_subset = true,
+      ']' => in_subset = false,
+      '>' if !in_subset => return i + 1,
+      _ => {}
+    }
+  }
+  s.len()
+}
+
+// 查找标签结束位置（跳过引号内的 '>'）
+fn tag_end(s: &str) -> Option<usize> {
+  let mut quote: Option<char> = None;
+  for (i, c) in s.char_indices().skip(1) {
+    match quote {
+      Some(q) => if c == q { quote = None },
+      None => match c {
+        '"' | '\'' => quote = Some(c),
+        '>' => return Some(i + 1),
+        _ => {}
+      },
+    }
+  }
+  None
+}
+
+fn tag_name(tag: &str) -> String {
+  tag.trim_start_matches('<').trim_start_matches('/')
+    .chars()
+    .take_while(|c| !c.is_whitespace() && *c != '>' && *c != '/')
+    .collect::<String>()
+    .to_ascii_lowercase()
+}
+
+// 重建开始标签，仅保留安全属性
+fn clean_tag(tag: &str) -> String {
+  let inner = tag.trim_start_matches('<').trim_end_matches('>');
+  let (inner, self_closing) = match inner.strip_suffix('/') { Some(s) => (s, true), None => (inner, false) };
+  let name_len = inner.find(|c: char| c.is_whitespace()).unwrap_or(inner.len());
+  let (name, mut attrs) = inner.split_at(name_len);
+  let mut out = format!("<{}", name);
+  loop {
+    attrs = attrs.trim_start();
+    if attrs.is_empty() { break; }
+    let key_len = attrs.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(attrs.len());
+    let key = &attrs[..key_len];
+    attrs = attrs[key_len..].trim_start();
+    let mut raw_value: Option<&str> = None;
+    let mut value = "";
+    if let Some(after_eq) = attrs.strip_prefix('=') {
+      let after_eq = after_eq.trim_start();
+      let (v, consumed) = match after_eq.chars().next() {
+        Some(q @ ('"' | '\'')) => match after_eq[1..].find(q) {
+          Some(i) => (&after_eq[1..i + 1], i + 2),
+          None => (&after_eq[1..], after_eq.len()),
+        },
+        _ => {
+          let l = after_eq.find(char::is_whitespace).unwrap_or(after_eq.len());
+          (&after_eq[..l], l)
+        }
+      };
+      value = v;
+      raw_value = Some(&after_eq[..consumed]);
+      attrs = &after_eq[consumed..];
+    }
+    if key.is_empty() {
+      // 非法片段：跳过一个字符避免死循环
+      attrs = attrs.get(1..).unwrap_or("");
+      continue;
+    }
+    let lk = key.to_ascii_lowercase();
+    let lk = local_name(&lk);
+    if lk.starts_with("on") { continue; }
+    if SVG_URL_ATTRS.contains(&lk) && is_dangerous_url(value) { continue; }
+    out.push(' ');
+    out.push_str(key);
+    if let Some(rv) = raw_value { out.push('='); out.push_str(rv); }
+  }
+  if self_closing { out.push_str(" />"); } else { out.push('>'); }
+  out
+}
+
+// 解码属性值中的字符引用（&#106; / &#x6A; 及常见命名实体），无法识别的原样保留
+fn decode_entities(v: &str) -> String {
+  let mut out = String::with_capacity(v.len());
+  let mut rest = v;
+  while let Some(i) = rest.find('&') {
+    out.push_str(&rest[..i]);
+    rest = &rest[i..];
+    let decoded = rest[1..].find(';').filter(|&j| j > 0 && j <= 32).and_then(|j| {
+      let name = &rest[1..j + 1];
+      let c = if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+          Some(hex) => u32::from_str_radix(hex, 16).ok(),
+          None => num.parse().ok(),
+        };
+        code.and_then(char::from_u32)
+      } else {
+        match name.to_ascii_lowercase().as_str() {
+          "amp" => Some('&'),
+          "lt" => Some('<'),
+          "gt" => Some('>'),
+          "quot" => Some('"'),
+          "apos" => Some('\''),
+          "colon" => Some(':'),
+          "tab" => Some('\t'),
+          "newline" => Some('\n'),
+          _ => None,
+        }
+      };
+      c.map(|c| (c, j + 2))
+    });
+    match decoded {
+      Some((c, len)) => { out.push(c); rest = &rest[len..]; }
+      None => { out.push('&'); rest = &rest[1..]; }
+    }
+  }
+  out.push_str(rest);
+  out
+}
+
+fn is_dangerous_url(v: &str) -> bool {
+  // 先解码字符引用，再去除空白与控制字符后比较，防止 "&#106;avascript:"、"java\tscript:" 绕过
+  let compact: String = decode_entities(v).chars().filter(|c| !c.is_whitespace() && !c.is_control()).collect::<String>().to_ascii_lowercase();
+  compact.contains("javascript:") || compact.contains("vbscript:") || compact.starts_with("data:text/html") || compact.starts_with("data:image/svg")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sniff() {
+    let mut bmp = b"BM".to_vec();
+    bmp.extend_from_slice(&[0x46, 0, 0, 0, 0, 0, 0, 0, 0x36, 0, 0, 0]);
+    let cases: &[(&[u8], Option<&str>)] = &[
+      (b"\x89PNG\r\n\x1a\n....", Some("image/png")),
+      (b"\xff\xd8\xff\xe0", Some("image/jpeg")),
+      (b"GIF89a...", Some("image/gif")),
+      (b"RIFF\x00\x00\x00\x00WEBPVP8 ", Some("image/webp")),
+      (b"\x00\x00\x00\x1cftypavif\x00\x00\x00\x00mif1", Some("image/avif")),
+      (&bmp, Some("image/bmp")),
+      (b"\x00\x00\x01\x00\x01\x00\x10\x10", Some("image/x-icon")),
+      (b"%PDF-1.7", Some("application/pdf")),
+      (b"PK\x03\x04....word/document.xml", Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document")),
+      (b"PK\x03\x04....", Some("application/zip")),
+      (b"ID3\x04", Some("audio/mpeg")),
+      (b"\x00\x00\x00\x18ftypmp42", Some("video/mp4")),
+      (b"\xef\xbb\xbf<?xml version=\"1.0\"?>\n<!-- x -->\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>", Some("image/svg+xml")),
+      (b"<html><body><svg/></body></html>", None),
+      // 只有 BM 两个字节（如以 "BM" 开头的文本）不视为 BMP
+      (b"BMW is a car", None),
+      (b"\x00\x00\x01\x00\x00\x00", None),
+      (b"hello", None),
+    ];
+    for (bytes, want) in cases {
+      assert_eq!(sniff_mime(bytes), *want, "{:?}", String::from_utf8_lossy(bytes));
+    }
+  }
+
+  #[test]
+  fn policy() {
+    let p = UploadPolicy::default();
+    let mut bmp = b"BM".to_vec();
+    bmp.extend_from_slice(&[0; 12]);
+    assert_eq!(p.enforce(bmp, Some("image/bmp")).unwrap().content_type, "image/bmp");
+    assert!(p.enforce(b"<html>".to_vec(), Some("image/png")).unwrap_err().starts_with("upload policy: unrecognized"));
+    assert!(p.enforce(b"PK\x03\x04".to_vec(), None).unwrap_err().contains("not allowed"));
+    let small = UploadPolicy::from_profile(None, Some(4));
+    assert!(small.enforce(b"%PDF-1.7".to_vec(), None).unwrap_err().contains("too large"));
+  }
+
+  #[test]
+  fn svg_sanitizer() {
+    // (输入, 输出中必须不含的片段)
+    let cases: &[(&str, &str)] = &[
+      (r#"<svg><script>alert(1)</script></svg>"#, "alert"),
+      (r#"<svg><a href="javascript:alert(1)">x</a></svg>"#, "javascript"),
+      (r#"<svg><a href="&#106;avascript:alert(1)">x</a></svg>"#, "avascript"),
+      (r#"<svg><a href="&#x6A;avascript&colon;alert(1)">x</a></svg>"#, "avascript"),
+      (r#"<svg><a xlink:href="java&#9;script:alert(1)">x</a></svg>"#, "script:"),
+      (r#"<svg><a href=" &#x1;javascript:alert(1)">x</a></svg>"#, "alert"),
+      (r#"<svg><image href="data:text/html;base64,PHNjcmlwdD4="/></svg>"#, "data:"),
+      (r#"<svg onload="alert(1)"><rect/></svg>"#, "onload"),
+      (r#"<svg><style>@import url(http://evil/x.css);</style><rect/></svg>"#, "@import"),
+      (r#"<svg><foreignObject><div>x</div></foreignObject></svg>"#, "<div"),
+      (r#"<!DOCTYPE svg [<!ENTITY x "y">]><svg>&x;</svg>"#, "ENTITY"),
+      // 带命名空间前缀的元素与属性按本地名处理
+      (r#"<svg xmlns:svg="http://www.w3.org/2000/svg"><svg:script>alert(1)</svg:script></svg>"#, "alert"),
+      (r#"<svg><svg:script><svg:script>a</svg:script>alert(1)</svg:script><rect/></svg>"#, "alert"),
+      (r#"<svg><svg:foreignObject><div>x</div></svg:foreignObject></svg>"#, "<div"),
+      (r#"<svg xmlns:x="http://www.w3.org/1999/xlink"><a x:href="javascript:alert(1)">x</a></svg>"#, "javascript"),
+      (r#"<svg><a x:HREF="javascript:alert(1)">x</a></svg>"#, "javascript"),
+      (r#"<svg><rect ev:onclick="alert(1)"/></svg>"#, "alert"),
+    ];
+    for (input, banned) in cases {
+      let out = sanitize_svg(input).unwrap();
+      assert!(!out.contains(banned), "{} -> {}", input, out);
+      assert!(out.contains("<svg"), "{}", out);
+    }
+    // 正常属性与链接保留
+    let ok = sanitize_svg(r##"<svg viewBox="0 0 10 10"><a href="https://example.com/?a=1&amp;b=2"><use href="#r"/></a></svg>"##).unwrap();
+    assert!(ok.contains(r#"viewBox="0 0 10 10""#) && ok.contains("https://example.com/?a=1&amp;b=2") && ok.contains(r##"href="#r""##), "{}", ok);
+    let ns = sanitize_svg(r##"<svg><svg:script>x</svg:script><svg:rect x:href="#r"/></svg>"##).unwrap();
+    assert_eq!(ns, r##"<svg><svg:rect x:href="#r" /></svg>"##);
+    assert!(sanitize_svg("<div>no svg</div>").is_err());
+  }
+}
@@ -0,0 +1,421 @@
+// XML-RPC 编解码：JSON <-> XML-RPC 值，methodCall 序列化与 methodResponse/fault 解析
+// JSON 中特殊值的约定：{"$base64": "..."} 表示 base64，{"$dateTime": "20250101T08:00:00"} 表示 dateTime.iso8601
+
+use base64::Engine;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Serialize;
+use serde_json::{Map, Number, Value as Json};
+use std::time::Duration;
+
+use crate::http_opts::{HttpError, HttpOptions};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+  Int(i64),
+  Bool(bool),
+  Double(f64),
+  String(String),
+  DateTime(String),
+  Base64(Vec<u8>),
+  Struct(Vec<(String, Value)>),
+  Array(Vec<Value>),
+  Nil,
+}
+
+impl Value {
+  // JSON -> XML-RPC：整数超出 i32 时退化为 double（多数服务端不支持 i8）
+  pub fn from_json(j: &Json) -> Result<Value, String> {
+    Ok(match j {
+      Json::Null => Value::Nil,
+      Json::Bool(b) => Value::Bool(*b),
+      Json::Number(n) => match n.as_i64() {
+        Some(i) if i32::try_from(i).is_ok() => Value::Int(i),
+        _ => Value::Double(n.as_f64().unwrap_or(0.0)),
+      },
+      Json::String(s) => Value::String(s.clone()),
+      Json::Array(a) => Value::Array(a.iter().map(Value::from_json).collect::<Result<_, _>>()?),
+      Json::Object(o) => {
+        if o.len() == 1 {
+          if let Some(b) = o.get("$base64") {
+            let s = b.as_str().ok_or_else(|| "$base64 must be a string".to_string())?;
+            let bytes = base64::engine::general_purpose::STANDARD
+              .decode(s.trim())
+              .map_err(|e| format!("invalid $base64: {e}"))?;
+            return Ok(Value::Base64(bytes));
+          }
+          if let Some(d) = o.get("$dateTime") {
+            let s = d.as_str().ok_or_else(|| "$dateTime must be a string".to_string())?;
+            return Ok(Value::DateTime(normalize_datetime(s)));
+          }
+        }
+        Value::Struct(o.iter().map(|(k, v)| Ok((k.clone(), Value::from_json(v)?))).collect::<Result<_, String>>()?)
+      }
+    })
+  }
+
+  // XML-RPC -> JSON：dateTime 输出为字符串，base64 输出为 {"$base64": "..."}
+  pub fn to_json(&self) -> Json {
+    match self {
+      Value::Int(i) => Json::Number((*i).into()),
+      Value::Bool(b) => Json::Bool(*b),
+      Value::Double(f) => Number::from_f64(*f).map(Json::Number).unwrap_or(Json::Null),
+      Value::String(s) | Value::DateTime(s) => Json::String(s.clone()),
+      Value::Base64(b) => {
+        let mut m = Map::new();
+        m.insert("$base64".into(), Json::String(base64::engine::general_purpose::STANDARD.encode(b)));
+        Json::Object(m)
+      }
+      Value::Struct(members) => Json::Object(members.iter().map(|(k, v)| (k.clone(), v.to_json())).collect()),
+      Value::Array(items) => Json::Array(items.iter().map(|v| v.to_json()).collect()),
+      Value::Nil => Json::Null,
+    }
+  }
+
+  pub fn get(&self, name: &str) -> Option<&Value> {
+    match self {
+      Value::Struct(m) => m.iter().find(|(k, _)| k == name).map(|(_, v)| v),
+      _ => None,
+    }
+  }
+
+  // 宽松取字符串：部分服务端把 id 返回为 int
+  pub fn as_string(&self) -> Option<String> {
+    match self {
+      Value::String(s) | Value::DateTime(s) => Some(s.clone()),
+      Value::Int(i) => Some(i.to_string()),
+      _ => None,
+    }
+  }
+
+  // XML-RPC 的 double 没有 NaN/Infinity 的写法，遇到时报错而不是写出服务端无法解析的报文
+  fn write_xml(&self, out: &mut String) -> Result<(), XmlRpcError> {
+    out.push_str("<value>");
+    match self {
+      Value::Int(i) => { out.push_str("<int>"); out.push_str(&i.to_string()); out.push_str("</int>"); }
+      Value::Bool(b) => { out.push_str(if *b { "<boolean>1</boolean>" } else { "<boolean>0</boolean>" }); }
+      Value::Double(f) => {
+        if !f.is_finite() {
+          return Err(XmlRpcError::Encode { message: format!("double {} is not representable in xml-rpc", f) });
+        }
+        out.push_str("<double>"); out.push_str(&f.to_string()); out.push_str("</double>");
+      }
+      Value::String(s) => { out.push_str("<string>"); out.push_str(&escape(s)); out.push_str("</string>"); }
+      Value::DateTime(s) => { out.push_str("<dateTime.iso8601>"); out.push_str(&escape(s)); out.push_str("</dateTime.iso8601>"); }
+      Value::Base64(b) => {
+        out.push_str("<base64>");
+        out.push_str(&base64::engine::general_purpose::STANDARD.encode(b));
+        out.push_str("</base64>");
+      }
+      Value::Struct(members) => {
+        out.push_str("<struct>");
+        for (k, v) in members {
+          out.push_str("<member><name>");
+          out.push_str(&escape(k));
+          out.push_str("</name>");
+          v.write_xml(out)?;
+          out.push_str("</member>");
+        }
+        out.push_str("</struct>");
+      }
+      Value::Array(items) => {
+        out.push_str("<array><data>");
+        for v in items { v.write_xml(out)?; }
+        out.push_str("</data></array>");
+      }
+      Value::Nil => out.push_str("<nil/>"),
+    }
+    out.push_str("</value>");
+    Ok(())
+  }
+}
+
+// 接受 RFC3339 与 XML-RPC 紧凑格式，统一为 YYYYMMDDTHH:MM:SS
+fn normalize_datetime(s: &str) -> String {
+  let s = s.trim();
+  match chrono::DateTime::parse_from_rfc3339(s) {
+    Ok(dt) => dt.with_timezone(&chrono::Utc).format("%Y%m%dT%H:%M:%S").to_string(),
+    Err(_) => s.to_string(),
+  }
+}
+
+fn escape(s: &str) -> String {
+  let mut out = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      // 原样写出的 \r 会被 XML 解析器规范化为 \n
+      '\r' => out.push_str("&#13;"),
+      _ => out.push(c),
+    }
+  }
+  out
+}
+
+pub fn encode_call(method: &str, params: &[Value]) -> Result<String, XmlRpcError> {
+  let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?><methodCall><methodName>");
+  out.push_str(&escape(method));
+  out.push_str("</methodName><params>");
+  for p in params {
+    out.push_str("<param>");
+    p.write_xml(&mut out)?;
+    out.push_str("</param>");
+  }
+  out.push_str("</params></methodCall>");
+  Ok(out)
+}
+
+// 结构化错误：fault 与 HTTP/网络/解析错误分开，前端可按 kind 分支
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum XmlRpcError {
+  Fault { code: i64, message: String },
+  Http { status: u16, body: String },
+  Network { message: String },
+  Tls { message: String },
+  Config { message: String },
+  Parse { message: String },
+  Encode { message: String },
+}
+
+impl std::fmt::Display for XmlRpcError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      XmlRpcError::Fault { code, message } => write!(f, "xmlrpc fault {}: {}", code, message),
+      XmlRpcError::Http { status, body } => write!(f, "HTTP {}: {}", status, body),
+      XmlRpcError::Network { message } => write!(f, "network error: {}", message),
+      XmlRpcError::Tls { message } => write!(f, "tls error: {}", message),
+      XmlRpcError::Config { message } => write!(f, "http config error: {}", message),
+      XmlRpcError::Parse { message } => write!(f, "xmlrpc parse error: {}", message),
+      XmlRpcError::Encode { message } => write!(f, "xmlrpc encode error: {}", message),
+    }
+  }
+}
+
+impl From<XmlRpcError> for String {
+  fn from(e: XmlRpcError) -> String { e.to_string() }
+}
+
+impl From<HttpError> for XmlRpcError {
+  fn from(e: HttpError) -> Self {
+    match e {
+      HttpError::Config { message } => XmlRpcError::Config { message },
+      HttpError::Network { message } => XmlRpcError::Network { message },
+      HttpError::Tls { message } => XmlRpcError::Tls { message },
+      HttpError::Http { status, body } => XmlRpcError::Http { status, body },
+    }
+  }
+}
+
+// 轻量 DOM：XML-RPC 报文很小，先建树再解释
+#[derive(Debug, Default)]
+struct Node {
+  name: String,
+  text: String,
+  children: Vec<Node>,
+}
+
+impl Node {
+  fn child(&self, name: &str) -> Option<&Node> { self.children.iter().find(|c| c.name == name) }
+}
+
+fn parse_tree(xml: &str) -> Result<Node, String> {
+  let mut reader = Reader::from_str(xml);
+  let mut stack: Vec<Node> = vec![Node::default()];
+  loop {
+    match reader.read_event().map_err(|e| e.to_string())? {
+      Event::Start(e) => {
+        let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+        stack.push(Node { name, ..Default::default() });
+      }
+      Event::Empty(e) => {
+        let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+        if let Some(top) = stack.last_mut() { top.children.push(Node { name, ..Default::default() }); }
+      }
+      Event::End(_) => {
+        let node = stack.pop().ok_or_else(|| "unbalanced xml".to_string())?;
+        let parent = stack.last_mut().ok_or_else(|| "unbalanced xml".to_string())?;
+        parent.children.push(node);
+      }
+      Event::Text(t) => {
+        let s = t.unescape().map_err(|e| e.to_string())?;
+        if let Some(top) = stack.last_mut() { top.text.push_str(&s); }
+      }
+      Event::CData(c) => {
+        let s = String::from_utf8_lossy(&c.into_inner()).to_string();
+        if let Some(top) = stack.last_mut() { top.text.push_str(&s); }
+      }
+      Event::Eof => break,
+      _ => {}
+    }
+  }
+  if stack.len() != 1 { return Err("unexpected end of xml".into()); }
+  Ok(stack.pop().unwrap_or_default())
+}
+
+fn parse_value(node: &Node) -> Result<Value, String> {
+  // <value> 无类型子元素时按 string 处理
+  let Some(typed) = node.children.first() else { return Ok(Value::String(node.text.clone())) };
+  let text = typed.text.trim();
+  Ok(match typed.name.as_str() {
+    "int" | "i4" | "i8" => Value::Int(text.parse::<i64>().map_err(|e| format!("invalid int {text:?}: {e}"))?),
+    "boolean" => Value::Bool(text == "1" || text.eq_ignore_ascii_case("true")),
+    "double" => match text.parse::<f64>() {
+      // Rust 能解析 NaN/inf，但它们不是合法的 XML-RPC double
+      Ok(f) if f.is_finite() => Value::Double(f),
+      Ok(_) => return Err(format!("invalid double {text:?}")),
+      Err(e) => return Err(format!("invalid double {text:?}: {e}")),
+    },
+    "string" => Value::String(typed.text.clone()),
+    "dateTime.iso8601" => Value::DateTime(text.to_string()),
+    "base64" => {
+      let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+      Value::Base64(base64::engine::general_purpose::STANDARD.decode(compact).map_err(|e| format!("invalid base64: {e}"))?)
+    }
+    "nil" => Value::Nil,
+    "struct" => {
+      let mut members = Vec::new();
+      for m in typed.children.iter().filter(|c| c.name == "member") {
+        let name = m.child("name").map(|n| n.text.clone()).unwrap_or_default();
+        let v = match m.child("value") { Some(v) => parse_value(v)?, None => Value::Nil };
+        members.push((name, v));
+      }
+      Value::Struct(members)
+    }
+    "array" => {
+      let data = typed.child("data");
+      let items = data.map(|d| d.children.iter().filter(|c| c.name == "value").map(parse_value).collect::<Result<Vec<_>, _>>()).transpose()?;
+      Value::Array(items.unwrap_or_default())
+    }
+    other => return Err(format!("unknown xmlrpc type <{}>", other)),
+  })
+}
+
+// 解析 methodResponse：成功返回首个参数，fault 转为结构化错误
+pub fn decode_response(xml: &str) -> Result<Value, XmlRpcError> {
+  let perr = |m: String| XmlRpcError::Parse { message: m };
+  let root = parse_tree(xml).map_err(perr)?;
+  let resp = root.child("methodResponse").ok_or_else(|| perr("missing methodResponse".into()))?;
+  if let Some(fault) = resp.child("fault") {
+    let v = fault.child("value").map(parse_value).transpose().map_err(perr)?.unwrap_or(Value::Nil);
+    let code = match v.get("faultCode") { Some(Value::Int(i)) => *i, Some(Value::String(s)) => s.trim().parse().unwrap_or(0), _ => 0 };
+    let message = v.get("faultString").and_then(|s| s.as_string()).unwrap_or_default();
+    return Err(XmlRpcError::Fault { code, message });
+  }
+  let param = resp.child("params").and_then(|p| p.child("param")).and_then(|p| p.child("value"));
+  match param {
+    Some(v) => parse_value(v).map_err(perr),
+    None => Ok(Value::Nil),
+  }
+}
+
+pub async fn call(url: &str, method: &str, params: &[Value], opts: &HttpOptions) -> Result<Value, XmlRpcError> {
+  let body = encode_call(method, params)?;
+  let client = opts.client(url, Duration::from_secs(20), concat!("flymd/", env!("CARGO_PKG_VERSION")))?;
+  let res = client
+    .post(url)
+    .header("Content-Type", "text/xml; charset=UTF-8")
+    .header("Accept", "text/xml, */*;q=0.1")
+    .body(body)
+    .send()
+    .await
+    .map_err(HttpError::from)?;
+  let status = res.status();
+  let text = res.text().await.map_err(|e| XmlRpcError::Network { message: format!("read error: {e}") })?;
+  if !status.is_success() {
+    return Err(XmlRpcError::Http { status: status.as_u16(), body: text });
+  }
+  decode_response(&text)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  // 把 methodCall 里的参数当作 methodResponse 解回来，验证编解码往返
+  fn round_trip(v: &Value) -> Value {
+    let call = encode_call("m", std::slice::from_ref(v)).unwrap();
+    let body = call.split("<params>").nth(1).unwrap().split("</params>").next().unwrap();
+    decode_response(&format!("<?xml version=\"1.0\"?><methodResponse><params>{}</params></methodResponse>", body)).unwrap()
+  }
+
+  #[test]
+  fn escaping() {
+    let call = encode_call("a<b>&c", &[Value::String("<p>Tom & \"Jerry\"</p>\r\n".into())]).unwrap();
+    assert!(call.contains("<methodName>a&lt;b&gt;&amp;c</methodName>"));
+    assert!(call.contains("<string>&lt;p&gt;Tom &amp; \"Jerry\"&lt;/p&gt;&#13;\n</string>"));
+    let members = Value::Struct(vec![("a&b".into(), Value::String("x]]>y".into()))]);
+    assert_eq!(round_trip(&members), members);
+    let s = Value::String("  前后空白 & <中文>\r\n".into());
+    assert_eq!(round_trip(&s), s);
+  }
+
+  #[test]
+  fn json_round_trip() {
+    let j = json!({
+      "title": "t",
+      "count": 3,
+      "big": 4_294_967_296i64,
+      "ratio": 0.5,
+      "ok": true,
+      "tags": ["a", "b"],
+      "none": null,
+      "bits": { "$base64": "aGVsbG8=" },
+    });
+    let v = Value::from_json(&j).unwrap();
+    assert_eq!(v.get("big"), Some(&Value::Double(4_294_967_296.0)));
+    assert_eq!(v.get("bits"), Some(&Value::Base64(b"hello".to_vec())));
+    let back = round_trip(&v).to_json();
+    assert_eq!(back["count"], json!(3));
+    assert_eq!(back["ratio"], json!(0.5));
+    assert_eq!(back["tags"], json!(["a", "b"]));
+    assert_eq!(back["bits"], json!({ "$base64": "aGVsbG8=" }));
+    assert_eq!(back["none"], Json::Null);
+
+    let d = Value::from_json(&json!({ "$dateTime": "2025-01-01T08:00:00+08:00" })).unwrap();
+    assert_eq!(d, Value::DateTime("20250101T00:00:00".into()));
+  }
+
+  #[test]
+  fn doubles() {
+    for f in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+      assert!(matches!(encode_call("m", &[Value::Double(f)]), Err(XmlRpcError::Encode { .. })));
+      let nested = Value::Array(vec![Value::Struct(vec![("x".into(), Value::Double(f))])]);
+      assert!(encode_call("m", &[nested]).is_err());
+    }
+    // 不能出现指数写法
+    let call = encode_call("m", &[Value::Double(1e21)]).unwrap();
+    assert!(call.contains("<double>1000000000000000000000</double>"));
+    assert_eq!(round_trip(&Value::Double(-0.25)), Value::Double(-0.25));
+    for bad in ["NaN", "inf", "-infinity", "abc"] {
+      let xml = format!("<methodResponse><params><param><value><double>{}</double></value></param></params></methodResponse>", bad);
+      assert!(matches!(decode_response(&xml), Err(XmlRpcError::Parse { .. })), "{}", bad);
+    }
+  }
+
+  #[test]
+  fn responses() {
+    let xml = "<?xml version=\"1.0\"?>\n<methodResponse><params><param><value>plain &amp; untyped</value></param></params></methodResponse>";
+    assert_eq!(decode_response(xml).unwrap(), Value::String("plain & untyped".into()));
+    let xml = "<methodResponse><params><param><value><i4> 42 </i4></value></param></params></methodResponse>";
+    assert_eq!(decode_response(xml).unwrap(), Value::Int(42));
+    let xml = "<methodResponse><params><param><value><string><![CDATA[<b>x</b>]]></string></value></param></params></methodResponse>";
+    assert_eq!(decode_response(xml).unwrap(), Value::String("<b>x</b>".into()));
+    let xml = "<methodResponse><fault><value><struct>\
+      <member><name>faultCode</name><value><int>403</int></value></member>\
+      <member><name>faultString</name><value><string>Incorrect username &amp; password.</string></value></member>\
+      </struct></value></fault></methodResponse>";
+    match decode_response(xml) {
+      Err(XmlRpcError::Fault { code, message }) => {
+        assert_eq!(code, 403);
+        assert_eq!(message, "Incorrect username & password.");
+      }
+      other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(decode_response("<html>oops</html>"), Err(XmlRpcError::Parse { .. })));
+    assert!(matches!(decode_response("<methodResponse><params>"), Err(XmlRpcError::Parse { .. })));
+  }
+}
@@ -25,7 +25,7 @@ import { getCurrentWindow } from '@tauri-apps/api/window'
 import { getCurrentWebview } from '@tauri-apps/api/webview'
 import { convertFileSrc, invoke } from '@tauri-apps/api/core'
 import fileTree from './fileTree'
-import { uploadImageToS3R2, type UploaderConfig } from './uploader/s3'
+import { uploadImageToS3R2, uploadAttachmentToS3R2, type UploaderConfig } from './uploader/s3'
 import appIconUrl from '../flymd.png?url'
 import { decorateCodeBlocks } from './decorate'
 import pkg from '../package.json'
@@ -966,7 +966,7 @@ const aboutBtn = document.createElement('div')
       const updBtn = document.createElement('div')
       updBtn.id = 'btn-update'
       updBtn.className = 'menu-item'
-      updBtn.title = '检查更新'
+      updBtn.title = '检查更新（右键：更新设置）'
       updBtn.textContent = '\u66f4\u65b0'
       menubar.appendChild(updBtn)
       menubar.appendChild(aboutBtn)
@@ -2042,9 +2042,20 @@ function isTauriRuntime(): boolean {
 type UpdateAssetInfo = {
   name: string
   size: number
+  kind: string
+  arch: string
   directUrl: string
   proxyUrl: string
 }
+type ReleaseNote = {
+  tag: string
+  name: string
+  notes: string
+  htmlUrl: string
+  publishedAt?: string | null
+  prerelease: boolean
+  breaking: boolean
+}
 type CheckUpdateResp = {
   hasUpdate: boolean
   current: string
@@ -2052,9 +2063,23 @@ type CheckUpdateResp = {
   releaseName: string
   notes: string
   htmlUrl: string
-  assetWin?: UpdateAssetInfo | null
-  assetLinuxAppimage?: UpdateAssetInfo | null
-  assetLinuxDeb?: UpdateAssetInfo | null
+  // 当前版本之后的全部更新说明（从新到旧）
+  releaseNotes?: ReleaseNote[]
+  hasBreaking?: boolean
+  // 适用于当前系统与架构的安装包（按推荐程度排序）
+  assets: UpdateAssetInfo[]
+  arch: string
+  installKind?: 'windows' | 'appimage' | 'deb' | 'unknown'
+  // 更新通道（stable / security / beta，保存在后端设置中）
+  channel?: 'stable' | 'security' | 'beta'
+  // 处于“稍后提醒”期间
+  snoozed?: boolean
+  // 可回滚到的旧版本（从新到旧）
+  rollbackVersions?: string[]
+}
+type InstallResult = {
+  method: string
+  restartRequired: boolean
 }
 
 async function openInBrowser(url: string) {
@@ -2111,8 +2136,6 @@ function ensureUpdateOverlay(): HTMLDivElement {
   return div
 }
 
-function showUpdateOverlayLinux(resp: CheckUpdateResp) {
-
 function showUpdateDownloadedOverlay(savePath: string, resp: CheckUpdateResp) {
   const ov = ensureUpdateOverlay()
   const body = ov.querySelector('#update-body') as HTMLDivElement
@@ -2137,12 +2160,59 @@ function showUpdateDownloadedOverlay(savePath: string, resp: CheckUpdateResp) {
   mkBtn('关闭', () => ov.classList.add('hidden'))
   ov.classList.remove('hidden')
 }
+
+// 稍后提醒：一段时间内自动检查不再提示该版本（手动检查仍会提示）
+async function remindUpdateLater(resp: CheckUpdateResp) {
+  try {
+    await invoke('remind_update_later', { version: resp.latest })
+    upMsg('已取消更新，24 小时内不再自动提醒')
+  } catch { upMsg('已取消更新') }
+}
+
+// 用户拒绝更新：可选择跳过该版本，否则稍后提醒
+async function declineUpdate(resp: CheckUpdateResp) {
+  const skip = await confirmNative(`是否跳过版本 ${resp.latest}？\n选择“否”将在 24 小时后再次提醒。`, '更新')
+  if (skip) await skipUpdateVersion(resp); else await remindUpdateLater(resp)
+}
+
+// 跳过此版本：之后只提示更高的版本
+async function skipUpdateVersion(resp: CheckUpdateResp) {
+  try {
+    await invoke('skip_update_version', { version: resp.latest })
+    setUpdateBadge(false)
+    upMsg(`已跳过版本 ${resp.latest}`)
+  } catch (e) { upMsg('跳过版本失败：' + String(e)) }
+}
+
+// 确认框中的简要说明：跨越的版本数与不兼容提示
+function updateNotesSummary(resp: CheckUpdateResp): string {
+  const list = resp.releaseNotes || []
+  let s = ''
+  if (list.length > 1) s += `\n期间共有 ${list.length} 个版本：${list.map(n => n.tag).join('、')}`
+  if (resp.hasBreaking) s += `\n注意：${list.filter(n => n.breaking).map(n => n.tag).join('、')} 包含不兼容变更或迁移说明，建议先在发布页阅读`
+  return s
+}
+
+// 汇总的更新说明：逐个版本列出，含不兼容变更的版本加标记；旧后端未返回时退回最新版本的说明
+function releaseNotesHtml(resp: CheckUpdateResp): string {
+  const esc = (s: string) => (s || '').replace(/&/g, '&amp;').replace(/</g, '&lt;')
+  const list = resp.releaseNotes || []
+  if (!list.length) return esc(resp.notes || '')
+  return list.map(n => {
+    const date = n.publishedAt ? ` <span style="opacity:.6;">${esc(n.publishedAt.slice(0, 10))}</span>` : ''
+    const mark = n.breaking ? ' <span style="color:#d9534f;">⚠ 不兼容变更</span>' : ''
+    return `<div style="margin-bottom:10px;"><b>${esc(n.name)}</b>${date}${mark}\n${esc(n.notes.trim())}</div>`
+  }).join('')
+}
+
+function showUpdateOverlayLinux(resp: CheckUpdateResp) {
   const ov = ensureUpdateOverlay()
   const body = ov.querySelector('#update-body') as HTMLDivElement
   const act = ov.querySelector('#update-actions') as HTMLDivElement
   body.innerHTML = `
     <div style="margin-bottom:8px;">发现新版本：<b>${resp.latest}</b>（当前：${resp.current}）</div>
-    <div style="white-space:pre-wrap;max-height:240px;overflow:auto;border:1px solid var(--fg-muted);padding:8px;border-radius:6px;">${(resp.notes||'').replace(/</g,'&lt;')}</div>
+    ${resp.hasBreaking ? '<div style="margin-bottom:8px;color:#d9534f;">⚠ 部分版本包含不兼容变更或迁移说明，请在更新前阅读</div>' : ''}
+    <div style="white-space:pre-wrap;max-height:240px;overflow:auto;border:1px solid var(--fg-muted);padding:8px;border-radius:6px;">${releaseNotesHtml(resp)}</div>
   `
   act.innerHTML = ''
   const mkBtn = (label: string, onClick: () => void) => {
@@ -2152,78 +2222,248 @@ function showUpdateDownloadedOverlay(savePath: string, resp: CheckUpdateResp) {
     act.appendChild(b)
     return b
   }
-  if (resp.assetLinuxAppimage) {
-    mkBtn('下载 AppImage（代理）', () => { void openInBrowser('https://gh-proxy.com/' + resp.assetLinuxAppimage!.directUrl) })
-  }
-  if (resp.assetLinuxDeb) {
-    mkBtn('下载 DEB（代理）', () => { void openInBrowser('https://gh-proxy.com/' + resp.assetLinuxDeb!.directUrl) })
+  for (const a of (resp.assets || []).slice(0, 4)) {
+    mkBtn(`下载 ${a.kind.toUpperCase()}（${a.arch}）`, () => { void openInBrowser(a.proxyUrl) })
   }
+  mkBtn('跳过此版本', () => { void skipUpdateVersion(resp); ov.classList.add('hidden') })
+  mkBtn('稍后提醒', () => { void remindUpdateLater(resp); ov.classList.add('hidden') })
   mkBtn('前往发布页', () => { void openInBrowser(resp.htmlUrl) })
   mkBtn('关闭', () => ov.classList.add('hidden'))
   ov.classList.remove('hidden')
 }
 
+// 更新源配置（Store 键 updater）：{ feedUrl?, mirrors?: string[], githubToken?, probeMirrors? }，未配置时后端使用默认值
+async function getUpdateConfig(): Promise<any | null> {
+  try { if (store) { const c = await store.get('updater'); if (c && typeof c === 'object') return c } } catch {}
+  return null
+}
+
+// 下载更新包并在状态栏显示进度；后端按 .part 断点续传，切换镜像时进度从已下载处继续
+async function downloadUpdateAsset(asset: UpdateAssetInfo, config: any, version?: string): Promise<string> {
+  // 后台已预先下载并校验过同一安装包时直接使用
+  if (version) {
+    try {
+      const st = await invoke('get_update_status') as any
+      const d = st?.downloaded
+      if (d && d.version === version && d.asset === asset.name && d.path) return String(d.path)
+    } catch {}
+  }
+  upMsg('正在下载安装包…')
+  // 下载期间在更新窗口显示进度与“取消下载”按钮；取消后 download_file 以 download cancelled 失败
+  const ov = ensureUpdateOverlay()
+  const esc = (s: string) => (s || '').replace(/&/g, '&amp;').replace(/</g, '&lt;')
+  const body = ov.querySelector('#update-body') as HTMLDivElement
+  const act = ov.querySelector('#update-actions') as HTMLDivElement
+  body.innerHTML = `
+    <div style="margin-bottom:8px;">正在下载 <b>${esc(asset.name)}</b></div>
+    <div id="update-progress" style="opacity:.8;">正在连接…</div>
+  `
+  act.innerHTML = ''
+  const cancelBtn = document.createElement('button')
+  cancelBtn.textContent = '取消下载'
+  cancelBtn.addEventListener('click', () => {
+    cancelBtn.disabled = true
+    void invoke('cancel_download').catch(() => {})
+  })
+  act.appendChild(cancelBtn)
+  ov.classList.remove('hidden')
+  const progressEl = body.querySelector('#update-progress') as HTMLDivElement | null
+  let unlisten: null | (() => void) = null
+  try {
+    const ev = await import('@tauri-apps/api/event')
+    unlisten = await ev.listen('update-download-progress', (e: any) => {
+      const p = e?.payload || {}
+      // 后台预下载的进度不显示在这里
+      if (p.background) return
+      const mb = (n: number) => (n / 1048576).toFixed(1)
+      const pct = p.total ? ` ${Math.floor(p.downloaded * 100 / p.total)}%` : ''
+      const text = `${pct} ${mb(p.downloaded || 0)}${p.total ? '/' + mb(p.total) : ''} MB ${mb(p.speed || 0)} MB/s`
+      try { status.textContent = `正在下载安装包…${text}` } catch {}
+      try { if (progressEl) progressEl.textContent = text.trim() } catch {}
+    })
+  } catch {}
+  try {
+    // 镜像与直连由后端按配置测速排序后依次尝试
+    // 可在 updater 配置中用 downloadDir 指定保存目录（绝对路径），默认保存到系统下载目录
+    const targetDir = (config && typeof config.downloadDir === 'string' && config.downloadDir) ? config.downloadDir : null
+    return await invoke('download_file', { url: asset.directUrl, config, targetDir }) as any as string
+  } finally {
+    try { unlisten?.() } catch {}
+    // 窗口仍在显示本次下载进度时关闭（关闭按钮只隐藏窗口，下载继续在状态栏显示）
+    if (cancelBtn.isConnected) ov.classList.add('hidden')
+  }
+}
+
+// 已是最新版本：有保留的旧版本时在更新窗口中提供回滚按钮，由用户主动选择
+function showUpToDateOverlay(resp: CheckUpdateResp) {
+  const versions = resp.rollbackVersions || []
+  if (!versions.length) return
+  const ov = ensureUpdateOverlay()
+  const body = ov.querySelector('#update-body') as HTMLDivElement
+  const act = ov.querySelector('#update-actions') as HTMLDivElement
+  body.innerHTML = `
+    <div style="margin-bottom:8px;">已是最新版本 <b>v${APP_VERSION}</b></div>
+    <div style="opacity:.8;">如果当前版本有问题，可以回滚到更新前保留的版本。</div>
+  `
+  act.innerHTML = ''
+  const mkBtn = (label: string, onClick: () => void) => {
+    const b = document.createElement('button')
+    b.textContent = label
+    b.addEventListener('click', onClick)
+    act.appendChild(b)
+    return b
+  }
+  for (const v of versions.slice(0, 3)) {
+    mkBtn(`回滚到 v${v}`, () => { ov.classList.add('hidden'); void rollbackUpdateInteractive(v) })
+  }
+  mkBtn('关闭', () => ov.classList.add('hidden'))
+  ov.classList.remove('hidden')
+}
+
+// 回滚到更新前保留的旧版本（新版本有问题时使用）
+async function rollbackUpdateInteractive(version: string) {
+  const ok = await confirmNative(`确定从 v${APP_VERSION} 回滚到 v${version}？`, '回滚')
+  if (!ok) return
+  try {
+    upMsg(`正在回滚到 v${version}…`)
+    const r = await invoke('rollback_update', { version }) as any as InstallResult
+    if (!r?.restartRequired) { upMsg('已启动安装程序，即将关闭…'); setTimeout(() => { try { void getCurrentWindow().destroy() } catch {} }, 800); return }
+    upMsg(`已回滚到 v${version}`)
+    if (await confirmNative(`已回滚到 v${version}，是否立即重启 flymd？`, '回滚')) await invoke('restart_after_update')
+  } catch (e) {
+    upMsg('回滚失败：' + String(e))
+  }
+}
+
 async function checkUpdateInteractive() {
   try {
     upMsg('正在检查更新…')
-    const resp = await invoke('check_update', { force: true, include_prerelease: false }) as any as CheckUpdateResp
-    if (!resp || !resp.hasUpdate) { setUpdateBadge(false); upMsg(`已是最新版本 v${APP_VERSION}`); return }
+    const config = await getUpdateConfig()
+    const resp = await invoke('check_update', { force: true, include_prerelease: false, config }) as any as CheckUpdateResp
+    if (!resp || !resp.hasUpdate) {
+      setUpdateBadge(false); upMsg(`已是最新版本 v${APP_VERSION}`)
+      if (resp) showUpToDateOverlay(resp)
+      return
+    }
     setUpdateBadge(true, `发现新版本 v${resp.latest}`)
-    // Windows：自动下载并运行；Linux：展示两个下载链接（依据后端返回的资产类型判断）
-    if (resp.assetWin) {
-      if (!resp.assetWin) { upMsg('发现新版本，但未找到 Windows 安装包'); await openInBrowser(resp.htmlUrl); return }
-      const ok = await confirmNative(`发现新版本 ${resp.latest}（当前 ${resp.current}）\n是否立即下载并安装？`, '更新')
-      if (!ok) { upMsg('已取消更新'); return }
+    // Windows：自动下载并运行安装程序；其他平台见下方（安装包由后端按系统、架构与安装方式排序）
+    const assets = resp.assets || []
+    if (resp.installKind === 'windows') {
+      const winAsset = assets.find(a => a.kind === 'nsis' || a.kind === 'msi')
+      if (!winAsset) { upMsg(`发现新版本，但未找到适用于 ${resp.arch} 的 Windows 安装包`); await openInBrowser(resp.htmlUrl); return }
+      const ok = await confirmNative(`发现新版本 ${resp.latest}（当前 ${resp.current}）${updateNotesSummary(resp)}\n是否立即下载并安装？`, '更新')
+      if (!ok) { await declineUpdate(resp); return }
       try {
-        upMsg('正在下载安装包…')
-        let savePath = ''
-        {
-          const direct = resp.assetWin.directUrl
-          const urls = [
-            'https://gh-proxy.com/' + direct,
-            'https://cdn.gh-proxy.com/' + direct,
-            'https://edgeone.gh-proxy.com/' + direct,
-            direct,
-          ]
-          let ok = false
-          for (const u of urls) {
-            try {
-              // 传 useProxy: false，避免后端二次拼接代理
-              savePath = await invoke('download_file', { url: u, useProxy: false }) as any as string
-              ok = true
-              break
-            } catch {}
-          }
-          if (!ok) throw new Error('all proxies failed')
-        }
+        const savePath = await downloadUpdateAsset(winAsset, config, resp.latest)
         upMsg('下载完成，正在启动安装…')
-        try { await invoke('run_installer', { path: savePath }); upMsg('已启动安装程序，即将关闭…'); setTimeout(() => { try { void getCurrentWindow().destroy() } catch {} }, 800) } catch (e) { showUpdateDownloadedOverlay(savePath, resp) }
+        try { await invoke('run_installer', { path: savePath, version: resp.latest }); upMsg('已启动安装程序，即将关闭…'); setTimeout(() => { try { void getCurrentWindow().destroy() } catch {} }, 800) } catch (e) { showUpdateDownloadedOverlay(savePath, resp) }
       } catch (e) {
+        if (String(e).includes('download cancelled')) { upMsg('已取消下载'); return }
+        if (String(e).startsWith('not enough disk space')) { upMsg('磁盘空间不足：' + String(e)); return }
         upMsg('下载或启动安装失败，将打开发布页');
         await openInBrowser(resp.htmlUrl)
       }
       return
     }
-    // Linux：展示选择
+    // Linux：按实际安装方式（AppImage / deb）就地更新；rpm、tar.gz、macOS 等展示下载选择
+    const linuxAsset = assets.find(a => a.kind === resp.installKind)
+    if (linuxAsset) {
+      const ok = await confirmNative(`发现新版本 ${resp.latest}（当前 ${resp.current}）${updateNotesSummary(resp)}\n是否立即下载并更新？`, '更新')
+      if (!ok) { await declineUpdate(resp); return }
+      let savePath = ''
+      try {
+        savePath = await downloadUpdateAsset(linuxAsset, config, resp.latest)
+        upMsg(resp.installKind === 'deb' ? '下载完成，正在安装（需要管理员授权）…' : '下载完成，正在替换 AppImage…')
+        const r = await invoke('run_installer', { path: savePath, version: resp.latest }) as any as InstallResult
+        upMsg(`已更新到 ${resp.latest}`)
+        if (r?.restartRequired && await confirmNative(`已更新到 ${resp.latest}，是否立即重启 flymd？`, '更新')) {
+          await invoke('restart_after_update')
+        }
+      } catch (e) {
+        if (String(e).includes('download cancelled')) { upMsg('已取消下载'); return }
+        upMsg('更新失败：' + String(e))
+        if (savePath) showUpdateDownloadedOverlay(savePath, resp); else showUpdateOverlayLinux(resp)
+      }
+      return
+    }
     showUpdateOverlayLinux(resp)
   } catch (e) {
+    // 后端以 "rate limited" 开头表示 GitHub 接口限流
+    if (String(e).startsWith('rate limited')) { upMsg('检查更新过于频繁（更新源限流），请稍后再试或在设置中配置 GitHub Token'); return }
     upMsg('检查更新失败')
   }
 }
 
-function checkUpdateSilentOnceAfterStartup() {
-  try {
-    setTimeout(async () => {
-      try {
-        const resp = await invoke('check_update', { force: false, include_prerelease: false }) as any as CheckUpdateResp
-        if (resp && resp.hasUpdate) {
-          setUpdateBadge(true, `发现新版本 v${resp.latest}`)
-        }
-      } catch {
-        // 静默失败不提示
-      }
-    }, 5000)
-  } catch {}
+// 更新设置（后端 updater.json）：通道、锁定版本、后台检查与预下载；其余字段（跳过的版本等）原样写回
+async function openUpdateSettingsInteractive() {
+  let st: any
+  try { st = await invoke('get_update_settings') } catch (e) { upMsg('读取更新设置失败：' + String(e)); return }
+  const ov = ensureUpdateOverlay()
+  const body = ov.querySelector('#update-body') as HTMLDivElement
+  const act = ov.querySelector('#update-actions') as HTMLDivElement
+  const esc = (s: string) => (s || '').replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/"/g, '&quot;')
+  const row = 'display:flex;align-items:center;gap:8px;margin-bottom:8px;'
+  body.innerHTML = `
+    <div style="${row}"><label for="upd-channel" style="min-width:96px;">更新通道</label>
+      <select id="upd-channel">
+        <option value="stable">稳定版</option>
+        <option value="security">仅修复（不跨 minor 版本）</option>
+        <option value="beta">测试版（含预发行版）</option>
+      </select></div>
+    <div style="${row}"><label for="upd-pinned" style="min-width:96px;">锁定版本</label>
+      <input id="upd-pinned" type="text" placeholder="留空不锁定，如 1.2.0" value="${esc(st?.pinned || '')}" /></div>
+    <div style="${row}"><input id="upd-auto-check" type="checkbox" /><label for="upd-auto-check">后台定时检查更新</label></div>
+    <div style="${row}"><input id="upd-auto-download" type="checkbox" /><label for="upd-auto-download">发现更新后在后台预先下载安装包</label></div>
+    <div id="upd-settings-msg" style="opacity:.8;"></div>
+  `
+  const channel = body.querySelector('#upd-channel') as HTMLSelectElement
+  const pinned = body.querySelector('#upd-pinned') as HTMLInputElement
+  const autoCheck = body.querySelector('#upd-auto-check') as HTMLInputElement
+  const autoDownload = body.querySelector('#upd-auto-download') as HTMLInputElement
+  const msg = body.querySelector('#upd-settings-msg') as HTMLDivElement
+  channel.value = st?.channel || 'stable'
+  autoCheck.checked = st?.autoCheck !== false
+  autoDownload.checked = !!st?.autoDownload
+  act.innerHTML = ''
+  const mkBtn = (label: string, onClick: () => void) => {
+    const b = document.createElement('button')
+    b.textContent = label
+    b.addEventListener('click', onClick)
+    act.appendChild(b)
+    return b
+  }
+  mkBtn('保存', () => { void (async () => {
+    const next = { ...st, channel: channel.value, pinned: pinned.value.trim() || null, autoCheck: autoCheck.checked, autoDownload: autoDownload.checked }
+    try {
+      await invoke('set_update_settings', { settings: next })
+      ov.classList.add('hidden')
+      upMsg('已保存更新设置')
+    } catch (e) { msg.textContent = '保存失败：' + String(e) }
+  })() })
+  mkBtn('取消', () => ov.classList.add('hidden'))
+  ov.classList.remove('hidden')
+}
+
+// 更新检查由后端定时执行（间隔、预下载等见 updater 设置）：监听检查结果，并补取本窗口加载前已完成的检查
+function watchBackgroundUpdates() {
+  void (async () => {
+    try {
+      const ev = await import('@tauri-apps/api/event')
+      await ev.listen('update-available', (e: any) => {
+        const r = e?.payload as CheckUpdateResp
+        if (r && r.hasUpdate) setUpdateBadge(true, `发现新版本 v${r.latest}`)
+      })
+      await ev.listen('update-downloaded', (e: any) => {
+        const d = e?.payload
+        if (d && d.version) setUpdateBadge(true, `新版本 v${d.version} 已下载，点击更新`)
+      })
+      const st = await invoke('get_update_status') as any
+      if (st?.downloaded?.version) setUpdateBadge(true, `新版本 v${st.downloaded.version} 已下载，点击更新`)
+      else if (st?.available?.hasUpdate) setUpdateBadge(true, `发现新版本 v${st.available.latest}`)
+    } catch {
+      // 静默失败不提示
+    }
+  })()
 }
 
 // 切换模式
@@ -2662,6 +2902,38 @@ async function getUploaderConfig(): Promise<UploaderConfig | null> {
   } catch { return null }
 }
 
+// 拖入的非图片附件：启用图床时优先上传，失败或未启用时复制到文档旁的 attachments/ 目录；
+// 文档尚未保存时无处可放，直接插入原文件路径。内容均由后端按路径读取
+async function insertDroppedAttachments(paths: string[]) {
+  if (paths.length === 0) return
+  const upCfg = await getUploaderConfig()
+  const parts: string[] = []
+  for (const p of paths) {
+    const name = p.split(/[\\/]+/).pop() || 'attachment'
+    if (upCfg) {
+      try {
+        const res = await uploadAttachmentToS3R2(p, name, upCfg)
+        parts.push(res.markdown)
+        continue
+      } catch (e) {
+        console.warn('附件上传失败，改为保存到本地', p, e)
+      }
+    }
+    if (currentFilePath) {
+      try {
+        const res = await invoke('save_attachment_local', { req: { docPath: currentFilePath, fileName: name, srcPath: p } }) as any
+        if (res && res.markdown) { parts.push(res.markdown); continue }
+      } catch (e) {
+        console.warn('附件保存失败', p, e)
+      }
+    }
+    const needAngle = /[\s()]/.test(p) || /^[a-zA-Z]:/.test(p) || /\\/.test(p)
+    parts.push(`[${name}](${needAngle ? `<${p}>` : p})`)
+  }
+  insertAtCursor(parts.join('\n'))
+  if (mode === 'preview') await renderPreview(); else if (wysiwyg) scheduleWysiwygRender()
+}
+
 function showUploaderOverlay(show: boolean) {
   const overlay = document.getElementById('uploader-overlay') as HTMLDivElement | null
   if (!overlay) return
@@ -3112,6 +3384,7 @@ function bindEvents() {
   if (btnToggle) btnToggle.addEventListener('click', guard(() => toggleMode()))
   if (btnWysiwyg) btnWysiwyg.addEventListener('click', guard(() => toggleWysiwyg()))
   if (btnUpdate) btnUpdate.addEventListener('click', guard(() => checkUpdateInteractive()))
+  if (btnUpdate) btnUpdate.addEventListener('contextmenu', (e) => { e.preventDefault(); void openUpdateSettingsInteractive() })
   // 代码复制按钮（事件委托）
   // 库侧栏右键菜单
   document.addEventListener('contextmenu', (ev) => {
@@ -3707,6 +3980,8 @@ function bindEvents() {
             const paths: string[] = (Array.isArray(arr) ? arr : []).map((p) => normalizePath(p))
             const md = paths.find((p) => /\.(md|markdown|txt)$/i.test(p))
             if (md) { void openFile2(md); return }
+            // 非图片附件（PDF/Office/压缩包/音视频）：与图片分开处理，不影响下方图片逻辑
+            await insertDroppedAttachments(paths.filter((p) => !/\.(png|jpe?g|gif|svg|webp|bmp|avif|ico)$/i.test(p)))
             const imgs = paths.filter((p) => /\.(png|jpe?g|gif|svg|webp|bmp|avif|ico)$/i.test(p))
             if (imgs.length > 0) {
               // Always-save-local: prefer local images folder for dropped files
@@ -3865,8 +4140,8 @@ function bindEvents() {
     } catch {}
     console.log('应用初始化完成')
     void logInfo('flyMD (飞速MarkDown) 应用初始化完成')
-    // 启动后 5 秒进行一次静默检查，仅加红点提示
-    checkUpdateSilentOnceAfterStartup()
+    // 更新检查与预下载由后端定时执行，这里只监听结果并在更新按钮上加红点提示
+    watchBackgroundUpdates()
   } catch (error) {
     console.error('应用启动失败:', error)
     showError('应用启动失败', error)
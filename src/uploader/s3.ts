@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core'
+import { readFile } from '@tauri-apps/plugin-fs'
 // 直连 S3/R2（SigV4）最小实现：
 // - 支持 path-style 与自定义域名
 // - 默认模板 {year}/{month}{fileName}{md5}.{extName}
@@ -15,6 +16,9 @@ export type UploaderConfig = {
   keyTemplate?: string
   aclPublicRead?: boolean
   forcePathStyle?: boolean
+  // 上传策略（后端按文件头校验）：允许的 MIME 列表与单文件大小上限（字节）
+  allowedTypes?: string[]
+  maxBytes?: number
 }
 
 function isTauriRuntime(): boolean {
@@ -293,11 +297,15 @@ export async function uploadImageToS3R2(input: Blob | ArrayBuffer | Uint8Array,
           customDomain: cfg.customDomain,
           key,
           contentType,
+          allowedTypes: cfg.allowedTypes,
+          maxBytes: cfg.maxBytes,
           bytes: Array.from(new Uint8Array(bytes))
         }
       })
       return { key: resp.key, publicUrl: resp.public_url }
     } catch (e) {
+      // 策略拒绝（类型不符/超限/非法 SVG）不走兜底，避免绕过后端校验
+      if (String(e).startsWith('upload policy:')) throw new Error(String(e))
       console.warn('upload_to_s3 (sdk) failed, fallback to presign', e)
       // 方案B 作为兜底：预签名 + PUT（插件/浏览器）
       try {
@@ -398,3 +406,31 @@ export async function uploadImageToS3R2(input: Blob | ArrayBuffer | Uint8Array,
   return { key, publicUrl }
 }
 
+// 通用附件（PDF/Office/压缩包/音视频）：由后端嗅探类型、校验大小并生成匹配的 Markdown
+export type AttachmentResult = { key: string; url: string; kind: string; contentType: string; markdown: string }
+
+export async function uploadAttachmentToS3R2(path: string, fileName: string, cfg: UploaderConfig): Promise<AttachmentResult> {
+  if (!cfg || !cfg.enabled) throw new Error('uploader disabled')
+  if (!cfg.accessKeyId || !cfg.secretAccessKey || !cfg.bucket) throw new Error('uploader config incomplete')
+  const endpointUrl = ensureEndpointUrl(cfg.endpoint)
+  const region = guessRegionForR2(endpointUrl.host, cfg.region)
+  const template = cfg.keyTemplate || '{year}/{month}{fileName}{md5}.{extName}'
+  // 文件内容由后端按 srcPath 读取；仅当模板需要 {md5} 时才在前端读一次
+  const bytes = /\{md5\}/.test(template) ? (await readFile(path)).slice().buffer : new ArrayBuffer(0)
+  const key = await makeKeyFromTemplate(template, fileName, '', bytes)
+  return await invoke<AttachmentResult>('upload_attachment', {
+    req: {
+      accessKeyId: cfg.accessKeyId,
+      secretAccessKey: cfg.secretAccessKey,
+      bucket: cfg.bucket,
+      region,
+      endpoint: cfg.endpoint,
+      forcePathStyle: cfg.forcePathStyle !== false,
+      aclPublicRead: cfg.aclPublicRead !== false,
+      customDomain: cfg.customDomain,
+      key,
+      fileName,
+      srcPath: path
+    }
+  })
+}